@@ -0,0 +1,24 @@
+use super::repos_root;
+use gitpow_rust::config::Config;
+use gitpow_rust::models::{CloneRequest, CloneStatus};
+use gitpow_rust::services::clone;
+use std::sync::Mutex;
+use tauri::State;
+
+#[tauri::command]
+pub fn start_clone(
+    req: CloneRequest,
+    config: State<'_, Mutex<Config>>,
+) -> Result<CloneStatus, String> {
+    clone::start_clone(&repos_root(&config), &req).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_clone_status(id: String) -> Result<CloneStatus, String> {
+    clone::clone_status(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn cancel_clone(id: String) -> Result<CloneStatus, String> {
+    clone::cancel_clone(&id).map_err(|e| e.to_string())
+}
@@ -0,0 +1,63 @@
+use super::{repos_root, run_blocking};
+use gitpow_rust::config::Config;
+use gitpow_rust::models::{CompareFilesResponse, DiffResponse, DiffSettings};
+use gitpow_rust::services::compare;
+use serde::Deserialize;
+use std::sync::Mutex;
+use tauri::State;
+
+#[derive(Deserialize)]
+pub struct GetCompareFilesParams {
+    repo: String,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct GetCompareDiffParams {
+    repo: String,
+    from: Option<String>,
+    to: Option<String>,
+    path: String,
+    #[serde(default, rename = "oldPath")]
+    old_path: Option<String>,
+    #[serde(flatten)]
+    options: DiffSettings,
+}
+
+#[tauri::command]
+pub async fn get_compare_files(
+    params: GetCompareFilesParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<CompareFilesResponse, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || {
+        compare::get_compare_files(
+            &repos_root,
+            &params.repo,
+            params.from.as_deref(),
+            params.to.as_deref(),
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_compare_diff(
+    params: GetCompareDiffParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<DiffResponse, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || {
+        compare::get_compare_diff(
+            &repos_root,
+            &params.repo,
+            params.from.as_deref(),
+            params.to.as_deref(),
+            &params.path,
+            params.old_path.as_deref(),
+            &params.options,
+        )
+    })
+    .await
+}
@@ -0,0 +1,66 @@
+use super::{repos_root, run_blocking};
+use gitpow_rust::config::Config;
+use gitpow_rust::models::{
+    CleanRequest, DiscardRequest, DiscardResponse, DiscardRestoreRequest, DiscardSnapshot,
+};
+use gitpow_rust::services::discard;
+use serde::Deserialize;
+use std::sync::Mutex;
+use tauri::State;
+
+#[derive(Deserialize)]
+pub struct DiscardParams {
+    repo: String,
+    #[serde(flatten)]
+    req: DiscardRequest,
+}
+
+#[derive(Deserialize)]
+pub struct CleanParams {
+    repo: String,
+    #[serde(flatten)]
+    req: CleanRequest,
+}
+
+#[derive(Deserialize)]
+pub struct RestoreParams {
+    repo: String,
+    #[serde(flatten)]
+    req: DiscardRestoreRequest,
+}
+
+#[tauri::command]
+pub async fn discard_changes(
+    params: DiscardParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<DiscardResponse, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || discard::discard(&repos_root, &params.repo, &params.req)).await
+}
+
+#[tauri::command]
+pub async fn clean(
+    params: CleanParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<DiscardResponse, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || discard::clean(&repos_root, &params.repo, &params.req)).await
+}
+
+#[tauri::command]
+pub async fn get_discard_snapshots(
+    repo: String,
+    config: State<'_, Mutex<Config>>,
+) -> Result<Vec<DiscardSnapshot>, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || discard::get_snapshots(&repos_root, &repo)).await
+}
+
+#[tauri::command]
+pub async fn restore_discarded(
+    params: RestoreParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<DiscardResponse, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || discard::restore(&repos_root, &params.repo, &params.req)).await
+}
@@ -1,29 +1,95 @@
+use super::{repos_root, run_blocking};
 use gitpow_rust::config::Config;
-use gitpow_rust::git::repository::GitRepository;
-use gitpow_rust::utils::get_repo_path;
-use std::path::PathBuf;
+use gitpow_rust::models::{FetchPolicy, RepoFetchStatus, ShallowFetchRequest, ShallowInfo};
+use gitpow_rust::services::{fetch, resolve_root};
+use serde::Deserialize;
 use std::sync::Mutex;
 use tauri::State;
 
+#[derive(Deserialize)]
+pub struct GetFetchStatusParams {
+    #[serde(default)]
+    repos_root: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SetFetchPolicyParams {
+    repo: String,
+    enabled: bool,
+    #[serde(default)]
+    interval_secs: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct DeepenHistoryParams {
+    repo: String,
+    #[serde(default)]
+    deepen: Option<u32>,
+    #[serde(default)]
+    since: Option<String>,
+    #[serde(default)]
+    unshallow: bool,
+}
+
 #[tauri::command]
-pub fn fetch_repo(
+pub async fn fetch_repo(
     repo: String,
     config: State<'_, Mutex<Config>>,
 ) -> Result<String, String> {
-    let config = config.lock().unwrap();
-    let repo_path = get_repo_path(&repo, &config.repos_root);
+    let repos_root = repos_root(&config);
+    run_blocking(move || fetch::fetch_repo(&repos_root, &repo)).await
+}
+
+#[tauri::command]
+pub async fn get_fetch_status(
+    params: Option<GetFetchStatusParams>,
+    config: State<'_, Mutex<Config>>,
+) -> Result<Vec<RepoFetchStatus>, String> {
+    let custom_root = params.and_then(|p| p.repos_root);
+    let repos_root = resolve_root(custom_root.as_deref(), &repos_root(&config));
+    run_blocking(move || fetch::get_fetch_status(&repos_root)).await
+}
 
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return Err("Repository not found".to_string());
-    }
+#[tauri::command]
+pub fn get_fetch_policy(
+    repo: String,
+    config: State<'_, Mutex<Config>>,
+) -> Result<FetchPolicy, String> {
+    Ok(fetch::get_fetch_policy(&repos_root(&config), &repo))
+}
 
-    match GitRepository::open(&repo_path) {
-        Ok(repo) => match repo.fetch_all() {
-            Ok(_) => Ok("Fetch successful".to_string()),
-            Err(e) => Err(format!("Failed to fetch: {}", e)),
-        },
-        Err(e) => Err(format!("Failed to open repository: {}", e)),
-    }
+#[tauri::command]
+pub fn set_fetch_policy(
+    params: SetFetchPolicyParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<FetchPolicy, String> {
+    fetch::set_fetch_policy(
+        &repos_root(&config),
+        &params.repo,
+        params.enabled,
+        params.interval_secs,
+    )
+    .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub fn get_shallow_info(
+    repo: String,
+    config: State<'_, Mutex<Config>>,
+) -> Result<ShallowInfo, String> {
+    fetch::get_shallow_info(&repos_root(&config), &repo).map_err(|e| e.to_string())
+}
 
+#[tauri::command]
+pub async fn deepen_history(
+    params: DeepenHistoryParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<ShallowInfo, String> {
+    let repos_root = repos_root(&config);
+    let req = ShallowFetchRequest {
+        deepen: params.deepen,
+        since: params.since,
+        unshallow: params.unshallow,
+    };
+    run_blocking(move || fetch::deepen_history(&repos_root, &params.repo, &req)).await
+}
@@ -1,6 +1,11 @@
+use super::{repos_root, run_blocking};
 use gitpow_rust::config::Config;
-use gitpow_rust::git::repository::GitRepository;
-use gitpow_rust::utils::get_repo_path;
+use gitpow_rust::models::{
+    DiffResponse, DiffSettings, GitOperationResponse, StashApplyResponse, StashBranchRequest,
+    StashDetail, StashListResponse, StashPushRequest,
+};
+use gitpow_rust::services::git_ops;
+use serde::Deserialize;
 use std::sync::Mutex;
 use tauri::State;
 
@@ -8,119 +13,143 @@ use tauri::State;
 pub fn pull_repo(
     repo: String,
     config: State<'_, Mutex<Config>>,
-) -> Result<serde_json::Value, String> {
-    let config = config.lock().unwrap();
-    let repo_path = get_repo_path(&repo, &config.repos_root);
-
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return Err("Repository not found".to_string());
-    }
-
-    match GitRepository::open(&repo_path) {
-        Ok(repo) => match repo.pull() {
-            Ok(output) => Ok(serde_json::json!({
-                "success": true,
-                "message": "Pull successful",
-                "output": output
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "error": format!("Failed to pull: {}", e),
-                "message": format!("Pull failed: {}", e)
-            })),
-        },
-        Err(e) => Err(format!("Failed to open repository: {}", e)),
-    }
+) -> Result<GitOperationResponse, String> {
+    git_ops::pull_repo(&repos_root(&config), &repo).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn push_repo(
     repo: String,
     config: State<'_, Mutex<Config>>,
-) -> Result<serde_json::Value, String> {
-    let config = config.lock().unwrap();
-    let repo_path = get_repo_path(&repo, &config.repos_root);
-
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return Err("Repository not found".to_string());
-    }
+) -> Result<GitOperationResponse, String> {
+    git_ops::push_repo(&repos_root(&config), &repo).map_err(|e| e.to_string())
+}
 
-    match GitRepository::open(&repo_path) {
-        Ok(repo) => match repo.push() {
-            Ok(output) => Ok(serde_json::json!({
-                "success": true,
-                "message": "Push successful",
-                "output": output
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "error": format!("Failed to push: {}", e),
-                "message": format!("Push failed: {}", e)
-            })),
-        },
-        Err(e) => Err(format!("Failed to open repository: {}", e)),
-    }
+#[tauri::command]
+pub fn stash_list(
+    repo: String,
+    config: State<'_, Mutex<Config>>,
+) -> Result<StashListResponse, String> {
+    git_ops::stash_list(&repos_root(&config), &repo).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn stash_push(
     repo: String,
     message: Option<String>,
+    options: Option<StashPushRequest>,
     config: State<'_, Mutex<Config>>,
-) -> Result<serde_json::Value, String> {
-    let config = config.lock().unwrap();
-    let repo_path = get_repo_path(&repo, &config.repos_root);
-
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return Err("Repository not found".to_string());
+) -> Result<GitOperationResponse, String> {
+    let mut req = options.unwrap_or_default();
+    if req.message.is_none() {
+        req.message = message;
     }
+    git_ops::stash_push(&repos_root(&config), &repo, &req).map_err(|e| e.to_string())
+}
 
-    match GitRepository::open(&repo_path) {
-        Ok(repo) => {
-            let msg = message.as_deref();
-            match repo.stash_push(msg) {
-                Ok(output) => Ok(serde_json::json!({
-                    "success": true,
-                    "message": "Changes stashed",
-                    "output": output
-                })),
-                Err(e) => Ok(serde_json::json!({
-                    "success": false,
-                    "error": format!("Failed to stash: {}", e),
-                    "message": format!("Stash failed: {}", e)
-                })),
-            }
-        }
-        Err(e) => Err(format!("Failed to open repository: {}", e)),
-    }
+#[tauri::command(rename_all = "snake_case")]
+pub fn stash_pop(
+    repo: String,
+    stash_ref: Option<String>,
+    index: Option<bool>,
+    config: State<'_, Mutex<Config>>,
+) -> Result<StashApplyResponse, String> {
+    git_ops::stash_pop(
+        &repos_root(&config),
+        &repo,
+        stash_ref.as_deref(),
+        index.unwrap_or(false),
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub fn stash_apply(
+    repo: String,
+    stash_ref: Option<String>,
+    index: Option<bool>,
+    config: State<'_, Mutex<Config>>,
+) -> Result<StashApplyResponse, String> {
+    git_ops::stash_apply(
+        &repos_root(&config),
+        &repo,
+        stash_ref.as_deref(),
+        index.unwrap_or(false),
+    )
+    .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn stash_pop(
+pub fn stash_branch(
     repo: String,
+    req: StashBranchRequest,
     config: State<'_, Mutex<Config>>,
-) -> Result<serde_json::Value, String> {
-    let config = config.lock().unwrap();
-    let repo_path = get_repo_path(&repo, &config.repos_root);
+) -> Result<StashApplyResponse, String> {
+    git_ops::stash_branch(
+        &repos_root(&config),
+        &repo,
+        &req.branch,
+        req.stash_ref.as_deref(),
+    )
+    .map_err(|e| e.to_string())
+}
 
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return Err("Repository not found".to_string());
-    }
+#[tauri::command(rename_all = "snake_case")]
+pub fn stash_drop(
+    repo: String,
+    stash_ref: Option<String>,
+    config: State<'_, Mutex<Config>>,
+) -> Result<GitOperationResponse, String> {
+    git_ops::stash_drop(&repos_root(&config), &repo, stash_ref.as_deref())
+        .map_err(|e| e.to_string())
+}
 
-    match GitRepository::open(&repo_path) {
-        Ok(repo) => match repo.stash_pop() {
-            Ok(output) => Ok(serde_json::json!({
-                "success": true,
-                "message": "Stash popped",
-                "output": output
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "error": format!("Failed to pop stash: {}", e),
-                "message": format!("Stash pop failed: {}", e)
-            })),
-        },
-        Err(e) => Err(format!("Failed to open repository: {}", e)),
-    }
+#[derive(Deserialize)]
+pub struct StashShowParams {
+    repo: String,
+    #[serde(default)]
+    stash_ref: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct StashDiffParams {
+    repo: String,
+    #[serde(default)]
+    stash_ref: Option<String>,
+    path: String,
+    #[serde(default)]
+    part: Option<String>,
+    #[serde(flatten)]
+    options: DiffSettings,
+}
+
+#[tauri::command]
+pub async fn stash_show(
+    params: StashShowParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<StashDetail, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || {
+        git_ops::stash_show(&repos_root, &params.repo, params.stash_ref.as_deref())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn stash_diff(
+    params: StashDiffParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<DiffResponse, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || {
+        git_ops::stash_diff(
+            &repos_root,
+            &params.repo,
+            params.stash_ref.as_deref(),
+            &params.path,
+            params.part.as_deref(),
+            &params.options,
+        )
+    })
+    .await
+}
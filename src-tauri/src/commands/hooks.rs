@@ -0,0 +1,31 @@
+use super::repos_root;
+use gitpow_rust::config::Config;
+use gitpow_rust::models::{HookInfo, HookListResponse, HookToggleRequest};
+use gitpow_rust::services::hooks;
+use serde::Deserialize;
+use std::sync::Mutex;
+use tauri::State;
+
+#[derive(Deserialize)]
+pub struct SetHookEnabledParams {
+    repo: String,
+    #[serde(flatten)]
+    req: HookToggleRequest,
+}
+
+#[tauri::command]
+pub fn get_hooks(
+    repo: String,
+    config: State<'_, Mutex<Config>>,
+) -> Result<HookListResponse, String> {
+    hooks::get_hooks(&repos_root(&config), &repo).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_hook_enabled(
+    params: SetHookEnabledParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<HookInfo, String> {
+    hooks::set_hook_enabled(&repos_root(&config), &params.repo, &params.req)
+        .map_err(|e| e.to_string())
+}
@@ -1,28 +1,75 @@
 pub mod branches;
 pub mod browse;
+pub mod clone;
 pub mod commits;
+pub mod compare;
 pub mod conflicts;
 pub mod diff;
+pub mod discard;
 pub mod explorer;
 pub mod fetch;
 pub mod files;
 pub mod git_ops;
+pub mod hooks;
+pub mod patch;
 pub mod rebase;
 pub mod repos;
 pub mod staging;
+pub mod submodules;
+pub mod worktrees;
+
+use gitpow_rust::config::Config;
+use gitpow_rust::services::ServiceResult;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::State;
 
 // Re-export all command functions
 pub use branches::{get_branches, get_branch_ahead_behind, get_branch_creation, get_branch_status};
 pub use browse::browse_projects_root;
-pub use commits::{get_commits, get_commits_all_branches, get_commits_between, get_commit_metrics, get_tags};
+pub use clone::{cancel_clone, get_clone_status, start_clone};
+pub use commits::{
+    create_tag, get_commit_detail, get_commit_metrics, get_commits, get_commits_all_branches,
+    get_commits_between, get_signing_config, get_tags,
+};
+pub use compare::{get_compare_diff, get_compare_files};
 pub use conflicts::{get_conflicts, get_conflict_file, resolve_conflict};
 pub use diff::get_diff;
+pub use discard::{clean, discard_changes, get_discard_snapshots, restore_discarded};
 pub use explorer::open_explorer;
-pub use fetch::fetch_repo;
+pub use fetch::{
+    deepen_history, fetch_repo, get_fetch_policy, get_fetch_status, get_shallow_info,
+    set_fetch_policy,
+};
 pub use files::{get_files, get_commit_files, get_file, get_file_creation, get_file_creation_batch, get_image};
-pub use git_ops::{pull_repo, push_repo, stash_pop, stash_push};
+pub use git_ops::{
+    pull_repo, push_repo, stash_apply, stash_branch, stash_diff, stash_drop, stash_list,
+    stash_pop, stash_push, stash_show,
+};
+pub use hooks::{get_hooks, set_hook_enabled};
+pub use patch::{apply_patch, format_patch, get_patch};
 pub use rebase::{get_rebase_preview, post_rebase_plan};
 pub use repos::{get_config, get_repos};
-pub use staging::{get_status, stage, unstage, commit};
+pub use staging::{
+    get_status, add_ignore_pattern, stage, unstage, commit, lint_commit_message, get_commit_lint_rules,
+    set_commit_lint_rules,
+};
+pub use submodules::{get_submodules, submodule_init, submodule_sync, submodule_update};
+pub use worktrees::{add_worktree, get_worktrees, prune_worktrees, remove_worktree};
 
+/// Snapshot of the configured repos root, so the lock is not held across awaits
+pub(crate) fn repos_root(config: &State<'_, Mutex<Config>>) -> PathBuf {
+    config.lock().unwrap().repos_root.clone()
+}
 
+/// Run a blocking service call off the async runtime and flatten its error to a string
+pub(crate) async fn run_blocking<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> ServiceResult<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| e.to_string())
+}
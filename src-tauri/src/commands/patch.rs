@@ -0,0 +1,61 @@
+use super::{repos_root, run_blocking};
+use gitpow_rust::config::Config;
+use gitpow_rust::models::{
+    DiffSettings, FormatPatchRequest, FormatPatchResponse, PatchApplyRequest, PatchApplyResponse,
+    PatchResponse,
+};
+use gitpow_rust::services::patch;
+use serde::Deserialize;
+use std::sync::Mutex;
+use tauri::State;
+
+#[derive(Deserialize)]
+pub struct GetPatchParams {
+    repo: String,
+    #[serde(rename = "ref")]
+    ref_: String,
+    #[serde(flatten)]
+    options: DiffSettings,
+}
+
+#[derive(Deserialize)]
+pub struct FormatPatchParams {
+    repo: String,
+    #[serde(flatten)]
+    req: FormatPatchRequest,
+}
+
+#[derive(Deserialize)]
+pub struct ApplyPatchParams {
+    repo: String,
+    #[serde(flatten)]
+    req: PatchApplyRequest,
+}
+
+#[tauri::command]
+pub async fn get_patch(
+    params: GetPatchParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<PatchResponse, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || patch::get_patch(&repos_root, &params.repo, &params.ref_, &params.options))
+        .await
+}
+
+#[tauri::command]
+pub async fn format_patch(
+    params: FormatPatchParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<FormatPatchResponse, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || patch::format_patch(&repos_root, &params.repo, &params.req)).await
+}
+
+#[tauri::command]
+pub async fn apply_patch(
+    params: ApplyPatchParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<PatchApplyResponse, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || patch::apply_patch(&repos_root, &params.repo, &params.req)).await
+}
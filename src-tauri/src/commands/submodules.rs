@@ -0,0 +1,59 @@
+use super::{repos_root, run_blocking};
+use gitpow_rust::config::Config;
+use gitpow_rust::models::{SubmoduleInfo, SubmoduleUpdateRequest};
+use gitpow_rust::services::submodules::{self, SubmoduleAction};
+use serde::Deserialize;
+use std::sync::Mutex;
+use tauri::State;
+
+#[derive(Deserialize)]
+pub struct SubmoduleActionParams {
+    repo: String,
+    #[serde(flatten)]
+    req: SubmoduleUpdateRequest,
+}
+
+#[tauri::command]
+pub async fn get_submodules(
+    repo: String,
+    config: State<'_, Mutex<Config>>,
+) -> Result<Vec<SubmoduleInfo>, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || submodules::get_submodules(&repos_root, &repo)).await
+}
+
+async fn run_submodule_action(
+    params: SubmoduleActionParams,
+    config: State<'_, Mutex<Config>>,
+    action: SubmoduleAction,
+) -> Result<Vec<SubmoduleInfo>, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || {
+        submodules::run_submodule_action(&repos_root, &params.repo, &params.req, action)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn submodule_init(
+    params: SubmoduleActionParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<Vec<SubmoduleInfo>, String> {
+    run_submodule_action(params, config, SubmoduleAction::Init).await
+}
+
+#[tauri::command]
+pub async fn submodule_update(
+    params: SubmoduleActionParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<Vec<SubmoduleInfo>, String> {
+    run_submodule_action(params, config, SubmoduleAction::Update).await
+}
+
+#[tauri::command]
+pub async fn submodule_sync(
+    params: SubmoduleActionParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<Vec<SubmoduleInfo>, String> {
+    run_submodule_action(params, config, SubmoduleAction::Sync).await
+}
@@ -0,0 +1,77 @@
+use super::{repos_root, run_blocking};
+use gitpow_rust::config::Config;
+use gitpow_rust::models::{WorktreeAddRequest, WorktreeInfo, WorktreeRemoveRequest};
+use gitpow_rust::services::worktrees::{self, WorktreeAction};
+use serde::Deserialize;
+use std::sync::Mutex;
+use tauri::State;
+
+#[derive(Deserialize)]
+pub struct AddWorktreeParams {
+    repo: String,
+    path: String,
+    #[serde(default)]
+    branch: Option<String>,
+    #[serde(default)]
+    commit: Option<String>,
+    #[serde(default)]
+    new_branch: Option<String>,
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Deserialize)]
+pub struct RemoveWorktreeParams {
+    repo: String,
+    #[serde(flatten)]
+    req: WorktreeRemoveRequest,
+}
+
+async fn run_worktree_action(
+    repo: String,
+    config: State<'_, Mutex<Config>>,
+    action: WorktreeAction,
+) -> Result<Vec<WorktreeInfo>, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || worktrees::run_worktree_action(&repos_root, &repo, action)).await
+}
+
+#[tauri::command]
+pub async fn get_worktrees(
+    repo: String,
+    config: State<'_, Mutex<Config>>,
+) -> Result<Vec<WorktreeInfo>, String> {
+    let repos_root = repos_root(&config);
+    run_blocking(move || worktrees::get_worktrees(&repos_root, &repo)).await
+}
+
+#[tauri::command]
+pub async fn add_worktree(
+    params: AddWorktreeParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<Vec<WorktreeInfo>, String> {
+    let req = WorktreeAddRequest {
+        path: params.path,
+        branch: params.branch,
+        commit: params.commit,
+        new_branch: params.new_branch,
+        force: params.force,
+    };
+    run_worktree_action(params.repo, config, WorktreeAction::Add(req)).await
+}
+
+#[tauri::command]
+pub async fn remove_worktree(
+    params: RemoveWorktreeParams,
+    config: State<'_, Mutex<Config>>,
+) -> Result<Vec<WorktreeInfo>, String> {
+    run_worktree_action(params.repo, config, WorktreeAction::Remove(params.req)).await
+}
+
+#[tauri::command]
+pub async fn prune_worktrees(
+    repo: String,
+    config: State<'_, Mutex<Config>>,
+) -> Result<Vec<WorktreeInfo>, String> {
+    run_worktree_action(repo, config, WorktreeAction::Prune).await
+}
@@ -1,4 +1,5 @@
 use gitpow_rust::config::Config;
+use gitpow_rust::git::fetch_scheduler::{run_scheduler, FETCH_SCHEDULER};
 use std::sync::Mutex;
 use tauri::State;
 
@@ -24,8 +25,16 @@ pub fn run() {
     let config = Config::init();
     tracing::info!("Repos root: {:?}", config.repos_root);
 
+    // Background fetch scheduler for every repo under repos_root
+    FETCH_SCHEDULER.set_default_interval(config.fetch_interval_secs);
+    let scheduler_root = config.repos_root.clone();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .setup(move |_app| {
+            tauri::async_runtime::spawn(run_scheduler(scheduler_root.clone()));
+            Ok(())
+        })
         .manage(Mutex::new(config))
         .invoke_handler(tauri::generate_handler![
             // Config
@@ -33,6 +42,10 @@ pub fn run() {
             // Repos
             commands::repos::get_repos,
             commands::browse::browse_projects_root,
+            // Clone
+            commands::clone::start_clone,
+            commands::clone::get_clone_status,
+            commands::clone::cancel_clone,
             // Branches
             commands::branches::get_branches,
             commands::branches::get_branch_ahead_behind,
@@ -44,6 +57,9 @@ pub fn run() {
             commands::commits::get_commits_between,
             commands::commits::get_commit_metrics,
             commands::commits::get_tags,
+            commands::commits::get_commit_detail,
+            commands::commits::create_tag,
+            commands::commits::get_signing_config,
             // Files
             commands::files::get_files,
             commands::files::get_commit_files,
@@ -53,18 +69,55 @@ pub fn run() {
             commands::files::get_image,
             // Diff
             commands::diff::get_diff,
+            commands::compare::get_compare_files,
+            commands::compare::get_compare_diff,
             // Staging
             commands::staging::get_status,
+            commands::staging::add_ignore_pattern,
             commands::staging::stage,
             commands::staging::unstage,
             commands::staging::commit,
+            commands::hooks::get_hooks,
+            commands::hooks::set_hook_enabled,
+            commands::patch::get_patch,
+            commands::patch::format_patch,
+            commands::patch::apply_patch,
+            commands::staging::lint_commit_message,
+            commands::staging::get_commit_lint_rules,
+            commands::staging::set_commit_lint_rules,
+            // Discard
+            commands::discard::discard_changes,
+            commands::discard::clean,
+            commands::discard::get_discard_snapshots,
+            commands::discard::restore_discarded,
+            // Submodules
+            commands::submodules::get_submodules,
+            commands::submodules::submodule_init,
+            commands::submodules::submodule_update,
+            commands::submodules::submodule_sync,
+            // Worktrees
+            commands::worktrees::get_worktrees,
+            commands::worktrees::add_worktree,
+            commands::worktrees::remove_worktree,
+            commands::worktrees::prune_worktrees,
             // Fetch
             commands::fetch::fetch_repo,
+            commands::fetch::get_fetch_status,
+            commands::fetch::get_fetch_policy,
+            commands::fetch::set_fetch_policy,
+            commands::fetch::get_shallow_info,
+            commands::fetch::deepen_history,
             // Git Operations
             commands::git_ops::pull_repo,
             commands::git_ops::push_repo,
+            commands::git_ops::stash_list,
             commands::git_ops::stash_push,
             commands::git_ops::stash_pop,
+            commands::git_ops::stash_apply,
+            commands::git_ops::stash_drop,
+            commands::git_ops::stash_branch,
+            commands::git_ops::stash_show,
+            commands::git_ops::stash_diff,
             // Rebase
             commands::rebase::get_rebase_preview,
             commands::rebase::post_rebase_plan,
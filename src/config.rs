@@ -1,10 +1,14 @@
 use std::env;
 use std::path::PathBuf;
 
+use crate::git::fetch_scheduler::DEFAULT_FETCH_INTERVAL_SECS;
+
 #[derive(Clone)]
 pub struct Config {
     pub repos_root: PathBuf,
     pub port: String,
+    /// Default background fetch interval in seconds (0 disables background fetch)
+    pub fetch_interval_secs: u64,
 }
 
 impl Config {
@@ -20,6 +24,15 @@ impl Config {
 
         let port = env::var("PORT").unwrap_or_else(|_| "3000".to_string());
 
-        Self { repos_root, port }
+        let fetch_interval_secs = env::var("FETCH_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_FETCH_INTERVAL_SECS);
+
+        Self {
+            repos_root,
+            port,
+            fetch_interval_secs,
+        }
     }
 }
@@ -0,0 +1,361 @@
+//! Clone jobs.
+//!
+//! Clones run `git clone --progress` on a worker thread so the client can poll for
+//! progress and cancel. The CLI is used (like pull/push) so the user's credential
+//! helpers and the full set of transports, including shallow clones over `file://`,
+//! behave exactly as they do in a terminal.
+
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::git::discovery::repo_entry;
+use crate::git::fetch_scheduler::FETCH_SCHEDULER;
+use crate::models::{CloneRequest, CloneStatus};
+
+static CLONE_JOBS: Lazy<Mutex<HashMap<String, Arc<CloneJob>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static NEXT_CLONE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// How long a finished job's status stays available to poll
+const FINISHED_JOB_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Matches git's progress lines, e.g. "Receiving objects:  45% (450/1000), 1.2 MiB | ..."
+static PROGRESS_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:remote: )?([A-Za-z ]+):\s+(\d+)% \((\d+)/(\d+)\)").unwrap());
+
+struct CloneJob {
+    status: Mutex<CloneStatus>,
+    child: Mutex<Option<Child>>,
+    cancelled: AtomicBool,
+    finished_at: Mutex<Option<Instant>>,
+}
+
+impl CloneJob {
+    fn is_expired(&self) -> bool {
+        self.finished_at
+            .lock()
+            .unwrap()
+            .is_some_and(|at| at.elapsed() > FINISHED_JOB_TTL)
+    }
+}
+
+/// Forget jobs that finished longer than `FINISHED_JOB_TTL` ago
+fn prune_jobs(jobs: &mut HashMap<String, Arc<CloneJob>>) {
+    jobs.retain(|_, job| !job.is_expired());
+}
+
+/// Kill git and the helpers it started (remote-https, index-pack), which
+/// would otherwise keep writing into the target
+fn kill_tree(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        // The child leads its own process group, see `execute_clone`
+        unsafe {
+            libc::kill(-(child.id() as i32), libc::SIGKILL);
+        }
+    }
+    #[cfg(windows)]
+    {
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &child.id().to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+    let _ = child.kill();
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+    path.is_dir()
+        && fs::read_dir(path)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false)
+}
+
+/// Validate the target directory and resolve it under `repos_root`.
+fn resolve_target(repos_root: &Path, directory: &str) -> Result<PathBuf> {
+    let relative = Path::new(directory.trim());
+    if relative.as_os_str().is_empty() {
+        bail!("target directory is required");
+    }
+    // Only direct children of repos_root show up in the repo list
+    let mut components = relative.components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        bail!("target directory must be a folder name directly inside the repos root");
+    }
+
+    Ok(repos_root.join(relative))
+}
+
+/// Claim the target directory: create it, or accept an existing empty one.
+/// Returns whether it was created. Creating it before git starts leaves no
+/// gap for something else to appear there.
+fn claim_target(target: &Path) -> Result<bool> {
+    match fs::create_dir(target) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            if !is_empty_dir(target) {
+                bail!(
+                    "{} already exists and is not an empty directory",
+                    target.display()
+                );
+            }
+            Ok(false)
+        }
+        Err(e) => Err(e).with_context(|| format!("Failed to create {}", target.display())),
+    }
+}
+
+/// Local paths are cloned through `file://` when a depth is requested, because
+/// git ignores `--depth` for plain-path local clones.
+fn clone_source(url: &str, shallow: bool) -> String {
+    let path = Path::new(url);
+    if shallow && !url.contains("://") && path.is_absolute() && path.exists() {
+        let normalized = url.replace('\\', "/");
+        if normalized.starts_with('/') {
+            format!("file://{}", normalized)
+        } else {
+            format!("file:///{}", normalized)
+        }
+    } else {
+        url.to_string()
+    }
+}
+
+fn update_progress(job: &CloneJob, line: &str) {
+    let line = line.trim();
+    if line.is_empty() {
+        return;
+    }
+    let mut status = job.status.lock().unwrap();
+    if let Some(caps) = PROGRESS_RE.captures(line) {
+        status.phase = Some(caps[1].trim().to_string());
+        status.percent = caps[2].parse().ok();
+        status.current = caps[3].parse().ok();
+        status.total = caps[4].parse().ok();
+    } else {
+        status.message = Some(line.to_string());
+    }
+}
+
+fn run_clone(job: Arc<CloneJob>, args: Vec<String>, target: PathBuf, created_dir: bool) {
+    execute_clone(&job, args, &target, created_dir);
+    *job.finished_at.lock().unwrap() = Some(Instant::now());
+}
+
+fn execute_clone(job: &CloneJob, args: Vec<String>, target: &Path, created_dir: bool) {
+    let mut command = Command::new("git");
+    // In a process group of its own, so cancelling can kill git's helpers too
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let spawned = command
+        .args(&args)
+        // Never block on an interactive credential prompt
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn();
+
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => {
+            let mut status = job.status.lock().unwrap();
+            status.state = "failed".to_string();
+            status.error = Some(format!("Failed to run git clone: {}", e));
+            return;
+        }
+    };
+
+    let stderr = child.stderr.take();
+    // A cancel that arrived before the child was stored could not kill it
+    if job.cancelled.load(Ordering::SeqCst) {
+        kill_tree(&mut child);
+    }
+    *job.child.lock().unwrap() = Some(child);
+
+    // git rewrites progress lines in place with '\r', so split on both separators
+    let mut last_line = String::new();
+    if let Some(mut stderr) = stderr {
+        let mut buf = [0u8; 4096];
+        let mut pending: Vec<u8> = Vec::new();
+        while let Ok(n) = stderr.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            for &b in &buf[..n] {
+                if b == b'\r' || b == b'\n' {
+                    let line = String::from_utf8_lossy(&pending).to_string();
+                    if !line.trim().is_empty() {
+                        update_progress(job, &line);
+                        last_line = line;
+                    }
+                    pending.clear();
+                } else {
+                    pending.push(b);
+                }
+            }
+        }
+        if !pending.is_empty() {
+            last_line = String::from_utf8_lossy(&pending).to_string();
+        }
+    }
+
+    let exit = job
+        .child
+        .lock()
+        .unwrap()
+        .take()
+        .map(|mut child| child.wait());
+
+    let succeeded = matches!(exit, Some(Ok(s)) if s.success());
+    let cancelled = job.cancelled.load(Ordering::SeqCst);
+
+    if succeeded && !cancelled {
+        FETCH_SCHEDULER.register(target);
+        let mut status = job.status.lock().unwrap();
+        status.state = "succeeded".to_string();
+        status.percent = Some(100);
+        status.repo = Some(repo_entry(target));
+        return;
+    }
+
+    // Remove whatever the partial clone left behind
+    if target.exists() {
+        if let Err(e) = fs::remove_dir_all(target) {
+            tracing::warn!(
+                "Failed to clean up partial clone {}: {}",
+                target.display(),
+                e
+            );
+        } else if !created_dir {
+            let _ = fs::create_dir_all(target);
+        }
+    }
+
+    let mut status = job.status.lock().unwrap();
+    if cancelled {
+        status.state = "cancelled".to_string();
+    } else {
+        status.state = "failed".to_string();
+        status.error = Some(match exit {
+            Some(Err(e)) => format!("Failed to wait for git clone: {}", e),
+            _ => last_line.trim().to_string(),
+        });
+    }
+}
+
+/// Start cloning `req.url` into a directory under `repos_root`.
+/// Returns immediately; poll [`clone_status`] for progress.
+pub fn start_clone(repos_root: &Path, req: &CloneRequest) -> Result<CloneStatus> {
+    let url = req.url.trim();
+    if url.is_empty() {
+        bail!("url is required");
+    }
+    let target = resolve_target(repos_root, &req.directory)?;
+    fs::create_dir_all(repos_root)
+        .with_context(|| format!("Failed to create {}", repos_root.display()))?;
+
+    let mut args = vec!["clone".to_string(), "--progress".to_string()];
+    if let Some(branch) = req.branch.as_deref().filter(|b| !b.is_empty()) {
+        args.push("--branch".to_string());
+        args.push(branch.to_string());
+    }
+    let depth = req.depth.filter(|d| *d > 0);
+    if let Some(depth) = depth {
+        args.push("--depth".to_string());
+        args.push(depth.to_string());
+    }
+    match req.single_branch {
+        Some(true) => args.push("--single-branch".to_string()),
+        // --depth implies --single-branch unless told otherwise
+        Some(false) => args.push("--no-single-branch".to_string()),
+        None => {}
+    }
+    args.push("--".to_string());
+    args.push(clone_source(url, depth.is_some()));
+    args.push(target.to_string_lossy().to_string());
+
+    let id = format!("clone-{}", NEXT_CLONE_ID.fetch_add(1, Ordering::SeqCst));
+    let status = CloneStatus {
+        id: id.clone(),
+        url: url.to_string(),
+        path: target.to_string_lossy().to_string(),
+        state: "running".to_string(),
+        phase: None,
+        percent: None,
+        current: None,
+        total: None,
+        message: None,
+        error: None,
+        repo: None,
+    };
+
+    let job = Arc::new(CloneJob {
+        status: Mutex::new(status.clone()),
+        child: Mutex::new(None),
+        cancelled: AtomicBool::new(false),
+        finished_at: Mutex::new(None),
+    });
+    // Claimed under the lock, so two jobs cannot share an empty target
+    let created_dir = {
+        let mut jobs = CLONE_JOBS.lock().unwrap();
+        prune_jobs(&mut jobs);
+        let busy = jobs.values().any(|other| {
+            let other = other.status.lock().unwrap();
+            other.state == "running" && other.path == status.path
+        });
+        if busy {
+            bail!("{} is the target of a clone in progress", target.display());
+        }
+        let created_dir = claim_target(&target)?;
+        jobs.insert(id, job.clone());
+        created_dir
+    };
+
+    std::thread::spawn(move || run_clone(job, args, target, created_dir));
+
+    Ok(status)
+}
+
+/// Current progress of a clone job.
+pub fn clone_status(id: &str) -> Option<CloneStatus> {
+    let job = {
+        let mut jobs = CLONE_JOBS.lock().unwrap();
+        prune_jobs(&mut jobs);
+        jobs.get(id).cloned()?
+    };
+    let status = job.status.lock().unwrap().clone();
+    Some(status)
+}
+
+/// Cancel a running clone, killing git and its helpers. The partially cloned
+/// directory is removed once git exits.
+pub fn cancel_clone(id: &str) -> Option<CloneStatus> {
+    let job = {
+        let mut jobs = CLONE_JOBS.lock().unwrap();
+        prune_jobs(&mut jobs);
+        jobs.get(id).cloned()?
+    };
+    if job.status.lock().unwrap().state == "running" {
+        job.cancelled.store(true, Ordering::SeqCst);
+        if let Some(child) = job.child.lock().unwrap().as_mut() {
+            kill_tree(child);
+        }
+    }
+    let status = job.status.lock().unwrap().clone();
+    Some(status)
+}
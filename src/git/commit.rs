@@ -0,0 +1,74 @@
+use anyhow::Result;
+use chrono::DateTime;
+
+use crate::git::repository::GitRepository;
+use crate::models::{CommitRequest, CommitResult, HookRun};
+
+/// Whether `author` has the "Name <email>" form; anything else makes
+/// `git commit --author` search existing authors instead
+pub fn is_explicit_author(author: &str) -> bool {
+    match (author.find('<'), author.rfind('>')) {
+        (Some(open), Some(close)) => open > 0 && close == author.len() - 1 && open < close,
+        _ => false,
+    }
+}
+
+impl GitRepository {
+    /// Create a commit with `git commit`, so hooks and commit config apply.
+    /// A failing hook comes back as a `HookFailure`.
+    pub fn create_commit(&self, req: &CommitRequest) -> Result<CommitResult> {
+        let mut args: Vec<&str> = vec!["commit", "-m", &req.message];
+        if req.sign_off {
+            args.push("--signoff");
+        }
+        let author = req
+            .author
+            .as_deref()
+            .map(str::trim)
+            .filter(|a| !a.is_empty());
+        if let Some(author) = author {
+            args.extend(["--author", author]);
+        }
+        let date = req.date.as_deref().map(str::trim).filter(|d| !d.is_empty());
+        if let Some(date) = date {
+            args.extend(["--date", date]);
+        }
+        if req.allow_empty {
+            args.push("--allow-empty");
+        }
+        if req.no_verify {
+            args.push("--no-verify");
+        }
+        match req.sign {
+            Some(true) => args.push("-S"),
+            Some(false) => args.push("--no-gpg-sign"),
+            None => {}
+        }
+        if !req.paths.is_empty() {
+            args.extend(["--only", "--"]);
+            args.extend(req.paths.iter().map(String::as_str));
+        }
+
+        let (_, hooks) = self.run_git_with_hooks(&args)?;
+        self.head_commit_result(hooks)
+    }
+
+    fn head_commit_result(&self, hooks: Vec<HookRun>) -> Result<CommitResult> {
+        let commit = self.repo.head()?.peel_to_commit()?;
+        let sha = commit.id().to_string();
+        let author = commit.author();
+        let date = DateTime::from_timestamp(author.when().seconds(), 0)
+            .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap())
+            .to_rfc3339();
+        Ok(CommitResult {
+            short_sha: sha[..7].to_string(),
+            summary: commit.summary().unwrap_or("").to_string(),
+            author: author.name().unwrap_or("").to_string(),
+            email: author.email().unwrap_or("").to_string(),
+            date,
+            signature: self.commit_signature(&sha)?,
+            sha,
+            hooks,
+        })
+    }
+}
@@ -0,0 +1,476 @@
+use anyhow::{Context, Result};
+use git2::Config;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::git::repository::GitRepository;
+use crate::models::{CommitLintResult, CommitLintRules, LintViolation};
+
+/// `type(scope)!: description`
+static CONVENTIONAL_HEADER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?P<type>[A-Za-z]+)(?:\((?P<scope>[^()]*)\))?(?P<breaking>!)?: (?P<desc>\S.*)$")
+        .unwrap()
+});
+
+fn config_list(config: &Config, key: &str) -> Vec<String> {
+    config
+        .get_string(key)
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn remove_config_key(config: &mut Config, key: &str) -> Result<()> {
+    if let Err(e) = config.remove(key) {
+        if e.code() != git2::ErrorCode::NotFound {
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+
+/// Character column of a byte offset in `line`
+fn column(line: &str, byte: usize) -> usize {
+    line[..byte].chars().count()
+}
+
+fn violation(rule: &str, message: String, line: usize, start: usize, end: usize) -> LintViolation {
+    LintViolation {
+        rule: rule.to_string(),
+        message,
+        line,
+        start,
+        end,
+    }
+}
+
+/// Branch name matched against a name or a glob with `*`
+fn branch_matches(pattern: &str, branch: &str) -> bool {
+    let pattern = format!("^{}$", regex::escape(pattern).replace(r"\*", ".*"));
+    Regex::new(&pattern)
+        .map(|re| re.is_match(branch))
+        .unwrap_or(false)
+}
+
+/// Case-insensitive match of a marker, as a whole word when it starts or
+/// ends with a word character (so "WIP" does not flag "wiped")
+fn find_marker(subject: &str, marker: &str) -> Option<(usize, usize)> {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let mut pattern = String::from("(?i)");
+    if is_word(marker.chars().next()) {
+        pattern.push_str(r"\b");
+    }
+    pattern.push_str(&regex::escape(marker));
+    if is_word(marker.chars().last()) {
+        pattern.push_str(r"\b");
+    }
+    let found = Regex::new(&pattern).ok()?.find(subject)?;
+    Some((found.start(), found.end()))
+}
+
+fn lint_conventional(subject: &str, rules: &CommitLintRules, out: &mut Vec<LintViolation>) {
+    let subject_len = subject.chars().count();
+    let Some(caps) = CONVENTIONAL_HEADER.captures(subject) else {
+        out.push(violation(
+            "conventional-format",
+            "Subject must read \"type(scope): description\"".to_string(),
+            1,
+            0,
+            subject_len,
+        ));
+        return;
+    };
+
+    let kind = caps.name("type").unwrap();
+    if !rules.types.is_empty() && !rules.types.iter().any(|t| t == kind.as_str()) {
+        out.push(violation(
+            "conventional-type",
+            format!(
+                "Type \"{}\" is not one of: {}",
+                kind.as_str(),
+                rules.types.join(", ")
+            ),
+            1,
+            column(subject, kind.start()),
+            column(subject, kind.end()),
+        ));
+    }
+
+    match caps.name("scope").filter(|s| !s.as_str().trim().is_empty()) {
+        Some(scope)
+            if !rules.scopes.is_empty() && !rules.scopes.iter().any(|s| s == scope.as_str()) =>
+        {
+            out.push(violation(
+                "conventional-scope",
+                format!(
+                    "Scope \"{}\" is not one of: {}",
+                    scope.as_str(),
+                    rules.scopes.join(", ")
+                ),
+                1,
+                column(subject, scope.start()),
+                column(subject, scope.end()),
+            ));
+        }
+        Some(_) => {}
+        None if rules.require_scope => {
+            out.push(violation(
+                "conventional-scope",
+                "A scope is required, as in \"type(scope): description\"".to_string(),
+                1,
+                column(subject, kind.start()),
+                column(subject, kind.end()),
+            ));
+        }
+        None => {}
+    }
+}
+
+/// Check `message` against `rules`. `protected_branch` enables the WIP marker
+/// rule; `sign_off` means `git commit --signoff` will add the trailer.
+pub fn lint_message(
+    message: &str,
+    rules: &CommitLintRules,
+    protected_branch: bool,
+    sign_off: bool,
+) -> Result<Vec<LintViolation>> {
+    let lines: Vec<&str> = message.lines().collect();
+    let subject = lines.first().copied().unwrap_or("");
+    let subject_len = subject.chars().count();
+    let mut out = Vec::new();
+
+    if rules.conventional {
+        lint_conventional(subject, rules, &mut out);
+    }
+
+    if rules.subject_max_length > 0 && subject_len > rules.subject_max_length {
+        out.push(violation(
+            "subject-max-length",
+            format!(
+                "Subject is {} characters long; the limit is {}",
+                subject_len, rules.subject_max_length
+            ),
+            1,
+            rules.subject_max_length,
+            subject_len,
+        ));
+    }
+
+    if rules.blank_second_line {
+        if let Some(second) = lines.get(1).filter(|l| !l.trim().is_empty()) {
+            out.push(violation(
+                "blank-second-line",
+                "Separate the subject from the body with a blank line".to_string(),
+                2,
+                0,
+                second.chars().count(),
+            ));
+        }
+    }
+
+    if protected_branch {
+        for marker in &rules.wip_markers {
+            if let Some((start, end)) = find_marker(subject, marker) {
+                out.push(violation(
+                    "wip-marker",
+                    format!("\"{}\" commits are not allowed on this branch", marker),
+                    1,
+                    column(subject, start),
+                    column(subject, end),
+                ));
+            }
+        }
+    }
+
+    let ticket_pattern = match rules.ticket_pattern.as_deref() {
+        Some(pattern) if !pattern.is_empty() => {
+            Some(Regex::new(pattern).context("Invalid gitpow.lint.ticketPattern")?)
+        }
+        _ => None,
+    };
+    if !rules.required_trailers.is_empty() || ticket_pattern.is_some() {
+        let trailers: Vec<(String, String)> = git2::message_trailers_strs(message)
+            .map(|trailers| {
+                trailers
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Missing trailers belong at the end of the message
+        let last_line = lines.len().max(1);
+        let last_len = lines.last().map(|l| l.chars().count()).unwrap_or(0);
+
+        for required in &rules.required_trailers {
+            let present = trailers
+                .iter()
+                .any(|(key, _)| key.eq_ignore_ascii_case(required))
+                || (sign_off && required.eq_ignore_ascii_case("Signed-off-by"));
+            if !present {
+                out.push(violation(
+                    "required-trailer",
+                    format!("Missing \"{}:\" trailer", required),
+                    last_line,
+                    last_len,
+                    last_len,
+                ));
+            }
+        }
+
+        if let Some(pattern) = &ticket_pattern {
+            if !trailers.iter().any(|(_, value)| pattern.is_match(value)) {
+                out.push(violation(
+                    "ticket-reference",
+                    format!(
+                        "No trailer references a ticket matching {}",
+                        pattern.as_str()
+                    ),
+                    last_line,
+                    last_len,
+                    last_len,
+                ));
+            }
+        }
+    }
+
+    out.sort_by_key(|v| (v.line, v.start));
+    Ok(out)
+}
+
+impl GitRepository {
+    /// Commit message rules from `gitpow.lint.*`
+    pub fn commit_lint_rules(&self) -> Result<CommitLintRules> {
+        let config = self.repo.config()?.snapshot()?;
+        Ok(CommitLintRules {
+            conventional: config.get_bool("gitpow.lint.conventional").unwrap_or(false),
+            types: config_list(&config, "gitpow.lint.types"),
+            scopes: config_list(&config, "gitpow.lint.scopes"),
+            require_scope: config.get_bool("gitpow.lint.requireScope").unwrap_or(false),
+            subject_max_length: config
+                .get_i64("gitpow.lint.subjectMaxLength")
+                .map(|n| n.max(0) as usize)
+                .unwrap_or(0),
+            blank_second_line: config
+                .get_bool("gitpow.lint.blankSecondLine")
+                .unwrap_or(false),
+            required_trailers: config_list(&config, "gitpow.lint.requiredTrailers"),
+            ticket_pattern: config
+                .get_string("gitpow.lint.ticketPattern")
+                .ok()
+                .filter(|p| !p.is_empty()),
+            wip_markers: config_list(&config, "gitpow.lint.wipMarkers"),
+            protected_branches: config_list(&config, "gitpow.lint.protectedBranches"),
+        })
+    }
+
+    /// Persist commit message rules in the repository's own git config
+    pub fn set_commit_lint_rules(&self, rules: &CommitLintRules) -> Result<()> {
+        let mut config = self.repo.config()?;
+        config.set_bool("gitpow.lint.conventional", rules.conventional)?;
+        config.set_str("gitpow.lint.types", &rules.types.join(","))?;
+        config.set_str("gitpow.lint.scopes", &rules.scopes.join(","))?;
+        config.set_bool("gitpow.lint.requireScope", rules.require_scope)?;
+        config.set_i64(
+            "gitpow.lint.subjectMaxLength",
+            rules.subject_max_length as i64,
+        )?;
+        config.set_bool("gitpow.lint.blankSecondLine", rules.blank_second_line)?;
+        config.set_str(
+            "gitpow.lint.requiredTrailers",
+            &rules.required_trailers.join(","),
+        )?;
+        match rules.ticket_pattern.as_deref().filter(|p| !p.is_empty()) {
+            Some(pattern) => config.set_str("gitpow.lint.ticketPattern", pattern)?,
+            None => remove_config_key(&mut config, "gitpow.lint.ticketPattern")?,
+        }
+        config.set_str("gitpow.lint.wipMarkers", &rules.wip_markers.join(","))?;
+        config.set_str(
+            "gitpow.lint.protectedBranches",
+            &rules.protected_branches.join(","),
+        )?;
+        Ok(())
+    }
+
+    /// Lint a commit message for `branch` (the current branch when absent)
+    pub fn lint_commit_message(
+        &self,
+        message: &str,
+        branch: Option<&str>,
+        sign_off: bool,
+    ) -> Result<CommitLintResult> {
+        let rules = self.commit_lint_rules()?;
+        let branch = match branch.map(str::trim).filter(|b| !b.is_empty()) {
+            Some(branch) => Some(branch.to_string()),
+            // Read HEAD itself so an unborn branch still has a name
+            None => self
+                .repo
+                .find_reference("HEAD")
+                .ok()
+                .and_then(|head| head.symbolic_target().map(str::to_string))
+                .and_then(|target| target.strip_prefix("refs/heads/").map(str::to_string)),
+        };
+        let protected = branch.as_deref().is_some_and(|branch| {
+            rules
+                .protected_branches
+                .iter()
+                .any(|pattern| branch_matches(pattern, branch))
+        });
+
+        let violations = lint_message(message, &rules, protected, sign_off)?;
+        Ok(CommitLintResult {
+            valid: violations.is_empty(),
+            branch,
+            violations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> CommitLintRules {
+        CommitLintRules::default()
+    }
+
+    fn rule_names(violations: &[LintViolation]) -> Vec<&str> {
+        violations.iter().map(|v| v.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn accepts_anything_without_rules() {
+        let violations = lint_message("wip\nno blank line", &rules(), true, false).unwrap();
+        assert!(violations.is_empty());
+    }
+
+    #[test]
+    fn checks_conventional_headers() {
+        let rules = CommitLintRules {
+            conventional: true,
+            types: vec!["feat".to_string(), "fix".to_string()],
+            scopes: vec!["ui".to_string()],
+            ..rules()
+        };
+        let ok = lint_message("feat(ui)!: add a button", &rules, false, false).unwrap();
+        assert!(ok.is_empty());
+
+        let bad = lint_message("add a button", &rules, false, false).unwrap();
+        assert_eq!(rule_names(&bad), ["conventional-format"]);
+        assert_eq!((bad[0].start, bad[0].end), (0, 12));
+
+        let bad = lint_message("docs(api): explain", &rules, false, false).unwrap();
+        assert_eq!(
+            rule_names(&bad),
+            ["conventional-type", "conventional-scope"]
+        );
+        assert_eq!((bad[0].start, bad[0].end), (0, 4));
+        assert_eq!((bad[1].start, bad[1].end), (5, 8));
+    }
+
+    #[test]
+    fn requires_a_scope_when_asked() {
+        let rules = CommitLintRules {
+            conventional: true,
+            require_scope: true,
+            ..rules()
+        };
+        let bad = lint_message("fix: typo", &rules, false, false).unwrap();
+        assert_eq!(rule_names(&bad), ["conventional-scope"]);
+        let bad = lint_message("fix( ): typo", &rules, false, false).unwrap();
+        assert_eq!(rule_names(&bad), ["conventional-scope"]);
+    }
+
+    #[test]
+    fn counts_subject_length_in_characters() {
+        let rules = CommitLintRules {
+            subject_max_length: 5,
+            ..rules()
+        };
+        assert!(lint_message("héllo", &rules, false, false)
+            .unwrap()
+            .is_empty());
+        let bad = lint_message("héllo!!", &rules, false, false).unwrap();
+        assert_eq!(rule_names(&bad), ["subject-max-length"]);
+        assert_eq!((bad[0].start, bad[0].end), (5, 7));
+    }
+
+    #[test]
+    fn wants_a_blank_second_line() {
+        let rules = CommitLintRules {
+            blank_second_line: true,
+            ..rules()
+        };
+        assert!(lint_message("subject\n\nbody", &rules, false, false)
+            .unwrap()
+            .is_empty());
+        let bad = lint_message("subject\nbody", &rules, false, false).unwrap();
+        assert_eq!(rule_names(&bad), ["blank-second-line"]);
+        assert_eq!(bad[0].line, 2);
+    }
+
+    #[test]
+    fn flags_wip_markers_as_whole_words_on_protected_branches() {
+        let rules = CommitLintRules {
+            wip_markers: vec!["WIP".to_string(), "fixup!".to_string()],
+            ..rules()
+        };
+        assert!(lint_message("wip: later", &rules, false, false)
+            .unwrap()
+            .is_empty());
+        assert!(lint_message("wiped the cache", &rules, true, false)
+            .unwrap()
+            .is_empty());
+        let bad = lint_message("Fix it [wip]", &rules, true, false).unwrap();
+        assert_eq!(rule_names(&bad), ["wip-marker"]);
+        assert_eq!((bad[0].start, bad[0].end), (8, 11));
+        let bad = lint_message("fixup! Fix it", &rules, true, false).unwrap();
+        assert_eq!(rule_names(&bad), ["wip-marker"]);
+    }
+
+    #[test]
+    fn checks_trailers_and_ticket_references() {
+        let rules = CommitLintRules {
+            required_trailers: vec!["Signed-off-by".to_string()],
+            ticket_pattern: Some(r"[A-Z]+-\d+".to_string()),
+            ..rules()
+        };
+        let message = "Fix it\n\nBody\n\nRefs: GP-12\nSigned-off-by: A <a@b>\n";
+        assert!(lint_message(message, &rules, false, false)
+            .unwrap()
+            .is_empty());
+
+        let bad = lint_message("Fix it\n\nBody", &rules, false, false).unwrap();
+        assert_eq!(rule_names(&bad), ["required-trailer", "ticket-reference"]);
+        assert_eq!((bad[0].line, bad[0].start), (3, 4));
+
+        // `--signoff` adds the trailer itself
+        let bad = lint_message("Fix it\n\nRefs: GP-12", &rules, false, true).unwrap();
+        assert!(bad.is_empty());
+    }
+
+    #[test]
+    fn rejects_an_invalid_ticket_pattern() {
+        let rules = CommitLintRules {
+            ticket_pattern: Some("(".to_string()),
+            ..rules()
+        };
+        assert!(lint_message("Fix it", &rules, false, false).is_err());
+    }
+
+    #[test]
+    fn matches_branch_globs() {
+        assert!(branch_matches("main", "main"));
+        assert!(!branch_matches("main", "main2"));
+        assert!(branch_matches("release/*", "release/1.2"));
+        assert!(!branch_matches("release/*", "hotfix/release/1"));
+        assert!(branch_matches("v1.*", "v1.x"));
+        assert!(!branch_matches("v1.*", "v1x"));
+    }
+}
@@ -0,0 +1,226 @@
+use anyhow::{anyhow, Result};
+use git2::Oid;
+
+use crate::git::diff_options::DiffConfig;
+use crate::git::repository::{parse_raw_changes, FileDiff, GitRepository};
+use crate::models::FileChange;
+
+/// One end of a comparison
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffSide {
+    /// A tree, with the commit it belongs to when the spec named one
+    Tree {
+        tree: Oid,
+        commit: Option<Oid>,
+    },
+    Index,
+    /// Tracked files as they are on disk; untracked files are left out, as in `git diff`
+    Worktree,
+}
+
+impl DiffSide {
+    /// The side as reported back: the commit (or tree) id, INDEX or WORKTREE
+    pub fn label(self) -> String {
+        match self {
+            DiffSide::Tree { tree, commit } => commit.unwrap_or(tree).to_string(),
+            DiffSide::Index => "INDEX".to_string(),
+            DiffSide::Worktree => "WORKTREE".to_string(),
+        }
+    }
+
+    /// Trees come before the index and the index before the working tree,
+    /// the only direction libgit2 and `git diff` compare in
+    fn rank(self) -> u8 {
+        match self {
+            DiffSide::Tree { .. } => 0,
+            DiffSide::Index => 1,
+            DiffSide::Worktree => 2,
+        }
+    }
+
+    /// `git diff` blob spec of a path on this side; the working tree has none
+    fn blob_spec(self, path: &str) -> Option<String> {
+        match self {
+            DiffSide::Tree { tree, .. } => Some(format!("{}:{}", tree, path)),
+            DiffSide::Index => Some(format!(":{}", path)),
+            DiffSide::Worktree => None,
+        }
+    }
+}
+
+/// A comparison in the direction it can be computed in, reversed when the
+/// request asked for the other one
+struct Comparison {
+    old: DiffSide,
+    new: DiffSide,
+    reversed: bool,
+}
+
+impl Comparison {
+    fn new(from: DiffSide, to: DiffSide) -> Self {
+        if from.rank() > to.rank() {
+            Comparison {
+                old: to,
+                new: from,
+                reversed: true,
+            }
+        } else {
+            Comparison {
+                old: from,
+                new: to,
+                reversed: false,
+            }
+        }
+    }
+
+    /// Both ends are the same, so nothing differs
+    fn is_empty(&self) -> bool {
+        self.old == self.new
+    }
+
+    /// The comparison as `git diff` arguments
+    fn git_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.reversed {
+            args.push("-R".to_string());
+        }
+        match (self.old, self.new) {
+            (DiffSide::Tree { tree: old, .. }, DiffSide::Tree { tree: new, .. }) => {
+                args.extend([old.to_string(), new.to_string()])
+            }
+            (DiffSide::Tree { tree, .. }, DiffSide::Index) => {
+                args.extend(["--cached".to_string(), tree.to_string()])
+            }
+            (DiffSide::Tree { tree, .. }, DiffSide::Worktree) => args.push(tree.to_string()),
+            // Index to working tree
+            _ => {}
+        }
+        args
+    }
+}
+
+impl GitRepository {
+    /// Resolve one end of a comparison: "INDEX", "WORKTREE" or any revision
+    /// naming a commit, tag or tree
+    pub fn diff_side(&self, spec: &str) -> Result<DiffSide> {
+        let spec = spec.trim();
+        match spec {
+            "INDEX" => return Ok(DiffSide::Index),
+            "WORKTREE" => return Ok(DiffSide::Worktree),
+            _ => {}
+        }
+        let object = self
+            .repo
+            .revparse_single(spec)
+            .map_err(|e| anyhow!("Unknown revision {}: {}", spec, e.message()))?;
+        let tree = object
+            .peel_to_tree()
+            .map_err(|_| anyhow!("{} does not name a commit or tree", spec))?;
+        Ok(DiffSide::Tree {
+            tree: tree.id(),
+            commit: object.peel_to_commit().ok().map(|commit| commit.id()),
+        })
+    }
+
+    /// Files that differ between two sides, with renames and copies detected
+    /// as in commit file lists
+    pub fn changed_files_between(&self, from: DiffSide, to: DiffSide) -> Result<Vec<FileChange>> {
+        let comparison = Comparison::new(from, to);
+        if comparison.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut args = vec![
+            "diff".to_string(),
+            "--raw".to_string(),
+            "-z".to_string(),
+            "--no-ext-diff".to_string(),
+        ];
+        args.extend(self.rename_args());
+        args.extend(comparison.git_args());
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        parse_raw_changes(&self.run_git_bytes(&args)?)
+    }
+
+    /// Diff of one file between two sides. `old_path` names the file on the
+    /// `from` side when it was renamed or copied to `file_path`.
+    pub fn file_diff_between(
+        &self,
+        from: DiffSide,
+        to: DiffSide,
+        file_path: &str,
+        old_path: Option<&str>,
+        config: &DiffConfig,
+    ) -> Result<FileDiff> {
+        let comparison = Comparison::new(from, to);
+        if comparison.is_empty() {
+            return Ok(FileDiff {
+                diff: String::new(),
+                hunks: vec![],
+                file_path: file_path.to_string(),
+                submodule: None,
+                binary: None,
+            });
+        }
+        let old_path = old_path.filter(|old_path| *old_path != file_path);
+
+        if config.needs_git_cli() {
+            match old_path {
+                None => {
+                    let mut revs = comparison.git_args();
+                    if comparison.reversed {
+                        // `-R` swaps the prefixes along with the sides
+                        revs.extend(["--src-prefix=b/".to_string(), "--dst-prefix=a/".to_string()]);
+                    }
+                    let revs: Vec<&str> = revs.iter().map(String::as_str).collect();
+                    return self.git_cli_file_diff(&revs, &[file_path], file_path, config);
+                }
+                Some(old_path) => {
+                    if let (Some(old_spec), Some(new_spec)) =
+                        (from.blob_spec(old_path), to.blob_spec(file_path))
+                    {
+                        return self.git_cli_file_diff(
+                            &[&old_spec, &new_spec],
+                            &[],
+                            file_path,
+                            config,
+                        );
+                    }
+                    // A rename into or out of the working tree has no blob
+                    // spec; libgit2 diffs it with its default algorithm
+                }
+            }
+        }
+
+        let mut opts = config.diff_options(file_path);
+        opts.reverse(comparison.reversed);
+        if let Some(old_path) = old_path {
+            // The source of a copy is unchanged, so it has to be listed explicitly
+            opts.pathspec(old_path).include_unmodified(true);
+        }
+
+        let mut diff = match (comparison.old, comparison.new) {
+            (DiffSide::Tree { tree: old, .. }, DiffSide::Tree { tree: new, .. }) => {
+                let (old, new) = (self.repo.find_tree(old)?, self.repo.find_tree(new)?);
+                self.repo
+                    .diff_tree_to_tree(Some(&old), Some(&new), Some(&mut opts))?
+            }
+            (DiffSide::Tree { tree, .. }, DiffSide::Index) => {
+                let tree = self.repo.find_tree(tree)?;
+                self.repo
+                    .diff_tree_to_index(Some(&tree), None, Some(&mut opts))?
+            }
+            (DiffSide::Tree { tree, .. }, DiffSide::Worktree) => {
+                let tree = self.repo.find_tree(tree)?;
+                self.repo
+                    .diff_tree_to_workdir_with_index(Some(&tree), Some(&mut opts))?
+            }
+            _ => self.repo.diff_index_to_workdir(None, Some(&mut opts))?,
+        };
+        if old_path.is_some() {
+            Self::pair_renamed(&mut diff)?;
+        }
+
+        // With `reverse` the new side of each delta is still `to`
+        self.collect_file_diff(&diff, file_path, config)
+    }
+}
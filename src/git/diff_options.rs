@@ -0,0 +1,776 @@
+use std::fs;
+use std::ops::Range;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use git2::{Diff, DiffOptions, ObjectType, Oid};
+
+use crate::git::repository::{DiffHunkData, FileDiff, GitRepository};
+use crate::models::{BinaryDiff, DiffSettings};
+
+/// Context line count used for `context=full`; large enough to span any file
+pub const FULL_CONTEXT: u32 = i32::MAX as u32;
+
+const DEFAULT_CONTEXT: u32 = 3;
+
+/// Default for `gitpow.diffMaxFileSize`
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Default for `gitpow.diffMaxLines`
+pub const DEFAULT_MAX_LINES: usize = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Whitespace {
+    Exact,
+    IgnoreAll,
+    IgnoreChange,
+    IgnoreAtEol,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffAlgorithm {
+    Myers,
+    Minimal,
+    Patience,
+    /// Not implemented by libgit2; computed by `git diff --histogram`
+    Histogram,
+}
+
+/// Which side a commit is diffed against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffParent {
+    /// Parent by 0-based index (`commit^1` is index 0)
+    Parent(usize),
+    /// Dense combined diff of a merge against all its parents (`--cc`)
+    Combined,
+}
+
+impl Default for DiffParent {
+    fn default() -> Self {
+        DiffParent::Parent(0)
+    }
+}
+
+impl DiffParent {
+    /// Parse a request value: a 1-based parent number as in `commit^N`, or "combined"
+    pub fn parse(value: Option<&str>) -> Result<Self> {
+        match value.map(str::trim) {
+            None | Some("") => Ok(DiffParent::default()),
+            Some("combined") => Ok(DiffParent::Combined),
+            Some(n) => match n.parse::<usize>() {
+                Ok(n) if n > 0 => Ok(DiffParent::Parent(n - 1)),
+                _ => bail!(
+                    "Invalid parent: {} (expected a parent number from 1 or \"combined\")",
+                    n
+                ),
+            },
+        }
+    }
+
+    /// What this selection means for a commit with `parent_count` parents:
+    /// combined only applies to merges, and a root commit has no parent to pick
+    pub fn resolve(self, parent_count: usize) -> Result<Self> {
+        match self {
+            DiffParent::Combined if parent_count > 1 => Ok(self),
+            DiffParent::Combined => Ok(DiffParent::Parent(0)),
+            DiffParent::Parent(0) => Ok(self),
+            DiffParent::Parent(i) if i < parent_count => Ok(self),
+            DiffParent::Parent(i) => bail!(
+                "Parent {} does not exist; the commit has {} parent(s)",
+                i + 1,
+                parent_count
+            ),
+        }
+    }
+}
+
+/// Validated `DiffSettings`, applied the same way to commit, working tree,
+/// stash and compare diffs
+#[derive(Debug, Clone, Copy)]
+pub struct DiffConfig {
+    pub context: u32,
+    pub whitespace: Whitespace,
+    pub ignore_blank_lines: bool,
+    pub algorithm: DiffAlgorithm,
+    pub indent_heuristic: bool,
+    /// Files larger than this many bytes are reported by size; 0 for no limit
+    pub max_file_size: u64,
+    /// Diff lines per page of hunks; 0 for no limit
+    pub max_lines: usize,
+    /// First hunk of the page
+    pub hunk_offset: usize,
+    /// Line of the first hunk the page starts at, when a previous page split it
+    pub hunk_line: usize,
+}
+
+impl Default for DiffConfig {
+    fn default() -> Self {
+        Self {
+            context: DEFAULT_CONTEXT,
+            whitespace: Whitespace::Exact,
+            ignore_blank_lines: false,
+            algorithm: DiffAlgorithm::Myers,
+            indent_heuristic: false,
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            max_lines: DEFAULT_MAX_LINES,
+            hunk_offset: 0,
+            hunk_line: 0,
+        }
+    }
+}
+
+fn flag(name: &str, value: Option<&str>) -> Result<bool> {
+    match value.map(str::trim) {
+        None | Some("") | Some("false") | Some("0") => Ok(false),
+        Some("true") | Some("1") => Ok(true),
+        Some(other) => bail!("Invalid {}: {} (expected true or false)", name, other),
+    }
+}
+
+fn number<T: std::str::FromStr>(name: &str, value: Option<&str>, default: T) -> Result<T> {
+    match value.map(str::trim) {
+        None | Some("") => Ok(default),
+        Some(n) => n
+            .parse()
+            .ok()
+            .with_context(|| format!("Invalid {}: {} (expected a number)", name, n)),
+    }
+}
+
+fn is_unset(value: &Option<String>) -> bool {
+    value.as_deref().is_none_or(|v| v.trim().is_empty())
+}
+
+impl DiffConfig {
+    pub fn parse(settings: &DiffSettings) -> Result<Self> {
+        let context = match settings.context.as_deref().map(str::trim) {
+            None | Some("") => DEFAULT_CONTEXT,
+            Some("full") => FULL_CONTEXT,
+            Some(n) => n
+                .parse::<u32>()
+                .ok()
+                .filter(|n| *n < FULL_CONTEXT)
+                .with_context(|| {
+                    format!("Invalid context: {} (expected a number or \"full\")", n)
+                })?,
+        };
+        let whitespace = match settings.whitespace.as_deref().map(str::trim) {
+            None | Some("") => Whitespace::Exact,
+            Some("ignore-all-space") => Whitespace::IgnoreAll,
+            Some("ignore-space-change") => Whitespace::IgnoreChange,
+            Some("ignore-space-at-eol") => Whitespace::IgnoreAtEol,
+            Some(other) => bail!(
+                "Invalid whitespace mode: {} (expected ignore-all-space, ignore-space-change or ignore-space-at-eol)",
+                other
+            ),
+        };
+        let algorithm = match settings.algorithm.as_deref().map(str::trim) {
+            None | Some("") | Some("myers") | Some("default") => DiffAlgorithm::Myers,
+            Some("minimal") => DiffAlgorithm::Minimal,
+            Some("patience") => DiffAlgorithm::Patience,
+            Some("histogram") => DiffAlgorithm::Histogram,
+            Some(other) => bail!(
+                "Invalid diff algorithm: {} (expected myers, minimal, patience or histogram)",
+                other
+            ),
+        };
+        Ok(Self {
+            context,
+            whitespace,
+            ignore_blank_lines: flag("ignoreBlankLines", settings.ignore_blank_lines.as_deref())?,
+            algorithm,
+            indent_heuristic: flag("indentHeuristic", settings.indent_heuristic.as_deref())?,
+            max_file_size: number(
+                "maxFileSize",
+                settings.max_file_size.as_deref(),
+                DEFAULT_MAX_FILE_SIZE,
+            )?,
+            max_lines: number("maxLines", settings.max_lines.as_deref(), DEFAULT_MAX_LINES)?,
+            hunk_offset: number("hunkOffset", settings.hunk_offset.as_deref(), 0)?,
+            hunk_line: number("hunkLine", settings.hunk_line.as_deref(), 0)?,
+        })
+    }
+
+    /// Whether a blob of `size` bytes is over the size limit
+    pub fn too_large(&self, size: u64) -> bool {
+        self.max_file_size > 0 && size > self.max_file_size
+    }
+
+    /// Whether the diff has to come from the git CLI rather than libgit2
+    pub fn needs_git_cli(&self) -> bool {
+        self.algorithm == DiffAlgorithm::Histogram
+    }
+
+    /// libgit2 options for a diff limited to `file_path`
+    pub fn diff_options(&self, file_path: &str) -> DiffOptions {
+        let mut opts = self.options();
+        opts.pathspec(file_path);
+        opts
+    }
+
+    /// libgit2 options for any diff, such as one between two buffers
+    pub fn options(&self) -> DiffOptions {
+        let mut opts = DiffOptions::new();
+        opts.context_lines(self.context)
+            .ignore_whitespace(self.whitespace == Whitespace::IgnoreAll)
+            .ignore_whitespace_change(self.whitespace == Whitespace::IgnoreChange)
+            .ignore_whitespace_eol(self.whitespace == Whitespace::IgnoreAtEol)
+            .ignore_blank_lines(self.ignore_blank_lines)
+            .minimal(self.algorithm == DiffAlgorithm::Minimal)
+            .patience(self.algorithm == DiffAlgorithm::Patience)
+            .indent_heuristic(self.indent_heuristic)
+            // libgit2 treats bigger blobs as binary; negative turns that off
+            .max_size(match self.max_file_size {
+                0 => -1,
+                n => i64::try_from(n).unwrap_or(i64::MAX),
+            });
+        opts
+    }
+
+    /// The same options as `git diff` flags
+    pub(crate) fn git_args(&self) -> Vec<String> {
+        let mut args = vec![format!("-U{}", self.context)];
+        match self.whitespace {
+            Whitespace::Exact => {}
+            Whitespace::IgnoreAll => args.push("--ignore-all-space".to_string()),
+            Whitespace::IgnoreChange => args.push("--ignore-space-change".to_string()),
+            Whitespace::IgnoreAtEol => args.push("--ignore-space-at-eol".to_string()),
+        }
+        if self.ignore_blank_lines {
+            args.push("--ignore-blank-lines".to_string());
+        }
+        args.push(
+            match self.algorithm {
+                DiffAlgorithm::Myers => "--diff-algorithm=myers",
+                DiffAlgorithm::Minimal => "--diff-algorithm=minimal",
+                DiffAlgorithm::Patience => "--diff-algorithm=patience",
+                DiffAlgorithm::Histogram => "--diff-algorithm=histogram",
+            }
+            .to_string(),
+        );
+        args.push(
+            if self.indent_heuristic {
+                "--indent-heuristic"
+            } else {
+                "--no-indent-heuristic"
+            }
+            .to_string(),
+        );
+        args
+    }
+}
+
+struct HunkHeader {
+    /// The header without its section heading
+    text: String,
+    old_start: i32,
+    old_count: i32,
+    new_start: i32,
+    new_count: i32,
+}
+
+/// Parse `@@ -a,b +c,d @@ ...`. Combined diff headers (`@@@ -a,b -c,d +e,f @@@`)
+/// report the first parent's range as the old one.
+fn parse_hunk_header(line: &str) -> Option<HunkHeader> {
+    let marks = line.chars().take_while(|c| *c == '@').count();
+    if marks < 2 {
+        return None;
+    }
+    let closing = format!(" {}", "@".repeat(marks));
+    let ranges = line[marks..]
+        .strip_prefix(' ')?
+        .split(closing.as_str())
+        .next()?;
+    let range = |r: &str| -> Option<(i32, i32)> {
+        match r.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((r.parse().ok()?, 1)),
+        }
+    };
+    let old = ranges.split(' ').find_map(|r| r.strip_prefix('-'))?;
+    let new = ranges.split(' ').find_map(|r| r.strip_prefix('+'))?;
+    let (old_start, old_count) = range(old)?;
+    let (new_start, new_count) = range(new)?;
+    let text = if marks == 2 {
+        format!(
+            "@@ -{},{} +{},{} @@",
+            old_start, old_count, new_start, new_count
+        )
+    } else {
+        format!("{} {}{}", "@".repeat(marks), ranges, closing)
+    };
+    Some(HunkHeader {
+        text,
+        old_start,
+        old_count,
+        new_start,
+        new_count,
+    })
+}
+
+/// Whether a hunk line exists on each side of a hunk with `columns` parents:
+/// the parents in order, then the result. In a combined diff a line removed
+/// from one parent is on no side whose column is blank.
+fn line_sides(line: &str, columns: usize) -> Vec<bool> {
+    let prefix: Vec<char> = line
+        .chars()
+        .chain(std::iter::repeat(' '))
+        .take(columns)
+        .collect();
+    let in_result = !prefix.contains(&'-');
+    let mut sides: Vec<bool> = prefix
+        .iter()
+        .map(|c| if in_result { *c != '+' } else { *c == '-' })
+        .collect();
+    sides.push(in_result);
+    sides
+}
+
+/// Lines `range` of a hunk (which starts after the header, at 1 or later) as a
+/// hunk of their own, with a header whose ranges count only those lines
+pub(crate) fn hunk_part(hunk: &DiffHunkData, range: Range<usize>) -> DiffHunkData {
+    let header = hunk.lines.first().map(String::as_str).unwrap_or_default();
+    let marks = header.chars().take_while(|c| *c == '@').count().max(2);
+    let columns = marks - 1;
+    let closing = format!(" {}", "@".repeat(marks));
+    let mut ranges: Vec<(i32, i32)> = header[marks.min(header.len())..]
+        .trim_start()
+        .split(closing.as_str())
+        .next()
+        .unwrap_or_default()
+        .split(' ')
+        .filter_map(|r| {
+            let r = r.get(1..)?;
+            match r.split_once(',') {
+                Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+                None => Some((r.parse().ok()?, 1)),
+            }
+        })
+        .collect();
+    if ranges.len() != columns + 1 {
+        ranges = vec![
+            (hunk.old_start, hunk.old_count),
+            (hunk.new_start, hunk.new_count),
+        ];
+    }
+    let columns = ranges.len() - 1;
+
+    // An empty range starts at the line before the hunk
+    let mut starts: Vec<i32> = ranges
+        .iter()
+        .map(|(start, count)| if *count == 0 { start + 1 } else { *start })
+        .collect();
+    let mut counts = vec![0; ranges.len()];
+    for (i, line) in hunk.lines.iter().enumerate().take(range.end).skip(1) {
+        for (side, present) in line_sides(line, columns).into_iter().enumerate() {
+            if !present {
+                continue;
+            }
+            if i < range.start {
+                starts[side] += 1;
+            } else {
+                counts[side] += 1;
+            }
+        }
+    }
+    for (start, count) in starts.iter_mut().zip(&counts) {
+        if *count == 0 {
+            *start -= 1;
+        }
+    }
+
+    let sides: Vec<String> = starts
+        .iter()
+        .zip(&counts)
+        .enumerate()
+        .map(|(side, (start, count))| {
+            let sign = if side == columns { '+' } else { '-' };
+            format!("{}{},{}", sign, start, count)
+        })
+        .collect();
+    let part_header = format!("{} {}{}", "@".repeat(marks), sides.join(" "), closing);
+    let mut lines = vec![part_header];
+    lines.extend(hunk.lines.iter().take(range.end).skip(range.start).cloned());
+    DiffHunkData {
+        old_start: starts[0],
+        old_count: counts[0],
+        new_start: starts[columns],
+        new_count: counts[columns],
+        lines,
+    }
+}
+
+/// Sizes from a `--stat` line such as ` img.png | Bin 120 -> 4096 bytes`
+fn parse_binary_stat(line: &str) -> Option<(u64, u64)> {
+    let (_, stat) = line.rsplit_once("| Bin ")?;
+    let (old, new) = stat.strip_suffix(" bytes")?.split_once(" -> ")?;
+    Some((old.trim().parse().ok()?, new.trim().parse().ok()?))
+}
+
+/// Convert `git diff --stat -p` output for one file into the shape libgit2
+/// diffs produce: the stat and the `diff --git`/`index` preamble are dropped
+/// and hunk headers lose their section heading
+fn parse_unified_diff(output: &str, file_path: &str) -> FileDiff {
+    let mut diff = String::new();
+    let mut hunks: Vec<DiffHunkData> = Vec::new();
+    let mut in_body = false;
+    let mut sizes = None;
+    let mut binary = None;
+
+    for line in output.lines() {
+        if let Some(header) = parse_hunk_header(line) {
+            in_body = true;
+            diff.push_str(&header.text);
+            diff.push('\n');
+            hunks.push(DiffHunkData {
+                old_start: header.old_start,
+                old_count: header.old_count,
+                new_start: header.new_start,
+                new_count: header.new_count,
+                lines: vec![header.text],
+            });
+            continue;
+        }
+        if !in_body {
+            if sizes.is_none() {
+                sizes = parse_binary_stat(line);
+            }
+            if let Some(files) = line
+                .strip_prefix("Binary files ")
+                .and_then(|files| files.strip_suffix(" differ"))
+            {
+                binary = Some((
+                    !files.starts_with("/dev/null "),
+                    !files.ends_with(" /dev/null"),
+                ));
+            }
+            if line.starts_with("--- ")
+                || line.starts_with("+++ ")
+                || line.starts_with("Binary files ")
+            {
+                // git ends a header with a tab when the path contains spaces
+                diff.push_str(line.trim_end());
+                diff.push('\n');
+            }
+            continue;
+        }
+        diff.push_str(line);
+        diff.push('\n');
+        if line.starts_with(['+', '-', ' ']) {
+            if let Some(hunk) = hunks.last_mut() {
+                hunk.lines.push(line.trim_end().to_string());
+            }
+        }
+    }
+
+    // The stat gives 0 for a missing side; the header tells it apart from an empty file
+    let binary = binary.map(|(old_exists, new_exists)| {
+        let (old_size, new_size) = match sizes {
+            Some((old, new)) => (Some(old), Some(new)),
+            None => (None, None),
+        };
+        BinaryDiff {
+            old_size: old_size.filter(|_| old_exists),
+            new_size: new_size.filter(|_| new_exists),
+            too_large: false,
+        }
+    });
+
+    FileDiff {
+        diff,
+        hunks,
+        file_path: file_path.to_string(),
+        submodule: None,
+        binary,
+    }
+}
+
+/// Diff two buffers with `git diff --no-index`, for options libgit2 cannot
+/// honour. Both are written to temporary files, without any filters applied.
+pub(crate) fn git_cli_buffer_diff(
+    old: &[u8],
+    new: &[u8],
+    config: &DiffConfig,
+) -> Result<Diff<'static>> {
+    let dir = tempfile::Builder::new()
+        .prefix("gitpow-diff-")
+        .tempdir()
+        .context("Failed to create a directory for git diff")?;
+    let mut command = Command::new("git");
+    command.args(["-c", "core.autocrlf=false"]);
+    if config.max_file_size > 0 {
+        command
+            .arg("-c")
+            .arg(format!("core.bigFileThreshold={}", config.max_file_size));
+    }
+    let output = fs::write(dir.path().join("old"), old)
+        .and_then(|_| fs::write(dir.path().join("new"), new))
+        .and_then(|_| {
+            command
+                .args(["diff", "--no-index"])
+                .args(["--no-color", "--no-ext-diff", "--no-textconv"])
+                .args(config.git_args())
+                .args(["old", "new"])
+                .current_dir(dir.path())
+                .output()
+        });
+    let output = output.context("Failed to run git diff")?;
+    // `--no-index` exits with 1 when the files differ
+    if !output.status.success() && output.status.code() != Some(1) {
+        bail!(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+    Ok(Diff::from_buffer(&output.stdout)?)
+}
+
+impl GitRepository {
+    /// Validate request diff options; size and line limits the request leaves
+    /// out come from `gitpow.diffMaxFileSize` and `gitpow.diffMaxLines`
+    pub fn diff_config(&self, settings: &DiffSettings) -> Result<DiffConfig> {
+        let mut config = DiffConfig::parse(settings)?;
+        let repo_config = self.repo.config()?;
+        let setting = |name: &str| repo_config.get_i64(name).ok().map(|n| n.max(0));
+        if is_unset(&settings.max_file_size) {
+            if let Some(n) = setting("gitpow.diffMaxFileSize") {
+                config.max_file_size = n as u64;
+            }
+        }
+        if is_unset(&settings.max_lines) {
+            if let Some(n) = setting("gitpow.diffMaxLines") {
+                config.max_lines = n as usize;
+            }
+        }
+        Ok(config)
+    }
+
+    /// Id of the empty tree, which git resolves without it being stored
+    pub(crate) fn empty_tree_id() -> Result<Oid> {
+        Ok(Oid::hash_object(ObjectType::Tree, &[])?)
+    }
+
+    /// Diff one file with `git diff <revs> -- <paths>`, for options libgit2
+    /// cannot honour
+    pub(crate) fn git_cli_file_diff(
+        &self,
+        revs: &[&str],
+        paths: &[&str],
+        file_path: &str,
+        config: &DiffConfig,
+    ) -> Result<FileDiff> {
+        let mut command = Command::new("git");
+        command.args(["-c", "core.quotePath=false"]);
+        if config.max_file_size > 0 {
+            // git diffs bigger files as binary
+            command
+                .arg("-c")
+                .arg(format!("core.bigFileThreshold={}", config.max_file_size));
+        }
+        let output = command
+            .arg("diff")
+            .args([
+                "--stat=1000",
+                "--patch",
+                "--no-color",
+                "--no-ext-diff",
+                "--no-textconv",
+                "--no-renames",
+                "--src-prefix=a/",
+                "--dst-prefix=b/",
+            ])
+            .args(config.git_args())
+            .args(revs)
+            .arg("--")
+            .args(paths)
+            .current_dir(self.path())
+            .output()
+            .context("Failed to run git diff")?;
+        // `--no-index` implies `--exit-code`, so 1 only means the files differ
+        // there; any other failure is an error
+        let differ = revs.contains(&"--no-index") && output.status.code() == Some(1);
+        if !output.status.success() && !differ {
+            bail!(String::from_utf8_lossy(&output.stderr).to_string());
+        }
+        let mut diff = parse_unified_diff(&String::from_utf8_lossy(&output.stdout), file_path);
+        if let Some(binary) = &mut diff.binary {
+            binary.too_large = [binary.old_size, binary.new_size]
+                .into_iter()
+                .flatten()
+                .any(|size| config.too_large(size));
+        }
+        Ok(diff)
+    }
+
+    /// Dense combined diff of one file in a merge: only lines where the result
+    /// differs from every parent. Each line starts with one column per parent.
+    pub fn combined_file_diff(
+        &self,
+        commit: &git2::Commit<'_>,
+        file_path: &str,
+        config: &DiffConfig,
+    ) -> Result<FileDiff> {
+        let mut revs = vec!["--cc".to_string(), commit.id().to_string()];
+        revs.extend(commit.parent_ids().map(|id| id.to_string()));
+        let revs: Vec<&str> = revs.iter().map(String::as_str).collect();
+        self.git_cli_file_diff(&revs, &[file_path], file_path, config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(lines: &[&str]) -> DiffHunkData {
+        let header = parse_hunk_header(lines[0]).unwrap();
+        DiffHunkData {
+            old_start: header.old_start,
+            old_count: header.old_count,
+            new_start: header.new_start,
+            new_count: header.new_count,
+            lines: lines.iter().map(|line| line.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn parses_settings_and_rejects_bad_values() {
+        let config = DiffConfig::parse(&DiffSettings::default()).unwrap();
+        assert_eq!(config.context, DEFAULT_CONTEXT);
+        assert_eq!(config.max_lines, DEFAULT_MAX_LINES);
+        assert!(!config.needs_git_cli());
+
+        let config = DiffConfig::parse(&DiffSettings {
+            context: Some("full".to_string()),
+            ignore_blank_lines: Some("1".to_string()),
+            algorithm: Some("histogram".to_string()),
+            max_lines: Some("0".to_string()),
+            ..DiffSettings::default()
+        })
+        .unwrap();
+        assert_eq!(config.context, FULL_CONTEXT);
+        assert!(config.ignore_blank_lines);
+        assert!(config.needs_git_cli());
+        assert_eq!(config.max_lines, 0);
+
+        for settings in [
+            DiffSettings {
+                context: Some("-1".to_string()),
+                ..DiffSettings::default()
+            },
+            DiffSettings {
+                whitespace: Some("ignore".to_string()),
+                ..DiffSettings::default()
+            },
+            DiffSettings {
+                indent_heuristic: Some("yes".to_string()),
+                ..DiffSettings::default()
+            },
+            DiffSettings {
+                hunk_offset: Some("next".to_string()),
+                ..DiffSettings::default()
+            },
+        ] {
+            assert!(DiffConfig::parse(&settings).is_err(), "{:?}", settings);
+        }
+    }
+
+    #[test]
+    fn parses_a_parent_number_or_combined() {
+        assert_eq!(DiffParent::parse(None).unwrap(), DiffParent::Parent(0));
+        assert_eq!(DiffParent::parse(Some("2")).unwrap(), DiffParent::Parent(1));
+        assert_eq!(
+            DiffParent::parse(Some("combined")).unwrap(),
+            DiffParent::Combined
+        );
+        assert!(DiffParent::parse(Some("0")).is_err());
+        assert!(DiffParent::Parent(2).resolve(2).is_err());
+        assert_eq!(
+            DiffParent::Combined.resolve(1).unwrap(),
+            DiffParent::Parent(0)
+        );
+    }
+
+    #[test]
+    fn tells_which_sides_a_line_is_on() {
+        assert_eq!(line_sides(" a", 1), [true, true]);
+        assert_eq!(line_sides("-a", 1), [true, false]);
+        assert_eq!(line_sides("+a", 1), [false, true]);
+        assert_eq!(line_sides("", 1), [true, true]);
+        // Combined diff: removed from the first parent only
+        assert_eq!(line_sides("- a", 2), [true, false, false]);
+        // Added relative to the second parent only
+        assert_eq!(line_sides(" +a", 2), [true, false, true]);
+    }
+
+    #[test]
+    fn cuts_a_hunk_part_with_its_own_header() {
+        let whole = hunk(&["@@ -10,4 +10,5 @@", " a", "-b", "+B", "+C", " d", " e"]);
+
+        let part = hunk_part(&whole, 3..6);
+        assert_eq!(part.lines, ["@@ -12,1 +11,3 @@", "+B", "+C", " d"]);
+        assert_eq!((part.old_start, part.old_count), (12, 1));
+        assert_eq!((part.new_start, part.new_count), (11, 3));
+
+        // No old lines: the range starts at the line before the part
+        let part = hunk_part(&whole, 3..5);
+        assert_eq!(part.lines[0], "@@ -11,0 +11,2 @@");
+
+        // The whole body gives the original ranges back
+        let part = hunk_part(&whole, 1..7);
+        assert_eq!(part.lines, whole.lines);
+    }
+
+    #[test]
+    fn cuts_a_combined_hunk_part() {
+        let whole = hunk(&["@@@ -1,2 -1,2 +1,3 @@@", "  a", " +b", "++c", "  d"]);
+        let part = hunk_part(&whole, 2..4);
+        assert_eq!(part.lines, ["@@@ -2,1 -1,0 +2,2 @@@", " +b", "++c"]);
+        assert_eq!((part.old_start, part.old_count), (2, 1));
+        assert_eq!((part.new_start, part.new_count), (2, 2));
+    }
+
+    #[test]
+    fn parses_git_diff_output_like_libgit2() {
+        let output = [
+            " my file.txt | 2 +-",
+            " 1 file changed",
+            "",
+            "diff --git a/my file.txt b/my file.txt",
+            "index 1111111..2222222 100644",
+            "--- a/my file.txt\t",
+            "+++ b/my file.txt\t",
+            "@@ -1,2 +1,2 @@ fn main() {",
+            " keep",
+            "-old",
+            "+new",
+            "\\ No newline at end of file",
+            "@@ -9 +9,0 @@",
+            "-gone",
+        ]
+        .join("\n");
+        let diff = parse_unified_diff(&output, "my file.txt");
+        assert!(diff.binary.is_none());
+        assert_eq!(diff.hunks.len(), 2);
+        assert_eq!(
+            diff.hunks[0].lines,
+            ["@@ -1,2 +1,2 @@", " keep", "-old", "+new"]
+        );
+        assert_eq!(diff.hunks[1].lines, ["@@ -9,1 +9,0 @@", "-gone"]);
+        assert_eq!((diff.hunks[1].new_start, diff.hunks[1].new_count), (9, 0));
+        assert!(diff
+            .diff
+            .starts_with("--- a/my file.txt\n+++ b/my file.txt\n@@ -1,2 +1,2 @@\n"));
+        assert!(diff.diff.contains("\\ No newline at end of file\n"));
+    }
+
+    #[test]
+    fn reads_binary_sizes_from_the_stat() {
+        let output = " img.png | Bin 120 -> 4096 bytes\n 1 file changed\n\n\
+                      diff --git a/img.png b/img.png\n\
+                      Binary files a/img.png and b/img.png differ\n";
+        let binary = parse_unified_diff(output, "img.png").binary.unwrap();
+        assert_eq!((binary.old_size, binary.new_size), (Some(120), Some(4096)));
+
+        let output = " img.png | Bin 0 -> 4096 bytes\n\n\
+                      Binary files /dev/null and b/img.png differ\n";
+        let binary = parse_unified_diff(output, "img.png").binary.unwrap();
+        assert_eq!((binary.old_size, binary.new_size), (None, Some(4096)));
+    }
+}
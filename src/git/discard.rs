@@ -0,0 +1,472 @@
+use std::fs;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::DateTime;
+use git2::build::CheckoutBuilder;
+use git2::{Index, Oid, Signature, Status, StatusOptions, TreeWalkMode, TreeWalkResult};
+
+use crate::git::repository::GitRepository;
+use crate::git::staging::{file_mode, index_entry, LineSelection};
+use crate::models::{
+    CleanRequest, DiscardRequest, DiscardResponse, DiscardSnapshot, DiscardedFile,
+};
+
+/// Hidden ref whose first-parent chain holds one commit per discard. Each
+/// commit's tree has the working tree content of the files it destroyed.
+const SNAPSHOT_REF: &str = "refs/gitpow/discarded";
+
+/// Larger files (typically build output in ignored folders) cannot be saved;
+/// `DiscardedFile.lost` reports them and destroying them takes `force`.
+const MAX_SNAPSHOT_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+const SNAPSHOT_LIST_LIMIT: usize = 50;
+
+const WORKTREE_CHANGES: Status = Status::WT_MODIFIED
+    .union(Status::WT_DELETED)
+    .union(Status::WT_TYPECHANGE)
+    .union(Status::WT_RENAMED);
+
+fn discarded(path: &str, action: &str) -> DiscardedFile {
+    DiscardedFile {
+        path: path.to_string(),
+        action: action.to_string(),
+        saved: false,
+        lost: false,
+    }
+}
+
+fn matches_paths(path: &str, paths: &[String]) -> bool {
+    paths.is_empty()
+        || paths.iter().any(|p| {
+            let p = p.trim_end_matches('/');
+            path == p || path.starts_with(&format!("{}/", p))
+        })
+}
+
+/// Limit a status scan to `paths`; "." (or nothing) means the whole repository.
+/// Paths are taken literally, so `*.log` or `a[1].txt` only name themselves.
+fn add_pathspecs(opts: &mut StatusOptions, paths: &[String]) {
+    for path in paths {
+        let path = path.trim_end_matches('/');
+        if !path.is_empty() && path != "." {
+            opts.pathspec(path);
+        }
+    }
+    opts.disable_pathspec_match(true);
+}
+
+impl GitRepository {
+    /// Throw away working tree changes: whole files (tracked files go back
+    /// to their staged content, untracked files are deleted) or, for a single
+    /// path, only the selected hunks/lines. Everything is saved to a snapshot first.
+    pub fn discard_changes(&self, req: &DiscardRequest) -> Result<DiscardResponse> {
+        let config = self.diff_config(&req.options)?;
+        if let Some(selection) =
+            LineSelection::from_request(req.hunks.as_deref(), req.lines.as_deref(), config)
+        {
+            let path = match req.paths.as_slice() {
+                [path] => path,
+                _ => bail!("Hunks and lines can only be discarded from a single file"),
+            };
+            let files = if selection.is_empty() {
+                Vec::new()
+            } else {
+                vec![discarded(path, "rewritten")]
+            };
+            return self.with_snapshot(files, req.dry_run, req.force, "Discard lines", |_| {
+                self.discard_lines(path, &selection)
+            });
+        }
+
+        if req.paths.is_empty() {
+            bail!("No paths to discard");
+        }
+
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .exclude_submodules(true);
+        add_pathspecs(&mut opts, &req.paths);
+
+        let mut files = Vec::new();
+        for entry in self.repo.statuses(Some(&mut opts))?.iter() {
+            let (status, path) = match entry.path() {
+                Some(path) => (entry.status(), path),
+                None => continue,
+            };
+            if status.is_conflicted() {
+                continue;
+            }
+            if status.is_wt_new() {
+                files.push(discarded(path, "deleted"));
+            } else if status.intersects(WORKTREE_CHANGES) {
+                files.push(discarded(path, "reverted"));
+            }
+        }
+        if !req.dry_run {
+            self.check_nested_repos(&files, req.remove_nested_repos)?;
+        }
+
+        self.with_snapshot(files, req.dry_run, req.force, "Discard", |files| {
+            let mut checkout = CheckoutBuilder::new();
+            checkout.force().disable_pathspec_match(true);
+            let mut revert = false;
+            for file in files {
+                if file.action == "deleted" {
+                    self.remove_workdir_file(&file.path)?;
+                } else {
+                    checkout.path(&file.path);
+                    revert = true;
+                }
+            }
+            if revert {
+                self.repo.checkout_index(None, Some(&mut checkout))?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Delete untracked files and, on request, ignored ones (`git clean`),
+    /// saving them to a snapshot first.
+    pub fn clean(&self, req: &CleanRequest) -> Result<DiscardResponse> {
+        let with_ignored = req.ignored || req.only_ignored;
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(!req.only_ignored)
+            .recurse_untracked_dirs(true)
+            .include_ignored(with_ignored)
+            .recurse_ignored_dirs(with_ignored)
+            .exclude_submodules(true);
+        add_pathspecs(&mut opts, &req.paths);
+
+        let files: Vec<DiscardedFile> = self
+            .repo
+            .statuses(Some(&mut opts))?
+            .iter()
+            .filter(|entry| {
+                let status = entry.status();
+                (status.is_wt_new() && !req.only_ignored) || (status.is_ignored() && with_ignored)
+            })
+            .filter_map(|entry| entry.path().map(|path| discarded(path, "deleted")))
+            .collect();
+        if !req.dry_run {
+            self.check_nested_repos(&files, req.remove_nested_repos)?;
+        }
+
+        self.with_snapshot(files, req.dry_run, req.force, "Clean", |files| {
+            for file in files {
+                self.remove_workdir_file(&file.path)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Saved snapshots, newest first
+    pub fn discard_snapshots(&self) -> Result<Vec<DiscardSnapshot>> {
+        let mut commit = match self.repo.find_reference(SNAPSHOT_REF) {
+            Ok(reference) => reference.peel_to_commit()?,
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        let mut snapshots = Vec::new();
+        loop {
+            let date = DateTime::from_timestamp(commit.time().seconds(), 0)
+                .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap())
+                .to_rfc3339();
+            snapshots.push(DiscardSnapshot {
+                id: commit.id().to_string(),
+                message: commit.summary().unwrap_or("").to_string(),
+                date,
+                files: self
+                    .snapshot_blobs(commit.id())?
+                    .into_iter()
+                    .map(|(path, _, _)| path)
+                    .collect(),
+            });
+
+            if snapshots.len() >= SNAPSHOT_LIST_LIMIT {
+                break;
+            }
+            commit = match commit.parent(0) {
+                Ok(parent) => parent,
+                Err(_) => break,
+            };
+        }
+        Ok(snapshots)
+    }
+
+    /// Write files from a snapshot (the latest by default) back into the
+    /// working tree. Content they replace is saved to a new snapshot.
+    pub fn restore_snapshot(
+        &self,
+        snapshot: Option<&str>,
+        paths: &[String],
+        force: bool,
+    ) -> Result<DiscardResponse> {
+        let id = match snapshot {
+            Some(id) => self.find_snapshot(id)?,
+            None => self
+                .repo
+                .refname_to_id(SNAPSHOT_REF)
+                .map_err(|_| anyhow!("No discarded changes to restore"))?,
+        };
+
+        let blobs: Vec<_> = self
+            .snapshot_blobs(id)?
+            .into_iter()
+            .filter(|(path, _, _)| matches_paths(path, paths))
+            .collect();
+        if blobs.is_empty() {
+            bail!("Nothing to restore from snapshot {}", id);
+        }
+
+        // Only content that differs from the snapshot is at risk
+        let overwritten: Vec<_> = blobs
+            .iter()
+            .filter(|(path, blob_id, _)| {
+                let full_path = self.path().join(path);
+                full_path.symlink_metadata().is_ok()
+                    && Oid::hash_file(git2::ObjectType::Blob, &full_path).ok() != Some(*blob_id)
+            })
+            .map(|(path, _, _)| discarded(path, "restored"))
+            .collect();
+        let mut response =
+            self.with_snapshot(overwritten, false, force, "Restore", |_| Ok(()))?;
+
+        for (path, blob_id, mode) in &blobs {
+            let blob = self.repo.find_blob(*blob_id)?;
+            self.write_workdir_file(path, blob.content(), *mode)?;
+        }
+
+        response.files = blobs
+            .iter()
+            .map(|(path, _, _)| DiscardedFile {
+                saved: response.files.iter().any(|f| &f.path == path && f.saved),
+                lost: response.files.iter().any(|f| &f.path == path && f.lost),
+                ..discarded(path, "restored")
+            })
+            .collect();
+        Ok(response)
+    }
+
+    /// Save the files about to be destroyed, then run `destroy`. A dry run
+    /// only reports which files would be touched, saved or lost. Files that
+    /// cannot be saved stop everything unless `force` is set.
+    fn with_snapshot(
+        &self,
+        mut files: Vec<DiscardedFile>,
+        dry_run: bool,
+        force: bool,
+        what: &str,
+        destroy: impl FnOnce(&[DiscardedFile]) -> Result<()>,
+    ) -> Result<DiscardResponse> {
+        if files.is_empty() {
+            return Ok(DiscardResponse {
+                dry_run,
+                files,
+                snapshot: None,
+            });
+        }
+
+        let mut saves = Vec::new();
+        for file in files.iter_mut() {
+            let full_path = self.path().join(&file.path);
+            let meta = match fs::symlink_metadata(&full_path) {
+                Ok(meta) => meta,
+                // Deleted in the working tree: nothing to lose
+                Err(_) => continue,
+            };
+
+            let mode = if meta.file_type().is_symlink() {
+                0o120000
+            } else if meta.is_file() && meta.len() <= MAX_SNAPSHOT_FILE_SIZE {
+                file_mode(&meta)
+            } else {
+                file.lost = true;
+                continue;
+            };
+            file.saved = true;
+            saves.push((file.path.clone(), full_path, mode));
+        }
+
+        if dry_run {
+            return Ok(DiscardResponse {
+                dry_run,
+                files,
+                snapshot: None,
+            });
+        }
+        let lost: Vec<&str> = files
+            .iter()
+            .filter(|f| f.lost)
+            .map(|f| f.path.as_str())
+            .collect();
+        if !lost.is_empty() && !force {
+            bail!(
+                "{} file(s) cannot be saved to a snapshot and would be lost: {}. Use force to go ahead anyway",
+                lost.len(),
+                lost.join(", ")
+            );
+        }
+
+        let mut index = Index::new()?;
+        for (path, full_path, mode) in saves {
+            let content = if mode == 0o120000 {
+                fs::read_link(&full_path)?
+                    .to_string_lossy()
+                    .into_owned()
+                    .into_bytes()
+            } else {
+                fs::read(&full_path)?
+            };
+            let mut entry = index_entry(&path, mode);
+            entry.id = self.repo.blob(&content)?;
+            entry.file_size = content.len() as u32;
+            index.add(&entry)?;
+        }
+
+        let snapshot = if index.is_empty() {
+            None
+        } else {
+            let tree = self.repo.find_tree(index.write_tree_to(&self.repo)?)?;
+            let parent = self
+                .repo
+                .find_reference(SNAPSHOT_REF)
+                .ok()
+                .and_then(|r| r.peel_to_commit().ok());
+            let signature = self
+                .repo
+                .signature()
+                .or_else(|_| Signature::now("gitpow", "gitpow@localhost"))?;
+            let saved: Vec<&str> = files
+                .iter()
+                .filter(|f| f.saved)
+                .map(|f| f.path.as_str())
+                .collect();
+            let message = format!("{} {} file(s)\n\n{}\n", what, saved.len(), saved.join("\n"));
+            let id = self.repo.commit(
+                Some(SNAPSHOT_REF),
+                &signature,
+                &signature,
+                &message,
+                &tree,
+                &parent.iter().collect::<Vec<_>>(),
+            )?;
+            Some(id.to_string())
+        };
+
+        destroy(&files)?;
+        Ok(DiscardResponse {
+            dry_run,
+            files,
+            snapshot,
+        })
+    }
+
+    /// Accept a snapshot id (or unique prefix) only if it is on the snapshot chain
+    fn find_snapshot(&self, id: &str) -> Result<Oid> {
+        let id = id.trim();
+        let mut commit = self
+            .repo
+            .find_reference(SNAPSHOT_REF)
+            .and_then(|r| r.peel_to_commit())
+            .map_err(|_| anyhow!("No discarded changes to restore"))?;
+        loop {
+            if id.len() >= 4 && commit.id().to_string().starts_with(id) {
+                return Ok(commit.id());
+            }
+            commit = commit
+                .parent(0)
+                .map_err(|_| anyhow!("Unknown snapshot {}", id))?;
+        }
+    }
+
+    fn snapshot_blobs(&self, id: Oid) -> Result<Vec<(String, Oid, u32)>> {
+        let tree = self.repo.find_commit(id)?.tree()?;
+        let mut blobs = Vec::new();
+        tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.kind() == Some(git2::ObjectType::Blob) {
+                if let Some(name) = entry.name() {
+                    blobs.push((
+                        format!("{}{}", dir, name),
+                        entry.id(),
+                        entry.filemode() as u32,
+                    ));
+                }
+            }
+            TreeWalkResult::Ok
+        })?;
+        Ok(blobs)
+    }
+
+    /// An untracked repository inside the working tree is listed as a single
+    /// directory and cannot be snapshotted; deleting it takes an explicit flag
+    fn check_nested_repos(&self, files: &[DiscardedFile], allowed: bool) -> Result<()> {
+        if allowed {
+            return Ok(());
+        }
+        let nested: Vec<&str> = files
+            .iter()
+            .filter(|file| file.action == "deleted")
+            .filter(|file| self.path().join(&file.path).join(".git").exists())
+            .map(|file| file.path.as_str())
+            .collect();
+        if !nested.is_empty() {
+            bail!(
+                "{} nested repositor{} would be deleted: {}. Use removeNestedRepos to go ahead anyway",
+                nested.len(),
+                if nested.len() == 1 { "y" } else { "ies" },
+                nested.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    fn remove_workdir_file(&self, path: &str) -> Result<()> {
+        let full_path = self.path().join(path);
+        match fs::symlink_metadata(&full_path) {
+            Ok(meta) if meta.is_dir() => fs::remove_dir_all(&full_path)?,
+            Ok(_) => fs::remove_file(&full_path)?,
+            Err(_) => return Ok(()),
+        }
+
+        // Drop directories the removal left empty, like `git clean -d`
+        let mut dir = full_path.parent();
+        while let Some(d) = dir {
+            if d == self.path() || fs::remove_dir(d).is_err() {
+                break;
+            }
+            dir = d.parent();
+        }
+        Ok(())
+    }
+
+    fn write_workdir_file(&self, path: &str, content: &[u8], mode: u32) -> Result<()> {
+        let full_path = self.path().join(path);
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if full_path.symlink_metadata().is_ok() {
+            self.remove_workdir_file(path)?;
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+
+        if mode == 0o120000 {
+            #[cfg(unix)]
+            {
+                let target = String::from_utf8_lossy(content).into_owned();
+                std::os::unix::fs::symlink(target, &full_path)?;
+                return Ok(());
+            }
+        }
+
+        fs::write(&full_path, content)?;
+        #[cfg(unix)]
+        if mode == 0o100755 {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&full_path, fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(())
+    }
+}
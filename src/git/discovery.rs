@@ -0,0 +1,69 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::Repo;
+
+/// Create a user-facing ID string from a repository path.
+/// On Windows, strip any extended-length prefix (\\?\C:\...) for readability.
+pub fn make_repo_id(path: &Path) -> String {
+    let raw = path.to_string_lossy().to_string();
+    if cfg!(windows) && raw.starts_with(r"\\?\") {
+        raw[4..].to_string()
+    } else {
+        raw
+    }
+}
+
+/// Check if a path is hidden (starts with '.').
+fn is_hidden(p: &Path) -> bool {
+    p.file_name()
+        .and_then(|s| s.to_str())
+        .map(|s| s.starts_with('.'))
+        .unwrap_or(false)
+}
+
+/// Find the git repositories under `repos_root`: the folder itself if it is a
+/// repository, plus any direct (non-hidden) child directory containing `.git`.
+pub fn discover_repo_paths(repos_root: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+
+    let self_is_repo = repos_root.join(".git").exists();
+    if self_is_repo {
+        paths.push(repos_root.to_path_buf());
+    }
+
+    let entries = match fs::read_dir(repos_root) {
+        Ok(entries) => entries,
+        // Folder itself is a repo; treat missing read_dir as "no additional repos".
+        Err(_) if self_is_repo => return Ok(paths),
+        Err(e) => return Err(e),
+    };
+
+    for entry in entries.flatten() {
+        if !entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
+            continue;
+        }
+        let full_path = repos_root.join(entry.file_name());
+        if is_hidden(&full_path) {
+            continue;
+        }
+        if full_path.join(".git").exists() {
+            paths.push(full_path);
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Build the repo list entry for a repository directory.
+pub fn repo_entry(path: &Path) -> Repo {
+    let name = path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string());
+    Repo {
+        id: make_repo_id(path),
+        name,
+        worktree_of: None,
+    }
+}
@@ -0,0 +1,388 @@
+//! Background fetch scheduler.
+//!
+//! Every repository under `repos_root` is fetched on its own interval, which can be
+//! configured (or turned off) per repo. Authentication and network failures back off
+//! exponentially so an offline laptop or a revoked key doesn't hammer the remote.
+//! The same bookkeeping is used for on-demand fetches (branch listing, the fetch
+//! button) so the status endpoint always reflects the most recent attempt.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::git::discovery::{discover_repo_paths, make_repo_id, repo_entry};
+use crate::git::repository::GitRepository;
+use crate::models::{FetchPolicy, RepoFetchStatus};
+
+/// Scheduler shared by the HTTP server and the desktop app.
+pub static FETCH_SCHEDULER: Lazy<FetchScheduler> = Lazy::new(FetchScheduler::new);
+
+/// Interval used for repos without an explicit `gitpow.fetchInterval` setting.
+pub const DEFAULT_FETCH_INTERVAL_SECS: u64 = 300;
+
+/// How often the background loop looks for repos that are due.
+pub const SCHEDULER_TICK: Duration = Duration::from_secs(30);
+
+/// Minimum time between on-demand fetches for the same repository (60 seconds).
+/// Prevents fetch storms when the frontend polls the branch list frequently.
+const ON_DEMAND_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Smallest interval a repo can be scheduled at.
+const MIN_INTERVAL_SECS: u64 = 30;
+
+/// Upper bound for the failure backoff.
+const MAX_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FetchOutcome {
+    Success,
+    AuthError,
+    NetworkError,
+    Error,
+}
+
+impl FetchOutcome {
+    fn classify(err: &anyhow::Error) -> Self {
+        match err.downcast_ref::<git2::Error>() {
+            Some(e) if e.code() == git2::ErrorCode::Auth => FetchOutcome::AuthError,
+            Some(e) if e.code() == git2::ErrorCode::Certificate => FetchOutcome::NetworkError,
+            Some(e)
+                if matches!(
+                    e.class(),
+                    git2::ErrorClass::Net
+                        | git2::ErrorClass::Http
+                        | git2::ErrorClass::Ssh
+                        | git2::ErrorClass::Ssl
+                        | git2::ErrorClass::Os
+                ) =>
+            {
+                FetchOutcome::NetworkError
+            }
+            _ => FetchOutcome::Error,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            FetchOutcome::Success => "success",
+            FetchOutcome::AuthError => "auth-error",
+            FetchOutcome::NetworkError => "network-error",
+            FetchOutcome::Error => "error",
+        }
+    }
+
+    fn should_back_off(&self) -> bool {
+        matches!(self, FetchOutcome::AuthError | FetchOutcome::NetworkError)
+    }
+}
+
+#[derive(Default)]
+struct RepoFetchState {
+    path: PathBuf,
+    policy: Option<FetchPolicy>,
+    in_flight: bool,
+    consecutive_failures: u32,
+    last_started: Option<DateTime<Utc>>,
+    last_finished: Option<Instant>,
+    last_duration: Option<Duration>,
+    last_outcome: Option<FetchOutcome>,
+    last_error: Option<String>,
+    next_run: Option<DateTime<Utc>>,
+}
+
+impl RepoFetchState {
+    /// Delay before the next scheduled run, taking failure backoff into account.
+    fn next_delay(&self, policy: &FetchPolicy) -> Duration {
+        let interval = Duration::from_secs(policy.interval_secs.max(MIN_INTERVAL_SECS));
+        if self.consecutive_failures == 0 {
+            return interval;
+        }
+        let exponent = (self.consecutive_failures - 1).min(16);
+        interval.saturating_mul(1 << exponent).min(MAX_BACKOFF.max(interval))
+    }
+
+    fn is_backing_off(&self, now: DateTime<Utc>) -> bool {
+        self.consecutive_failures > 0
+            && self.last_outcome.map(|o| o.should_back_off()).unwrap_or(false)
+            && self.next_run.map(|next| next > now).unwrap_or(false)
+    }
+}
+
+/// Why a fetch is being run. Scheduled and on-demand fetches respect the repo's
+/// policy and backoff; manual fetches always run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FetchTrigger {
+    Scheduled,
+    OnDemand,
+    Manual,
+}
+
+pub struct FetchScheduler {
+    repos: Mutex<HashMap<String, RepoFetchState>>,
+    default_interval_secs: AtomicU64,
+}
+
+impl FetchScheduler {
+    fn new() -> Self {
+        Self {
+            repos: Mutex::new(HashMap::new()),
+            default_interval_secs: AtomicU64::new(DEFAULT_FETCH_INTERVAL_SECS),
+        }
+    }
+
+    /// Set the interval used by repos without an explicit setting (0 = off).
+    pub fn set_default_interval(&self, secs: u64) {
+        self.default_interval_secs.store(secs, Ordering::Relaxed);
+    }
+
+    fn default_policy(&self) -> FetchPolicy {
+        let secs = self.default_interval_secs.load(Ordering::Relaxed);
+        FetchPolicy {
+            enabled: secs > 0,
+            interval_secs: if secs > 0 { secs } else { DEFAULT_FETCH_INTERVAL_SECS },
+            is_default: true,
+        }
+    }
+
+    fn load_policy(&self, repo_path: &Path) -> FetchPolicy {
+        let setting = GitRepository::open(repo_path)
+            .ok()
+            .and_then(|r| r.fetch_interval_setting());
+        match setting {
+            Some(0) => FetchPolicy {
+                enabled: false,
+                interval_secs: self.default_policy().interval_secs,
+                is_default: false,
+            },
+            Some(secs) => FetchPolicy {
+                enabled: true,
+                interval_secs: secs.max(MIN_INTERVAL_SECS),
+                is_default: false,
+            },
+            None => self.default_policy(),
+        }
+    }
+
+    /// Current policy for a repository, loading it from git config on first use.
+    pub fn policy(&self, repo_path: &Path) -> FetchPolicy {
+        let key = make_repo_id(repo_path);
+        if let Some(policy) = self
+            .repos
+            .lock()
+            .unwrap()
+            .get(&key)
+            .and_then(|state| state.policy)
+        {
+            return policy;
+        }
+        let policy = self.load_policy(repo_path);
+        let mut repos = self.repos.lock().unwrap();
+        let state = repos.entry(key).or_default();
+        state.path = repo_path.to_path_buf();
+        state.policy = Some(policy);
+        policy
+    }
+
+    /// Start tracking a repository that lives outside the scanned folders
+    /// (or that just appeared, e.g. a fresh clone) so the scheduler picks it up.
+    pub fn register(&self, repo_path: &Path) {
+        self.policy(repo_path);
+    }
+
+    /// Change a repository's policy. `interval_secs` of `None` while enabled
+    /// resets the repo to the app-wide default.
+    pub fn set_policy(
+        &self,
+        repo_path: &Path,
+        enabled: bool,
+        interval_secs: Option<u64>,
+    ) -> anyhow::Result<FetchPolicy> {
+        let git_repo = GitRepository::open(repo_path)?;
+        let setting = match (enabled, interval_secs) {
+            (false, _) => Some(0),
+            (true, Some(secs)) => Some(secs.max(MIN_INTERVAL_SECS)),
+            (true, None) => None,
+        };
+        git_repo.set_fetch_interval_setting(setting)?;
+
+        let policy = self.load_policy(repo_path);
+        let mut repos = self.repos.lock().unwrap();
+        let state = repos.entry(make_repo_id(repo_path)).or_default();
+        state.path = repo_path.to_path_buf();
+        state.policy = Some(policy);
+        state.next_run = if policy.enabled {
+            let base = state.last_started.unwrap_or_else(Utc::now);
+            Some(base + chrono::Duration::from_std(state.next_delay(&policy)).unwrap_or_default())
+        } else {
+            None
+        };
+        Ok(policy)
+    }
+
+    /// Decide whether a fetch should start now and mark it in flight if so.
+    fn begin(&self, repo_path: &Path, trigger: FetchTrigger) -> bool {
+        let policy = self.policy(repo_path);
+        let now = Utc::now();
+        let mut repos = self.repos.lock().unwrap();
+        let state = repos.entry(make_repo_id(repo_path)).or_default();
+        state.path = repo_path.to_path_buf();
+
+        if state.in_flight {
+            return false;
+        }
+
+        let allowed = match trigger {
+            FetchTrigger::Manual => true,
+            FetchTrigger::Scheduled => {
+                policy.enabled && state.next_run.map(|next| next <= now).unwrap_or(true)
+            }
+            FetchTrigger::OnDemand => {
+                policy.enabled
+                    && !state.is_backing_off(now)
+                    && state
+                        .last_finished
+                        .map(|t| t.elapsed() >= ON_DEMAND_COOLDOWN)
+                        .unwrap_or(true)
+            }
+        };
+
+        if allowed {
+            state.in_flight = true;
+            state.last_started = Some(now);
+        }
+        allowed
+    }
+
+    fn finish(&self, repo_path: &Path, started: Instant, result: &anyhow::Result<()>) {
+        let policy = self.policy(repo_path);
+        let mut repos = self.repos.lock().unwrap();
+        let state = repos.entry(make_repo_id(repo_path)).or_default();
+
+        let outcome = match result {
+            Ok(()) => FetchOutcome::Success,
+            Err(e) => FetchOutcome::classify(e),
+        };
+
+        state.in_flight = false;
+        state.last_finished = Some(Instant::now());
+        state.last_duration = Some(started.elapsed());
+        state.last_outcome = Some(outcome);
+        state.last_error = result.as_ref().err().map(|e| e.to_string());
+        if outcome.should_back_off() {
+            state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        } else {
+            state.consecutive_failures = 0;
+        }
+        state.next_run = if policy.enabled {
+            Some(Utc::now() + chrono::Duration::from_std(state.next_delay(&policy)).unwrap_or_default())
+        } else {
+            None
+        };
+    }
+
+    /// Fetch a repository if the trigger allows it. Blocking; call from a worker thread.
+    /// Returns `None` when the fetch was skipped (policy off, cooldown, backoff or
+    /// another fetch already running).
+    pub fn fetch(&self, repo_path: &Path, trigger: FetchTrigger) -> Option<anyhow::Result<()>> {
+        if !self.begin(repo_path, trigger) {
+            return None;
+        }
+
+        tracing::debug!("Fetch ({:?}) started for {}", trigger, repo_path.display());
+        let started = Instant::now();
+        let result = GitRepository::open(repo_path).and_then(|git_repo| git_repo.fetch_all());
+        self.finish(repo_path, started, &result);
+
+        match &result {
+            Ok(()) => tracing::debug!("Fetch for {} completed.", repo_path.display()),
+            Err(e) => tracing::warn!("Fetch for repo '{}' failed: {}", repo_path.display(), e),
+        }
+        Some(result)
+    }
+
+    /// Run every scheduled fetch that is due for the repositories under `repos_root`,
+    /// plus any repository that was registered from elsewhere (e.g. opened by absolute path).
+    pub fn run_due(&self, repos_root: &Path) {
+        let mut paths = discover_repo_paths(repos_root).unwrap_or_default();
+        for path in self.known_paths() {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        for path in paths {
+            self.fetch(&path, FetchTrigger::Scheduled);
+        }
+    }
+
+    fn known_paths(&self) -> Vec<PathBuf> {
+        self.repos
+            .lock()
+            .unwrap()
+            .values()
+            .map(|state| state.path.clone())
+            .filter(|path| !path.as_os_str().is_empty() && path.exists())
+            .collect()
+    }
+
+    /// Fetch status for a single repository.
+    pub fn status(&self, repo_path: &Path) -> RepoFetchStatus {
+        let policy = self.policy(repo_path);
+        let entry = repo_entry(repo_path);
+        let repos = self.repos.lock().unwrap();
+        let state = repos.get(&entry.id);
+
+        RepoFetchStatus {
+            id: entry.id,
+            name: entry.name,
+            policy,
+            in_progress: state.map(|s| s.in_flight).unwrap_or(false),
+            consecutive_failures: state.map(|s| s.consecutive_failures).unwrap_or(0),
+            last_fetch: state.and_then(|s| s.last_started).map(|t| t.to_rfc3339()),
+            last_duration_ms: state
+                .and_then(|s| s.last_duration)
+                .map(|d| d.as_millis() as u64),
+            last_result: state
+                .and_then(|s| s.last_outcome)
+                .map(|o| o.as_str().to_string()),
+            last_error: state.and_then(|s| s.last_error.clone()),
+            next_run: if policy.enabled {
+                // Repos that were never fetched are due on the next scheduler tick.
+                Some(
+                    state
+                        .and_then(|s| s.next_run)
+                        .unwrap_or_else(Utc::now)
+                        .to_rfc3339(),
+                )
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Fetch status for every repository under `repos_root`.
+    pub fn statuses(&self, repos_root: &Path) -> std::io::Result<Vec<RepoFetchStatus>> {
+        let paths = discover_repo_paths(repos_root)?;
+        Ok(paths.iter().map(|path| self.status(path)).collect())
+    }
+}
+
+/// Background loop that runs due fetches for the repositories under `repos_root`.
+/// Spawn it once on the async runtime at startup.
+pub async fn run_scheduler(repos_root: PathBuf) {
+    // Start after one tick so launching the app doesn't fetch every repo at once.
+    let mut ticker =
+        tokio::time::interval_at(tokio::time::Instant::now() + SCHEDULER_TICK, SCHEDULER_TICK);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let root = repos_root.clone();
+        // Fetches are blocking libgit2 calls; keep them off the async workers.
+        if let Err(e) = tokio::task::spawn_blocking(move || FETCH_SCHEDULER.run_due(&root)).await {
+            tracing::warn!("Fetch scheduler tick failed: {}", e);
+        }
+    }
+}
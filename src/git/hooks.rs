@@ -0,0 +1,303 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crate::git::repository::GitRepository;
+use crate::models::{HookInfo, HookListResponse, HookRun};
+
+/// Hooks git runs on the client side, in the order of `githooks(5)`
+const KNOWN_HOOKS: &[&str] = &[
+    "applypatch-msg",
+    "pre-applypatch",
+    "post-applypatch",
+    "pre-commit",
+    "pre-merge-commit",
+    "prepare-commit-msg",
+    "commit-msg",
+    "post-commit",
+    "pre-rebase",
+    "post-checkout",
+    "post-merge",
+    "pre-push",
+    "post-rewrite",
+    "pre-auto-gc",
+    "reference-transaction",
+    "push-to-checkout",
+    "sendemail-validate",
+    "fsmonitor-watchman",
+    "post-index-change",
+];
+
+/// Hooks whose stdout git reads as data; they are passed through untouched
+const PROTOCOL_HOOKS: &[&str] = &["fsmonitor-watchman", "reference-transaction"];
+
+/// Suffix a disabled hook is renamed to, so git no longer finds it
+const DISABLED_SUFFIX: &str = ".disabled";
+
+/// A git command that failed because one of its hooks did. Carried inside
+/// `anyhow::Error` so services can report the hook runs.
+#[derive(Debug)]
+pub struct HookFailure {
+    pub hook: String,
+    pub exit_code: Option<i32>,
+    pub hooks: Vec<HookRun>,
+}
+
+impl fmt::Display for HookFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.exit_code {
+            Some(code) => write!(f, "The {} hook failed with exit code {}", self.hook, code)?,
+            None => write!(f, "The {} hook failed", self.hook)?,
+        }
+        let output = self
+            .hooks
+            .iter()
+            .rev()
+            .find(|run| run.hook == self.hook)
+            .map(|run| {
+                if run.stderr.trim().is_empty() {
+                    run.stdout.trim()
+                } else {
+                    run.stderr.trim()
+                }
+            })
+            .unwrap_or("");
+        if !output.is_empty() {
+            write!(f, ": {}", output)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for HookFailure {}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::metadata(path)
+            .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+fn make_executable(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut permissions = fs::metadata(path)?.permissions();
+        permissions.set_mode(permissions.mode() | 0o111);
+        fs::set_permissions(path, permissions)?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Quote a path for a POSIX shell script
+fn sh_quote(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    #[cfg(windows)]
+    let path = path.replace('\\', "/");
+    format!("'{}'", path.replace('\'', r"'\''"))
+}
+
+/// Wrapper installed in place of a hook: runs the real one with its output
+/// and exit code recorded in the capture directory
+fn wrapper_script(hook: &str, real: &Path, capture_dir: &Path) -> String {
+    let real = sh_quote(real);
+    if PROTOCOL_HOOKS.contains(&hook) {
+        return format!("#!/bin/sh\nexec {} \"$@\"\n", real);
+    }
+    let out = |ext: &str| sh_quote(&capture_dir.join(format!("{}.{}", hook, ext)));
+    format!(
+        "#!/bin/sh\n\
+         echo {hook} >>{order}\n\
+         {real} \"$@\" >{stdout} 2>{stderr}\n\
+         code=$?\n\
+         echo $code >{status}\n\
+         exit $code\n",
+        hook = hook,
+        order = sh_quote(&capture_dir.join("order")),
+        real = real,
+        stdout = out("stdout"),
+        stderr = out("stderr"),
+        status = out("status"),
+    )
+}
+
+/// Hook runs recorded by the wrappers, in the order git ran them
+fn read_hook_runs(capture_dir: &Path) -> Vec<HookRun> {
+    let order = fs::read_to_string(capture_dir.join("order")).unwrap_or_default();
+    let mut runs: Vec<HookRun> = Vec::new();
+    for hook in order.lines().map(str::trim).filter(|h| !h.is_empty()) {
+        // A hook that runs more than once is reported once, with its last output
+        runs.retain(|run| run.hook != hook);
+        let read = |ext: &str| {
+            let bytes = fs::read(capture_dir.join(format!("{}.{}", hook, ext))).unwrap_or_default();
+            String::from_utf8_lossy(&bytes).to_string()
+        };
+        let exit_code = read("status").trim().parse::<i32>().ok();
+        runs.push(HookRun {
+            hook: hook.to_string(),
+            success: exit_code == Some(0),
+            exit_code,
+            stdout: read("stdout"),
+            stderr: read("stderr"),
+        });
+    }
+    runs
+}
+
+impl GitRepository {
+    /// Directory git takes hooks from: `core.hooksPath` (relative to the
+    /// working tree) or the `hooks` folder of the common git directory
+    pub fn hooks_dir(&self) -> PathBuf {
+        let configured = self
+            .repo
+            .config()
+            .and_then(|config| config.get_path("core.hooksPath"))
+            .ok();
+        match configured {
+            Some(path) if path.is_absolute() => path,
+            Some(path) => self.repo.workdir().unwrap_or(self.repo.path()).join(path),
+            None => self.repo.commondir().join("hooks"),
+        }
+    }
+
+    /// Installed hooks, enabled or disabled; `.sample` files are not listed
+    pub fn list_hooks(&self) -> Result<HookListResponse> {
+        let dir = self.hooks_dir();
+        let mut hooks = Vec::new();
+        for &name in KNOWN_HOOKS {
+            let path = dir.join(name);
+            let disabled_path = dir.join(format!("{}{}", name, DISABLED_SUFFIX));
+            if path.is_file() {
+                hooks.push(HookInfo {
+                    name: name.to_string(),
+                    enabled: is_executable(&path),
+                    path: path.to_string_lossy().to_string(),
+                });
+            } else if disabled_path.is_file() {
+                hooks.push(HookInfo {
+                    name: name.to_string(),
+                    enabled: false,
+                    path: disabled_path.to_string_lossy().to_string(),
+                });
+            }
+        }
+        Ok(HookListResponse {
+            hooks_path: dir.to_string_lossy().to_string(),
+            hooks,
+        })
+    }
+
+    /// Enable a hook (restoring its name and executable bit) or disable it by
+    /// renaming it to `<name>.disabled`
+    pub fn set_hook_enabled(&self, name: &str, enabled: bool) -> Result<HookInfo> {
+        if !KNOWN_HOOKS.contains(&name) {
+            bail!("Unknown hook: {}", name);
+        }
+        let dir = self.hooks_dir();
+        let path = dir.join(name);
+        let disabled_path = dir.join(format!("{}{}", name, DISABLED_SUFFIX));
+
+        if enabled {
+            if !path.exists() {
+                if !disabled_path.exists() {
+                    bail!("Hook {} is not installed", name);
+                }
+                fs::rename(&disabled_path, &path)
+                    .with_context(|| format!("Failed to enable hook {}", name))?;
+            }
+            make_executable(&path)?;
+        } else if path.exists() {
+            if disabled_path.exists() {
+                bail!("{} already exists", disabled_path.display());
+            }
+            fs::rename(&path, &disabled_path)
+                .with_context(|| format!("Failed to disable hook {}", name))?;
+        } else if !disabled_path.exists() {
+            bail!("Hook {} is not installed", name);
+        }
+
+        Ok(HookInfo {
+            name: name.to_string(),
+            enabled,
+            path: if enabled { path } else { disabled_path }
+                .to_string_lossy()
+                .to_string(),
+        })
+    }
+
+    /// Run a git command that may trigger hooks, recording each hook's output
+    /// and exit code. Hooks are pointed at wrapper scripts for the duration of
+    /// the command through `-c core.hooksPath`. When the command fails because
+    /// a hook did, the error is a `HookFailure`.
+    pub fn run_git_with_hooks(&self, args: &[&str]) -> Result<(String, Vec<HookRun>)> {
+        let real_dir = self.hooks_dir();
+        let installed: Vec<&str> = KNOWN_HOOKS
+            .iter()
+            .copied()
+            .filter(|hook| is_executable(&real_dir.join(hook)))
+            .collect();
+        if installed.is_empty() {
+            return Ok((self.run_git(args)?, Vec::new()));
+        }
+
+        // A fresh private directory, removed when dropped
+        let capture_dir = tempfile::Builder::new()
+            .prefix("gitpow-hooks-")
+            .tempdir()
+            .context("Failed to create hook capture directory")?;
+        self.run_with_wrapped_hooks(args, &installed, &real_dir, capture_dir.path())
+    }
+
+    fn run_with_wrapped_hooks(
+        &self,
+        args: &[&str],
+        installed: &[&str],
+        real_dir: &Path,
+        capture_dir: &Path,
+    ) -> Result<(String, Vec<HookRun>)> {
+        let wrapper_dir = capture_dir.join("hooks");
+        fs::create_dir_all(&wrapper_dir)?;
+        for &hook in installed {
+            let wrapper = wrapper_dir.join(hook);
+            fs::write(
+                &wrapper,
+                wrapper_script(hook, &real_dir.join(hook), capture_dir),
+            )?;
+            make_executable(&wrapper)?;
+        }
+
+        let output = Command::new("git")
+            .arg("-c")
+            .arg(format!("core.hooksPath={}", wrapper_dir.to_string_lossy()))
+            .args(args)
+            .current_dir(self.path())
+            .output()
+            .with_context(|| format!("Failed to run git with args {:?}", args))?;
+        let hooks = read_hook_runs(capture_dir);
+
+        if !output.status.success() {
+            if let Some(failed) = hooks.iter().rev().find(|run| !run.success) {
+                return Err(HookFailure {
+                    hook: failed.hook.clone(),
+                    exit_code: failed.exit_code,
+                    hooks: hooks.clone(),
+                }
+                .into());
+            }
+            bail!(String::from_utf8_lossy(&output.stderr).to_string());
+        }
+        Ok((String::from_utf8_lossy(&output.stdout).to_string(), hooks))
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::git::repository::GitRepository;
+use crate::models::{IgnorePatternRequest, IgnorePatternResponse, IgnoreRule};
+
+/// Ignore file name accepted for `IgnorePatternRequest.file`, besides `.gitignore` files
+pub const INFO_EXCLUDE: &str = "info/exclude";
+
+/// Rewrite a pattern from `<dir>/.gitignore` so it means the same thing as a
+/// repository-level rule: anchored patterns get the directory prefix, the
+/// others may match at any depth below it
+fn rule_for_dir(pattern: &str, dir: &str) -> String {
+    if dir.is_empty() {
+        return pattern.to_string();
+    }
+    let (negate, body) = match pattern.strip_prefix('!') {
+        Some(body) => ("!", body),
+        None => ("", pattern),
+    };
+    let anchored = body.trim_end_matches('/').contains('/');
+    if anchored {
+        format!("{}{}/{}", negate, dir, body.trim_start_matches('/'))
+    } else {
+        format!("{}{}/**/{}", negate, dir, body)
+    }
+}
+
+impl GitRepository {
+    /// The rule that ignores each path, with `git check-ignore -v` semantics
+    pub fn ignore_rules(&self, paths: &[&str]) -> Result<HashMap<String, IgnoreRule>> {
+        let mut rules = HashMap::new();
+        if paths.is_empty() {
+            return Ok(rules);
+        }
+
+        // NUL-separated paths in and out, so no path is ever quoted
+        let mut child = Command::new("git")
+            .args(["check-ignore", "-v", "-z", "--stdin"])
+            .current_dir(self.path())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to run git check-ignore")?;
+        let mut input = Vec::new();
+        for path in paths {
+            input.extend_from_slice(path.as_bytes());
+            input.push(0);
+        }
+        let mut stdin = child
+            .stdin
+            .take()
+            .context("No stdin for git check-ignore")?;
+        let writer = thread::spawn(move || stdin.write_all(&input));
+        let output = child.wait_with_output()?;
+        writer
+            .join()
+            .map_err(|_| anyhow!("Failed to write to git check-ignore"))??;
+        // Exit code 1 only means none of the paths is ignored
+        if !output.status.success() && output.status.code() != Some(1) {
+            bail!(String::from_utf8_lossy(&output.stderr).to_string());
+        }
+
+        // source NUL line NUL pattern NUL path NUL
+        let fields: Vec<String> = output
+            .stdout
+            .split(|b| *b == 0)
+            .map(|field| String::from_utf8_lossy(field).to_string())
+            .collect();
+        for record in fields.chunks_exact(4) {
+            if record[0].is_empty() {
+                continue;
+            }
+            rules.insert(
+                record[3].clone(),
+                IgnoreRule {
+                    source: record[0].clone(),
+                    line: record[1].parse().unwrap_or(0),
+                    pattern: record[2].clone(),
+                },
+            );
+        }
+        Ok(rules)
+    }
+
+    /// Resolve the ignore file a request names: `info/exclude` or a
+    /// `.gitignore` inside the working tree. Returns its path and the
+    /// directory its patterns are relative to.
+    fn ignore_file(&self, file: &str) -> Result<(PathBuf, String)> {
+        if file == INFO_EXCLUDE {
+            return Ok((self.repo.commondir().join(INFO_EXCLUDE), String::new()));
+        }
+
+        let rel = Path::new(file);
+        if rel.file_name().and_then(|n| n.to_str()) != Some(".gitignore")
+            || rel.components().any(|c| !matches!(c, Component::Normal(_)))
+        {
+            bail!(
+                "Ignore file must be {} or a .gitignore inside the working tree",
+                INFO_EXCLUDE
+            );
+        }
+        let dir = rel
+            .parent()
+            .map(|dir| dir.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        Ok((self.path().join(rel), dir))
+    }
+
+    /// Untracked files, listed one by one (directories expanded)
+    fn untracked_files(&self) -> Result<Vec<String>> {
+        let output = self.run_git_bytes(&["ls-files", "--others", "--exclude-standard", "-z"])?;
+        Ok(output
+            .split(|b| *b == 0)
+            .filter(|path| !path.is_empty())
+            .map(|path| String::from_utf8_lossy(path).to_string())
+            .collect())
+    }
+
+    /// Append a pattern to an ignore file, or with `dry_run` only report which
+    /// untracked files it would hide
+    pub fn add_ignore_pattern(&self, req: &IgnorePatternRequest) -> Result<IgnorePatternResponse> {
+        let pattern = req.pattern.trim_end();
+        if pattern.trim().is_empty() || pattern.contains('\n') {
+            bail!("Pattern must be a single non-empty line");
+        }
+        let file = req.file.as_deref().unwrap_or(".gitignore");
+        let (path, dir) = self.ignore_file(file)?;
+
+        // Evaluate the new rule in memory, on top of the current ones
+        let untracked = self.untracked_files()?;
+        self.repo.add_ignore_rule(&rule_for_dir(pattern, &dir))?;
+        let mut hidden = Vec::new();
+        for file in untracked {
+            if self.repo.status_should_ignore(Path::new(&file))? {
+                hidden.push(file);
+            }
+        }
+        self.repo.clear_ignore_rules()?;
+
+        if !req.dry_run {
+            // Only looked at for a missing final newline; the file is appended to
+            let existing = match fs::read(&path) {
+                Ok(existing) => existing,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => bail!("Cannot read {}: {}", file, e),
+            };
+            let mut line = String::new();
+            if !existing.is_empty() && !existing.ends_with(b"\n") {
+                line.push('\n');
+            }
+            line.push_str(pattern);
+            line.push('\n');
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)?
+                .write_all(line.as_bytes())?;
+        }
+
+        Ok(IgnorePatternResponse {
+            dry_run: req.dry_run,
+            file: file.to_string(),
+            pattern: pattern.to_string(),
+            hidden,
+        })
+    }
+}
@@ -0,0 +1,231 @@
+use crate::models::DiffSpan;
+
+/// Longer lines are left without intraline spans; the token LCS is quadratic
+const MAX_LINE_TOKENS: usize = 300;
+
+/// Paired lines sharing less than this fraction of their text are shown as
+/// whole-line changes, since highlighting nearly everything adds nothing
+const MIN_SHARED_RATIO: f64 = 0.3;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    Space,
+    Other,
+}
+
+fn token_kind(c: char) -> TokenKind {
+    if c.is_alphanumeric() || c == '_' {
+        TokenKind::Word
+    } else if c.is_whitespace() {
+        TokenKind::Space
+    } else {
+        TokenKind::Other
+    }
+}
+
+/// Split a line into words, whitespace runs and single punctuation characters,
+/// as (start, end) character ranges
+fn tokenize(chars: &[char]) -> Vec<(usize, usize)> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let kind = token_kind(chars[start]);
+        let mut end = start + 1;
+        if kind != TokenKind::Other {
+            while end < chars.len() && token_kind(chars[end]) == kind {
+                end += 1;
+            }
+        }
+        tokens.push((start, end));
+        start = end;
+    }
+    tokens
+}
+
+/// Which tokens of `a` and `b` are outside their longest common subsequence
+fn changed_tokens(a: &[&[char]], b: &[&[char]]) -> (Vec<bool>, Vec<bool>) {
+    let (n, m) = (a.len(), b.len());
+    let mut lcs = vec![vec![0u16; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut changed_a = vec![true; n];
+    let mut changed_b = vec![true; m];
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a[i] == b[j] {
+            changed_a[i] = false;
+            changed_b[j] = false;
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    (changed_a, changed_b)
+}
+
+/// Merge the changed tokens of one line into spans; changed tokens separated
+/// only by whitespace become one span
+fn spans_for(
+    line: usize,
+    chars: &[char],
+    tokens: &[(usize, usize)],
+    changed: &[bool],
+) -> Vec<DiffSpan> {
+    let mut spans: Vec<DiffSpan> = Vec::new();
+    let mut gap_start: Option<usize> = None;
+    for (idx, &(start, end)) in tokens.iter().enumerate() {
+        if !changed[idx] {
+            let whitespace = chars[start..end].iter().all(|c| c.is_whitespace());
+            if !whitespace {
+                gap_start = None;
+            } else if gap_start.is_none() {
+                gap_start = Some(start);
+            }
+            continue;
+        }
+        match spans.last_mut() {
+            Some(last) if last.end == start || gap_start.is_some_and(|g| g == last.end) => {
+                last.end = end;
+            }
+            _ => spans.push(DiffSpan { line, start, end }),
+        }
+        gap_start = None;
+    }
+    spans
+}
+
+/// Intraline spans for one removed/added line pair. Columns count characters
+/// of the line content, after the `-`/`+` prefix.
+fn pair_spans(old_idx: usize, old: &str, new_idx: usize, new: &str) -> Vec<DiffSpan> {
+    let old_chars: Vec<char> = old.chars().collect();
+    let new_chars: Vec<char> = new.chars().collect();
+    let old_tokens = tokenize(&old_chars);
+    let new_tokens = tokenize(&new_chars);
+    if old_tokens.len() > MAX_LINE_TOKENS || new_tokens.len() > MAX_LINE_TOKENS {
+        return Vec::new();
+    }
+
+    let old_words: Vec<&[char]> = old_tokens.iter().map(|&(s, e)| &old_chars[s..e]).collect();
+    let new_words: Vec<&[char]> = new_tokens.iter().map(|&(s, e)| &new_chars[s..e]).collect();
+    let (changed_old, changed_new) = changed_tokens(&old_words, &new_words);
+
+    let shared: usize = old_tokens
+        .iter()
+        .zip(&changed_old)
+        .filter(|(_, changed)| !**changed)
+        .map(|(&(s, e), _)| e - s)
+        .sum();
+    let longest = old_chars.len().max(new_chars.len());
+    if longest == 0 || (shared as f64) < (longest as f64) * MIN_SHARED_RATIO {
+        return Vec::new();
+    }
+
+    let mut spans = spans_for(old_idx, &old_chars, &old_tokens, &changed_old);
+    spans.extend(spans_for(new_idx, &new_chars, &new_tokens, &changed_new));
+    spans
+}
+
+/// Pair each block of removed lines with the added lines right after it
+/// (first with first, and so on) and compute word-level change spans.
+/// `lines` are hunk lines as in `DiffHunk.lines`, header included.
+pub fn hunk_spans(lines: &[String]) -> Vec<DiffSpan> {
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if !lines[i].starts_with('-') {
+            i += 1;
+            continue;
+        }
+        let removed_start = i;
+        while i < lines.len() && lines[i].starts_with('-') {
+            i += 1;
+        }
+        let added_start = i;
+        while i < lines.len() && lines[i].starts_with('+') {
+            i += 1;
+        }
+
+        let removed = added_start - removed_start;
+        let added = i - added_start;
+        for k in 0..removed.min(added) {
+            let (old_idx, new_idx) = (removed_start + k, added_start + k);
+            spans.extend(pair_spans(
+                old_idx,
+                &lines[old_idx][1..],
+                new_idx,
+                &lines[new_idx][1..],
+            ));
+        }
+    }
+    spans.sort_by_key(|span| (span.line, span.start));
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(lines: &[&str]) -> Vec<(usize, usize, usize)> {
+        let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        hunk_spans(&lines)
+            .into_iter()
+            .map(|span| (span.line, span.start, span.end))
+            .collect()
+    }
+
+    #[test]
+    fn marks_changed_words_with_header_inclusive_line_indices() {
+        let found = spans(&["@@ -1 +1 @@", "-let x = 1;", "+let x = 2;"]);
+        assert_eq!(found, [(1, 8, 9), (2, 8, 9)]);
+    }
+
+    #[test]
+    fn joins_changed_words_separated_by_whitespace() {
+        let found = spans(&["@@ -1 +1 @@", "-foo bar baz", "+qux quux baz"]);
+        assert_eq!(found, [(1, 0, 7), (2, 0, 8)]);
+    }
+
+    #[test]
+    fn counts_columns_in_characters() {
+        let found = spans(&["@@ -1 +1 @@", "-héllo wörld", "+héllo world"]);
+        assert_eq!(found, [(1, 6, 11), (2, 6, 11)]);
+    }
+
+    #[test]
+    fn pairs_removed_and_added_lines_in_order() {
+        let found = spans(&[
+            "@@ -1,3 +1,2 @@",
+            " context",
+            "-one a",
+            "-two b",
+            "+one c",
+            " context",
+        ]);
+        assert_eq!(found, [(2, 4, 5), (4, 4, 5)]);
+    }
+
+    #[test]
+    fn skips_lines_with_little_in_common() {
+        assert!(spans(&["@@ -1 +1 @@", "-abc", "+xyz"]).is_empty());
+        assert!(spans(&["@@ -1 +1 @@", "-", "+"]).is_empty());
+    }
+
+    #[test]
+    fn skips_very_long_lines() {
+        let old = format!("-{}", "a ".repeat(MAX_LINE_TOKENS));
+        let new = format!("+{}b", "a ".repeat(MAX_LINE_TOKENS));
+        assert!(spans(&["@@ -1 +1 @@", &old, &new]).is_empty());
+    }
+}
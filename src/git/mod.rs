@@ -1 +1,20 @@
+pub mod clone;
+pub mod commit;
+pub mod commit_lint;
+pub mod compare;
+pub mod discard;
+pub mod diff_options;
+pub mod discovery;
+pub mod fetch_scheduler;
+pub mod hooks;
+pub mod ignore;
+pub mod intraline;
+pub mod patch;
 pub mod repository;
+pub mod shallow;
+pub mod signing;
+pub mod staging;
+pub mod status;
+pub mod stash;
+pub mod submodules;
+pub mod worktrees;
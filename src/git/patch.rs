@@ -0,0 +1,526 @@
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use git2::{ApplyLocation, ApplyOptions, Diff, Oid, Patch, RepositoryState, RevparseMode, Sort};
+
+use crate::git::diff_options::DiffConfig;
+use crate::git::repository::GitRepository;
+use crate::models::{
+    ExportedPatch, FormatPatchRequest, FormatPatchResponse, PatchApplyFile, PatchApplyRequest,
+    PatchApplyResponse, PatchFileStat, PatchHunkResult, PatchResponse,
+};
+
+/// Where an uploaded patch is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchTarget {
+    Worktree,
+    Index,
+    /// One commit per mbox message, as `git am`
+    Commits,
+}
+
+impl PatchTarget {
+    pub fn parse(value: Option<&str>) -> Result<Self> {
+        match value.map(str::trim) {
+            None | Some("") | Some("worktree") => Ok(PatchTarget::Worktree),
+            Some("index") => Ok(PatchTarget::Index),
+            Some("commits") => Ok(PatchTarget::Commits),
+            Some(other) => bail!(
+                "Invalid target: {} (expected worktree, index or commits)",
+                other
+            ),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PatchTarget::Worktree => "worktree",
+            PatchTarget::Index => "index",
+            PatchTarget::Commits => "commits",
+        }
+    }
+}
+
+/// Commits a patch spans: `base..tip`, or a single commit whose base is its
+/// first parent (none for a root commit)
+struct PatchRange {
+    base: Option<Oid>,
+    tip: Oid,
+    single: bool,
+}
+
+impl GitRepository {
+    /// Resolve a commit or a `A..B` / `A...B` range
+    fn patch_range(&self, spec: &str) -> Result<PatchRange> {
+        let spec = spec.trim();
+        if spec.is_empty() || spec.starts_with('-') {
+            bail!("Invalid revision: {}", spec);
+        }
+        let revspec = self
+            .repo
+            .revparse(spec)
+            .with_context(|| format!("Unknown revision {}", spec))?;
+
+        if revspec.mode().contains(RevparseMode::SINGLE) {
+            let commit = revspec.from().context("Empty revision")?.peel_to_commit()?;
+            let base = if commit.parent_count() > 0 {
+                Some(commit.parent_id(0)?)
+            } else {
+                None
+            };
+            return Ok(PatchRange {
+                base,
+                tip: commit.id(),
+                single: true,
+            });
+        }
+
+        let (Some(from), Some(to)) = (revspec.from(), revspec.to()) else {
+            bail!("A range needs both ends: {}", spec);
+        };
+        let from = from.peel_to_commit()?.id();
+        let to = to.peel_to_commit()?.id();
+        let base = if revspec.mode().contains(RevparseMode::MERGE_BASE) {
+            self.repo.merge_base(from, to)?
+        } else {
+            from
+        };
+        Ok(PatchRange {
+            base: Some(base),
+            tip: to,
+            single: false,
+        })
+    }
+
+    /// `-M`/`-C` or `--no-renames`, following `gitpow.renameThreshold`
+    pub(crate) fn rename_args(&self) -> Vec<String> {
+        match self.rename_threshold() {
+            0 => vec!["--no-renames".to_string()],
+            threshold => vec![format!("-M{}%", threshold), format!("-C{}%", threshold)],
+        }
+    }
+
+    /// `git diff` between the ends of a range, with `extra` output options
+    fn range_diff_output(
+        &self,
+        from: &str,
+        to: &str,
+        extra: &[&str],
+        config: &DiffConfig,
+    ) -> Result<Vec<u8>> {
+        let output = Command::new("git")
+            .args(["-c", "core.quotePath=false", "diff"])
+            .args(["--no-color", "--no-ext-diff", "--no-textconv"])
+            .args(self.rename_args())
+            .args(config.git_args())
+            .args(extra)
+            .args([from, to])
+            .current_dir(self.path())
+            .output()
+            .context("Failed to run git diff")?;
+        if !output.status.success() {
+            bail!(String::from_utf8_lossy(&output.stderr).to_string());
+        }
+        Ok(output.stdout)
+    }
+
+    /// The complete patch of a commit or range: per-file stats, the
+    /// `--stat`/`--summary` header and every file's diff, with binary files
+    /// shown as "Binary files ... differ"
+    pub fn get_patch(&self, spec: &str, config: &DiffConfig) -> Result<PatchResponse> {
+        let range = self.patch_range(spec)?;
+        let from = match range.base {
+            Some(base) => base,
+            None => Self::empty_tree_id()?,
+        }
+        .to_string();
+        let to = range.tip.to_string();
+
+        // added TAB deleted TAB path NUL, or for renames: ... TAB NUL old NUL new NUL
+        let numstat = self.range_diff_output(&from, &to, &["--numstat", "-z"], config)?;
+        let mut fields = numstat
+            .split(|b| *b == 0)
+            .map(|field| String::from_utf8_lossy(field).to_string());
+        let mut files = Vec::new();
+        while let Some(record) = fields.next() {
+            let mut parts = record.splitn(3, '\t');
+            let (Some(added), Some(deleted), Some(path)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let (old_path, path) = if path.is_empty() {
+                match (fields.next(), fields.next()) {
+                    (Some(old), Some(new)) => (Some(old), new),
+                    _ => break,
+                }
+            } else {
+                (None, path.to_string())
+            };
+            let binary = added == "-" && deleted == "-";
+            files.push(PatchFileStat {
+                path,
+                old_path,
+                additions: added.parse().unwrap_or(0),
+                deletions: deleted.parse().unwrap_or(0),
+                binary,
+            });
+        }
+
+        let patch = self.range_diff_output(&from, &to, &["--stat", "--summary", "-p"], config)?;
+        Ok(PatchResponse {
+            from: range.base.map(|base| base.to_string()),
+            to,
+            additions: files.iter().map(|f| f.additions).sum(),
+            deletions: files.iter().map(|f| f.deletions).sum(),
+            files,
+            patch: String::from_utf8_lossy(&patch).to_string(),
+        })
+    }
+
+    /// `git format-patch` for a commit or range: one file per patch (plus the
+    /// cover letter), or everything in a single mbox
+    pub fn format_patch(&self, req: &FormatPatchRequest) -> Result<FormatPatchResponse> {
+        let range = self.patch_range(&req.range)?;
+        let mut args = vec!["format-patch".to_string(), "--no-color".to_string()];
+        args.extend(self.rename_args());
+        if req.cover_letter {
+            args.push("--cover-letter".to_string());
+        }
+        if let Some(prefix) = req.subject_prefix.as_deref().map(str::trim) {
+            if prefix.contains(['\n', '\r']) {
+                bail!("Subject prefix must be a single line");
+            }
+            if !prefix.is_empty() {
+                args.push(format!("--subject-prefix={}", prefix));
+            }
+        }
+        match (range.single, range.base) {
+            (false, Some(base)) => args.push(format!("{}..{}", base, range.tip)),
+            _ => args.extend(["-1".to_string(), range.tip.to_string()]),
+        }
+
+        if req.mbox {
+            args.push("--stdout".to_string());
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            let content = self.run_git(&args)?;
+            if content.is_empty() {
+                bail!("No commits to export in {}", req.range.trim());
+            }
+            let short = |oid: Oid| oid.to_string()[..7].to_string();
+            let file_name = match range.base.filter(|_| !range.single) {
+                Some(base) => format!("{}-{}.mbox", short(base), short(range.tip)),
+                None => format!("{}.mbox", short(range.tip)),
+            };
+            return Ok(FormatPatchResponse {
+                files: vec![ExportedPatch { file_name, content }],
+            });
+        }
+
+        let out_dir = tempfile::Builder::new()
+            .prefix("gitpow-format-patch-")
+            .tempdir()
+            .context("Failed to create a directory for the patches")?;
+        let files = self.format_patch_files(&args, out_dir.path())?;
+        if files.is_empty() {
+            bail!("No commits to export in {}", req.range.trim());
+        }
+        Ok(FormatPatchResponse { files })
+    }
+
+    fn format_patch_files(
+        &self,
+        args: &[String],
+        out_dir: &std::path::Path,
+    ) -> Result<Vec<ExportedPatch>> {
+        let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let out_dir_arg = out_dir.to_string_lossy().to_string();
+        args.extend(["-o", &out_dir_arg]);
+        self.run_git(&args)?;
+
+        let mut files = Vec::new();
+        for entry in fs::read_dir(out_dir)? {
+            let entry = entry?;
+            let bytes = fs::read(entry.path())?;
+            files.push(ExportedPatch {
+                file_name: entry.file_name().to_string_lossy().to_string(),
+                content: String::from_utf8_lossy(&bytes).to_string(),
+            });
+        }
+        // 0000-cover-letter.patch, 0001-..., 0002-...
+        files.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        Ok(files)
+    }
+}
+
+/// What the hunks of a patch are checked against
+#[derive(Clone, Copy)]
+enum CheckBase<'a> {
+    Location(ApplyLocation),
+    Tree(&'a git2::Tree<'a>),
+}
+
+impl GitRepository {
+    /// Write an uploaded patch where `git apply`/`git am` can read it. The
+    /// file is removed when dropped.
+    fn temp_patch_file(&self, content: &str) -> Result<tempfile::NamedTempFile> {
+        let mut file = tempfile::Builder::new()
+            .prefix("gitpow-apply-")
+            .suffix(".patch")
+            .tempfile()
+            .context("Failed to create a file for the patch")?;
+        file.write_all(content.as_bytes())?;
+        file.flush()?;
+        Ok(file)
+    }
+
+    /// Whether one hunk of one file (the whole file when `hunk_idx` is None)
+    /// applies on its own, without touching anything
+    fn hunk_applies(
+        &self,
+        diff: &Diff<'_>,
+        delta_idx: usize,
+        hunk_idx: Option<usize>,
+        base: CheckBase<'_>,
+    ) -> bool {
+        let deltas_seen = Cell::new(0usize);
+        let hunks_seen = Cell::new(0usize);
+        let mut opts = ApplyOptions::new();
+        opts.check(true);
+        opts.delta_callback(|_| {
+            let idx = deltas_seen.replace(deltas_seen.get() + 1);
+            idx == delta_idx
+        });
+        opts.hunk_callback(|_| {
+            let idx = hunks_seen.replace(hunks_seen.get() + 1);
+            hunk_idx.is_none_or(|hunk_idx| idx == hunk_idx)
+        });
+        match base {
+            CheckBase::Location(location) => {
+                self.repo.apply(diff, location, Some(&mut opts)).is_ok()
+            }
+            CheckBase::Tree(tree) => self.repo.apply_to_tree(tree, diff, Some(&mut opts)).is_ok(),
+        }
+    }
+
+    /// Check every hunk of a patch separately against the working tree or index
+    fn check_hunks(&self, diff: &Diff<'_>, base: CheckBase<'_>) -> Result<Vec<PatchApplyFile>> {
+        let mut files = Vec::new();
+        for (delta_idx, delta) in diff.deltas().enumerate() {
+            let path_of = |file: git2::DiffFile<'_>| {
+                file.path().map(|p| p.to_string_lossy().replace('\\', "/"))
+            };
+            let path = path_of(delta.new_file())
+                .or_else(|| path_of(delta.old_file()))
+                .unwrap_or_default();
+            let old_path = path_of(delta.old_file()).filter(|old| *old != path);
+
+            let mut hunks = Vec::new();
+            if let Some(patch) = Patch::from_diff(diff, delta_idx)? {
+                for hunk_idx in 0..patch.num_hunks() {
+                    let (hunk, _) = patch.hunk(hunk_idx)?;
+                    hunks.push(PatchHunkResult {
+                        header: String::from_utf8_lossy(hunk.header())
+                            .trim_end()
+                            .to_string(),
+                        applies: self.hunk_applies(diff, delta_idx, Some(hunk_idx), base),
+                    });
+                }
+            }
+            // Files without hunks (creations of empty files, mode changes,
+            // binary patches) are checked as a whole
+            let applies = if hunks.is_empty() {
+                self.hunk_applies(diff, delta_idx, None, base)
+            } else {
+                hunks.iter().all(|hunk| hunk.applies)
+            };
+            files.push(PatchApplyFile {
+                path,
+                old_path,
+                status: if applies { "applied" } else { "rejected" }.to_string(),
+                hunks,
+            });
+        }
+        Ok(files)
+    }
+
+    /// Apply a unified diff or mbox. Every hunk is checked first; unless all of
+    /// them apply, nothing is changed, or with `three_way` the files that do
+    /// not apply are merged (possibly leaving conflicts). Target "commits"
+    /// runs `git am`, which is aborted when a message fails.
+    pub fn apply_patch(&self, req: &PatchApplyRequest) -> Result<PatchApplyResponse> {
+        let target = PatchTarget::parse(req.target.as_deref())?;
+        if req.patch.trim().is_empty() {
+            bail!("The patch is empty");
+        }
+        // A pasted patch may have lost the newline ending its last line
+        let mut patch = req.patch.clone();
+        if !patch.ends_with('\n') {
+            patch.push('\n');
+        }
+        if target == PatchTarget::Commits {
+            return self.apply_mailbox(&patch, req.three_way);
+        }
+
+        let diff = Diff::from_buffer(patch.as_bytes()).context("Not a valid patch")?;
+        if diff.deltas().len() == 0 {
+            bail!("The patch contains no file changes");
+        }
+        let location = match target {
+            PatchTarget::Index => ApplyLocation::Index,
+            _ => ApplyLocation::WorkDir,
+        };
+        let mut files = self.check_hunks(&diff, CheckBase::Location(location))?;
+        let rejected = files.iter().filter(|f| f.status == "rejected").count();
+        let mut response = PatchApplyResponse {
+            applied: false,
+            target: target.as_str().to_string(),
+            files: Vec::new(),
+            commits: Vec::new(),
+            message: None,
+            hooks: Vec::new(),
+        };
+        if rejected > 0 && !req.three_way {
+            response.message = Some(format!(
+                "{} file(s) do not apply; nothing was changed",
+                rejected
+            ));
+            response.files = files;
+            return Ok(response);
+        }
+
+        // Paths unmerged before the apply are not the patch's conflicts
+        let unmerged_before: HashSet<String> = if rejected > 0 {
+            self.conflicted_files()?
+                .into_iter()
+                .map(|file| file.path)
+                .collect()
+        } else {
+            HashSet::new()
+        };
+
+        let patch_file = self.temp_patch_file(&patch)?;
+        let mut command = Command::new("git");
+        command.arg("apply");
+        if target == PatchTarget::Index {
+            command.arg("--cached");
+        }
+        if rejected > 0 {
+            command.arg("--3way");
+        }
+        let output = command
+            .arg(patch_file.path())
+            .current_dir(self.path())
+            .output()
+            .context("Failed to run git apply")?;
+        drop(patch_file);
+
+        if rejected > 0 {
+            // A 3-way apply exits with 1 when it leaves conflicts behind
+            let patched: HashSet<&str> = files.iter().map(|file| file.path.as_str()).collect();
+            let conflicted: HashSet<String> = self
+                .conflicted_files()?
+                .into_iter()
+                .map(|file| file.path)
+                .filter(|path| patched.contains(path.as_str()) && !unmerged_before.contains(path))
+                .collect();
+            if !output.status.success() && conflicted.is_empty() {
+                bail!(String::from_utf8_lossy(&output.stderr).to_string());
+            }
+            for file in files.iter_mut().filter(|f| f.status == "rejected") {
+                file.status = if conflicted.contains(&file.path) {
+                    "conflicted"
+                } else {
+                    "merged"
+                }
+                .to_string();
+            }
+            if !conflicted.is_empty() {
+                response.message = Some(format!(
+                    "{} file(s) have conflicts to resolve",
+                    conflicted.len()
+                ));
+            }
+        } else if !output.status.success() {
+            bail!(String::from_utf8_lossy(&output.stderr).to_string());
+        }
+
+        response.applied = true;
+        response.files = files;
+        Ok(response)
+    }
+
+    /// `git am` an mbox, one commit per message. When a message fails the
+    /// whole series is rolled back and its hunks are reported.
+    fn apply_mailbox(&self, patch: &str, three_way: bool) -> Result<PatchApplyResponse> {
+        // The abort below must only undo a session this call started
+        if self.repo.state() != RepositoryState::Clean
+            || self.repo.path().join("rebase-apply").exists()
+            || self.repo.path().join("rebase-merge").exists()
+        {
+            bail!("An am, rebase or merge is already in progress; finish or abort it first");
+        }
+        let head_before = self.repo.head().ok().and_then(|head| head.target());
+        let patch_file = self.temp_patch_file(patch)?;
+        let patch_path = patch_file.path().to_string_lossy().to_string();
+        let mut args = vec!["am"];
+        if three_way {
+            args.push("--3way");
+        }
+        args.push(&patch_path);
+        let result = self.run_git_with_hooks(&args);
+        drop(patch_file);
+
+        let mut response = PatchApplyResponse {
+            applied: false,
+            target: PatchTarget::Commits.as_str().to_string(),
+            files: Vec::new(),
+            commits: Vec::new(),
+            message: None,
+            hooks: Vec::new(),
+        };
+        match result {
+            Ok((_, hooks)) => {
+                let mut walk = self.repo.revwalk()?;
+                walk.push_head()?;
+                if let Some(before) = head_before {
+                    walk.hide(before)?;
+                }
+                walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+                response.commits = walk
+                    .map(|oid| oid.map(|oid| oid.to_string()))
+                    .collect::<Result<_, _>>()?;
+                response.applied = true;
+                response.hooks = hooks;
+                Ok(response)
+            }
+            Err(e) => {
+                // am stopped part way: report the message that failed, then
+                // put the branch back where it was. No session existed before
+                // this call, so the one left behind is its own.
+                if !self.repo.path().join("rebase-apply").exists() {
+                    return Err(e);
+                }
+                // am applies each message on top of HEAD; with --3way the
+                // working tree already holds the failed merge
+                let head_tree = self.repo.head().and_then(|head| head.peel_to_tree()).ok();
+                let current = self.run_git(&["am", "--show-current-patch=diff"]).ok();
+                if let (Some(tree), Some(current)) = (&head_tree, current) {
+                    if let Ok(diff) = Diff::from_buffer(current.as_bytes()) {
+                        response.files = self.check_hunks(&diff, CheckBase::Tree(tree))?;
+                    }
+                }
+                self.run_git(&["am", "--abort"])?;
+                if e.is::<crate::git::hooks::HookFailure>() {
+                    return Err(e);
+                }
+                response.message = Some(e.to_string().trim().to_string());
+                Ok(response)
+            }
+        }
+    }
+}
@@ -5,7 +5,13 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use crate::models::{BranchInfo, BranchMetadata, Commit, StashEntry};
+use crate::git::diff_options::{DiffConfig, DiffParent};
+use crate::models::{
+    BinaryDiff, BranchInfo, BranchMetadata, Commit, HookRun, StashEntry, StashPushRequest,
+};
+
+/// Rename and copy similarity threshold when `gitpow.renameThreshold` is unset
+pub const DEFAULT_RENAME_THRESHOLD: u8 = 50;
 
 /// Run a git command in the specified directory and return stdout as a String.
 /// This is a standalone utility for handlers that don't need a full GitRepository.
@@ -23,11 +29,138 @@ pub fn run_git(args: &[&str], repo_path: &Path) -> Result<String, String> {
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
+/// Reject a branch, tag, commit or path git would take for an option
+pub(crate) fn check_argument(what: &str, value: &str) -> Result<()> {
+    if value.trim().starts_with('-') {
+        bail!("Invalid {}: {}", what, value);
+    }
+    Ok(())
+}
+
 pub struct GitRepository {
     path: PathBuf,
     pub repo: Repository,
 }
 
+/// Sizes of `file_path` when `diff` treated it as binary, which libgit2 does
+/// for binary content, `-diff` in `.gitattributes` and blobs over `max_size`.
+/// The flag is only reliable once the patch has been generated.
+fn binary_delta(
+    repo: &Repository,
+    diff: &git2::Diff<'_>,
+    file_path: &str,
+    config: &DiffConfig,
+) -> Option<BinaryDiff> {
+    let delta = diff
+        .deltas()
+        .find(|delta| delta.new_file().path() == Some(Path::new(file_path)))?;
+    if !delta.flags().is_binary() {
+        return None;
+    }
+    let size = |file: git2::DiffFile<'_>| {
+        if !file.exists() {
+            return None;
+        }
+        if file.size() > 0 || file.id().is_zero() {
+            return Some(file.size());
+        }
+        // Content marked `-diff` is never loaded, so its size is still unknown
+        repo.odb()
+            .and_then(|odb| odb.read_header(file.id()))
+            .map(|(size, _)| size as u64)
+            .ok()
+    };
+    let (old_size, new_size) = (size(delta.old_file()), size(delta.new_file()));
+    Some(BinaryDiff {
+        old_size,
+        new_size,
+        too_large: [old_size, new_size]
+            .into_iter()
+            .flatten()
+            .any(|size| config.too_large(size)),
+    })
+}
+
+/// git's `Binary files a/x and b/y differ` line for a delta
+fn binary_line(delta: &git2::DiffDelta<'_>) -> String {
+    let side = |prefix: &str, file: git2::DiffFile<'_>, missing: bool| match file.path() {
+        Some(path) if !missing => format!("{}{}", prefix, path.to_string_lossy()),
+        _ => "/dev/null".to_string(),
+    };
+    let added = matches!(delta.status(), git2::Delta::Added | git2::Delta::Untracked);
+    let deleted = delta.status() == git2::Delta::Deleted;
+    format!(
+        "Binary files {} and {} differ\n",
+        side("a/", delta.old_file(), added),
+        side("b/", delta.new_file(), deleted)
+    )
+}
+
+/// Parse `git diff-tree`/`git diff` `--raw -z` output into file changes
+pub(crate) fn parse_raw_changes(output: &[u8]) -> Result<Vec<crate::models::FileChange>> {
+    // :old_mode new_mode old_sha new_sha status NUL path NUL [new_path NUL],
+    // or for combined diffs one colon, mode, sha and status letter per parent
+    let mut fields = output
+        .split(|b| *b == 0)
+        .map(|field| String::from_utf8_lossy(field).to_string());
+    let mut changes = Vec::new();
+    while let Some(record) = fields.next() {
+        let parents = record.chars().take_while(|c| *c == ':').count();
+        if parents == 0 {
+            continue;
+        }
+        let parts: Vec<&str> = record[parents..].split(' ').collect();
+        if parts.len() < 2 * (parents + 1) + 1 {
+            bail!("Unexpected diff-tree record: {}", record);
+        }
+        let modes = &parts[..=parents];
+        let code = parts[2 * (parents + 1)];
+        let Some(path) = fields.next() else { break };
+        let (old_path, path) = if parents == 1 && code.starts_with(['R', 'C']) {
+            match fields.next() {
+                Some(new_path) => (Some(path), new_path),
+                None => break,
+            }
+        } else {
+            (None, path)
+        };
+
+        let status = if parents > 1 {
+            // One letter per parent; added or removed only when against all of them
+            match code.chars().next() {
+                Some(first @ ('A' | 'D')) if code.chars().all(|c| c == first) => {
+                    if first == 'A' {
+                        "added"
+                    } else {
+                        "removed"
+                    }
+                }
+                _ => "modified",
+            }
+        } else {
+            match code.chars().next() {
+                Some('A') => "added",
+                Some('D') => "removed",
+                Some('R') => "renamed",
+                Some('C') => "copied",
+                _ => "modified",
+            }
+        };
+        let similarity = old_path.as_ref().and_then(|_| code[1..].parse().ok());
+        let is_submodule = modes.contains(&"160000");
+
+        changes.push(crate::models::FileChange {
+            path,
+            old_path,
+            status: status.to_string(),
+            similarity,
+            is_submodule,
+        });
+    }
+
+    Ok(changes)
+}
+
 impl GitRepository {
     pub fn open(repo_path: &Path) -> Result<Self> {
         let repo = Repository::open(repo_path)
@@ -43,8 +176,12 @@ impl GitRepository {
         &self.path
     }
 
+    /// Fetch every configured remote.
+    /// All remotes are attempted even if one fails; the first failure is returned
+    /// as a `git2::Error` so callers can tell authentication and network problems apart.
     pub fn fetch_all(&self) -> Result<()> {
         let remotes = self.repo.remotes()?;
+        let mut first_error: Option<git2::Error> = None;
         for remote_name in remotes.iter().flatten() {
             let mut remote = self.repo.find_remote(remote_name)?;
             
@@ -72,16 +209,44 @@ impl GitRepository {
             let mut fetch_options = git2::FetchOptions::new();
             fetch_options.remote_callbacks(callbacks);
             
-            // Try to fetch, but don't fail if authentication is not available
-            // This allows the app to work with local repos or repos that don't need auth
+            // Keep going with the remaining remotes so one unreachable remote
+            // doesn't prevent the others from being refreshed.
             if let Err(e) = remote.fetch(&[] as &[&str], Some(&mut fetch_options), None) {
-                // If it's an authentication error, log it but don't fail completely
-                // The branch list can still be populated from local branches
-                if e.code() == git2::ErrorCode::Auth {
-                    eprintln!("Warning: Failed to fetch remote '{}' due to authentication. Using local branches only.", remote_name);
-                    continue;
+                tracing::warn!("Failed to fetch remote '{}': {}", remote_name, e);
+                if first_error.is_none() {
+                    first_error = Some(e);
+                }
+            }
+        }
+        match first_error {
+            Some(e) => Err(e.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Per-repo background fetch interval in seconds, stored in the repository's
+    /// git config as `gitpow.fetchInterval`. `Some(0)` means background fetch is off,
+    /// `None` means the app-wide default applies.
+    pub fn fetch_interval_setting(&self) -> Option<u64> {
+        let config = self.repo.config().ok()?;
+        config
+            .get_i64("gitpow.fetchInterval")
+            .ok()
+            .map(|secs| secs.max(0) as u64)
+    }
+
+    /// Persist the per-repo background fetch interval (0 turns background fetch off).
+    /// Passing `None` removes the setting so the app-wide default applies again.
+    pub fn set_fetch_interval_setting(&self, secs: Option<u64>) -> Result<()> {
+        let mut config = self.repo.config()?;
+        match secs {
+            Some(secs) => config.set_i64("gitpow.fetchInterval", secs as i64)?,
+            None => {
+                if let Err(e) = config.remove("gitpow.fetchInterval") {
+                    if e.code() != git2::ErrorCode::NotFound {
+                        return Err(e.into());
+                    }
                 }
-                return Err(e.into());
             }
         }
         Ok(())
@@ -92,27 +257,61 @@ impl GitRepository {
         self.run_git(&["pull"])
     }
 
-    /// Push changes to the remote for the current branch
-    pub fn push(&self) -> Result<String> {
-        self.run_git(&["push"])
+    /// Push changes to the remote for the current branch, with the output of
+    /// the pre-push hook
+    pub fn push(&self) -> Result<(String, Vec<HookRun>)> {
+        self.run_git_with_hooks(&["push"])
     }
 
     /// Push with upstream tracking for a new branch
-    pub fn push_set_upstream(&self, branch: &str) -> Result<String> {
-        self.run_git(&["push", "-u", "origin", branch])
+    pub fn push_set_upstream(&self, branch: &str) -> Result<(String, Vec<HookRun>)> {
+        self.run_git_with_hooks(&["push", "-u", "origin", branch])
     }
 
-    /// Stash current changes
-    pub fn stash_push(&self, message: Option<&str>) -> Result<String> {
-        match message {
-            Some(msg) => self.run_git(&["stash", "push", "-m", msg]),
-            None => self.run_git(&["stash", "push"]),
+    /// Stash current changes, optionally limited to paths or including untracked/ignored files
+    pub fn stash_push(&self, req: &StashPushRequest) -> Result<String> {
+        if req.include_untracked && req.all {
+            bail!("include_untracked and all are mutually exclusive");
+        }
+        if req.staged && (req.include_untracked || req.all) {
+            bail!("staged cannot be combined with include_untracked or all");
+        }
+        if req.staged && req.keep_index {
+            bail!("staged cannot be combined with keep_index");
+        }
+
+        let mut args = vec!["stash", "push"];
+        if let Some(msg) = req.message.as_deref().filter(|m| !m.trim().is_empty()) {
+            args.push("-m");
+            args.push(msg);
         }
+        if req.include_untracked {
+            args.push("--include-untracked");
+        }
+        if req.all {
+            args.push("--all");
+        }
+        if req.keep_index {
+            args.push("--keep-index");
+        }
+        if req.staged {
+            args.push("--staged");
+        }
+        if !req.paths.is_empty() {
+            args.push("--");
+            args.extend(req.paths.iter().map(|p| p.as_str()));
+        }
+        self.run_git(&args)
     }
 
-    /// Pop the most recent stash
-    pub fn stash_pop(&self) -> Result<String> {
-        self.run_git(&["stash", "pop"])
+    /// Pop a stash; `restore_index` also restores what was staged (`--index`)
+    pub fn stash_pop(&self, stash_ref: &str, restore_index: bool) -> Result<String> {
+        let (stash_ref, _) = self.find_stash(stash_ref)?;
+        if restore_index {
+            self.run_git(&["stash", "pop", "--index", &stash_ref])
+        } else {
+            self.run_git(&["stash", "pop", &stash_ref])
+        }
     }
 
     /// List all stashes
@@ -137,9 +336,15 @@ impl GitRepository {
         Ok(entries)
     }
 
-    /// Apply a specific stash by index (e.g., "stash@{0}")
-    pub fn stash_apply(&self, stash_ref: &str) -> Result<String> {
-        self.run_git(&["stash", "apply", stash_ref])
+    /// Apply a specific stash by index (e.g., "stash@{0}"); `restore_index` also
+    /// restores what was staged (`--index`)
+    pub fn stash_apply(&self, stash_ref: &str, restore_index: bool) -> Result<String> {
+        let (stash_ref, _) = self.find_stash(stash_ref)?;
+        if restore_index {
+            self.run_git(&["stash", "apply", "--index", &stash_ref])
+        } else {
+            self.run_git(&["stash", "apply", &stash_ref])
+        }
     }
 
     /// Drop a specific stash by index
@@ -271,6 +476,9 @@ impl GitRepository {
         // Pre-resolve main branch OID once for merged checks
         let main_oid = self.repo.revparse_single(main_branch).ok().map(|o| o.id());
 
+        // Branches checked out in other worktrees can't be checked out here
+        let checked_out_elsewhere = self.branches_checked_out_elsewhere();
+
         // Calculate metadata for each branch in a single pass
         // Also collect OIDs for refs_hash to avoid double revparse_single calls
         let now = chrono::Utc::now();
@@ -322,6 +530,7 @@ impl GitRepository {
                     is_stale,
                     is_unborn,
                     last_commit_date,
+                    checked_out_in: checked_out_elsewhere.get(branch_name).cloned(),
                 },
             );
         }
@@ -404,6 +613,7 @@ impl GitRepository {
         revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
 
         let mut commits = Vec::new();
+        let shallow = self.shallow_boundaries();
 
         // Map branch tips -> commit IDs so we can tag head commits with the
         // branches that currently point at them. This is inexpensive and keeps
@@ -437,8 +647,9 @@ impl GitRepository {
                 .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap());
             let date_str = date_time.to_rfc3339();
 
+            let sha = commit.id().to_string();
             commits.push(Commit {
-                sha: commit.id().to_string(),
+                sha: sha.clone(),
                 author: commit.author().name().unwrap_or_default().to_string(),
                 email: commit.author().email().unwrap_or_default().to_string(),
                 date: date_str,
@@ -454,6 +665,8 @@ impl GitRepository {
                 branch_divergence_point: None,
                 branch_base: None,
                 branch_divergence_age_days: None,
+                is_shallow_boundary: shallow.contains(&sha).then_some(true),
+                signature: None,
             });
         }
 
@@ -480,6 +693,7 @@ impl GitRepository {
         revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
 
         let mut commits = Vec::new();
+        let shallow = self.shallow_boundaries();
 
         for oid in revwalk.take(limit) {
             let oid = oid?;
@@ -491,8 +705,9 @@ impl GitRepository {
                 .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap());
             let date_str = date_time.to_rfc3339();
 
+            let sha = commit.id().to_string();
             commits.push(Commit {
-                sha: commit.id().to_string(),
+                sha: sha.clone(),
                 author: commit.author().name().unwrap_or_default().to_string(),
                 email: commit.author().email().unwrap_or_default().to_string(),
                 date: date_str,
@@ -510,12 +725,44 @@ impl GitRepository {
                 branch_divergence_point: None,
                 branch_base: None,
                 branch_divergence_age_days: None,
+                is_shallow_boundary: shallow.contains(&sha).then_some(true),
+                signature: None,
             });
         }
 
         Ok(commits)
     }
 
+    /// A single commit by revspec, without branch annotations
+    pub fn get_commit(&self, spec: &str) -> Result<Commit> {
+        let commit = self.repo.revparse_single(spec)?.peel_to_commit()?;
+        let date_time = DateTime::from_timestamp(commit.time().seconds(), 0)
+            .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap());
+        let sha = commit.id().to_string();
+        let author = commit.author();
+
+        Ok(Commit {
+            author: author.name().unwrap_or_default().to_string(),
+            email: author.email().unwrap_or_default().to_string(),
+            date: date_time.to_rfc3339(),
+            message: commit.message().unwrap_or_default().to_string(),
+            parents: commit.parent_ids().map(|id| id.to_string()).collect(),
+            is_merge: commit.parent_count() > 1,
+            branches: Vec::new(),
+            primary_branch: None,
+            is_head: None,
+            is_main: None,
+            branch_angle: None,
+            branch_info: None,
+            branch_divergence_point: None,
+            branch_base: None,
+            branch_divergence_age_days: None,
+            is_shallow_boundary: self.shallow_boundaries().contains(&sha).then_some(true),
+            signature: Some(self.commit_signature(&sha)?),
+            sha,
+        })
+    }
+
     pub fn is_ancestor(&self, commit: &str, ancestor: &str) -> Result<bool> {
         let commit_oid = Oid::from_str(commit)?;
         let ancestor_oid = Oid::from_str(ancestor)?;
@@ -561,63 +808,53 @@ impl GitRepository {
         Ok((files_changed, lines_changed))
     }
 
-    /// Get the list of changed files in a commit using libgit2
-    /// Returns a Vec of FileChange with path and status (added, modified, removed)
-    pub fn get_commit_changed_files(&self, commit_sha: &str) -> Result<Vec<crate::models::FileChange>> {
-        use git2::Delta;
+    /// Similarity (percent) at which `gitpow.renameThreshold` treats a removed
+    /// and an added file as a rename or copy; 0 turns detection off
+    pub fn rename_threshold(&self) -> u8 {
+        self.repo
+            .config()
+            .and_then(|config| config.get_i64("gitpow.renameThreshold"))
+            .map(|n| n.clamp(0, 100) as u8)
+            .unwrap_or(DEFAULT_RENAME_THRESHOLD)
+    }
 
+    /// Get the list of changed files in a commit against one of its parents,
+    /// with renames and copies detected as `git diff-tree -M -C` does. The
+    /// combined view lists the files of a merge that differ from every parent.
+    pub fn get_commit_changed_files(
+        &self,
+        commit_sha: &str,
+        parent: DiffParent,
+    ) -> Result<Vec<crate::models::FileChange>> {
         let oid = Oid::from_str(commit_sha)?;
         let commit = self.repo.find_commit(oid)?;
-        let tree = commit.tree()?;
-
-        // Get parent tree (None for initial commit)
-        let parent_tree = if commit.parent_count() > 0 {
-            Some(commit.parent(0)?.tree()?)
-        } else {
-            None
-        };
-
-        // Create diff between parent and current commit
-        let diff = self.repo.diff_tree_to_tree(
-            parent_tree.as_ref(),
-            Some(&tree),
-            None,
-        )?;
-
-        let mut changes = Vec::new();
-
-        // Iterate through all deltas in the diff
-        for delta_idx in 0..diff.deltas().len() {
-            let delta = diff.get_delta(delta_idx).unwrap();
-
-            // Get the file path (prefer new_file path, fall back to old_file for deletions)
-            let path = delta
-                .new_file()
-                .path()
-                .or_else(|| delta.old_file().path())
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default();
-
-            if path.is_empty() {
-                continue;
+        let commit_id = commit.id().to_string();
+
+        let threshold = self.rename_threshold();
+        let (renames, copies) = (format!("-M{}%", threshold), format!("-C{}%", threshold));
+        let mut args = vec!["diff-tree", "-r", "-z", "--no-commit-id"];
+        let parent_id;
+        match parent.resolve(commit.parent_count())? {
+            DiffParent::Combined => args.extend(["--cc", "--raw"]),
+            DiffParent::Parent(index) => {
+                if threshold > 0 {
+                    args.push(&renames);
+                    args.push(&copies);
+                } else {
+                    args.push("--no-renames");
+                }
+                if commit.parent_count() > 0 {
+                    parent_id = commit.parent_id(index)?.to_string();
+                    args.push(&parent_id);
+                } else {
+                    args.push("--root");
+                }
             }
-
-            // Map git2 Delta to our status strings
-            let status = match delta.status() {
-                Delta::Added | Delta::Untracked => "added",
-                Delta::Deleted => "removed",
-                Delta::Modified | Delta::Typechange => "modified",
-                Delta::Renamed | Delta::Copied => "modified",
-                _ => "modified",
-            };
-
-            changes.push(crate::models::FileChange {
-                path,
-                status: status.to_string(),
-            });
         }
+        args.push(&commit_id);
+        let output = self.run_git_bytes(&args)?;
 
-        Ok(changes)
+        parse_raw_changes(&output)
     }
 
     /// Check if a branch is merged into main/master branch
@@ -671,14 +908,30 @@ impl GitRepository {
 
     /// Get the diff for a specific file in a commit compared to its parent
     /// Returns a tuple of (diff_text, hunks) where hunks contain parsed hunk information
-    pub fn get_file_diff(&self, commit_sha: &str, file_path: &str) -> Result<FileDiff> {
+    pub fn get_file_diff(
+        &self,
+        commit_sha: &str,
+        file_path: &str,
+        parent: DiffParent,
+        config: &DiffConfig,
+    ) -> Result<FileDiff> {
         let oid = Oid::from_str(commit_sha)?;
         let commit = self.repo.find_commit(oid)?;
+        let parent_index = match parent.resolve(commit.parent_count())? {
+            DiffParent::Combined => return self.combined_file_diff(&commit, file_path, config),
+            DiffParent::Parent(index) => index,
+        };
+
+        // Submodule pointer change: report the commit range instead of a blob diff
+        if let Some(diff) = self.commit_submodule_diff(commit_sha, file_path, parent_index)? {
+            return Ok(diff);
+        }
+
         let tree = commit.tree()?;
 
         // Get parent tree (None for initial commit)
         let parent_tree = if commit.parent_count() > 0 {
-            Some(commit.parent(0)?.tree()?)
+            Some(commit.parent(parent_index)?.tree()?)
         } else {
             None
         };
@@ -689,11 +942,27 @@ impl GitRepository {
             .as_ref()
             .and_then(|t| t.get_path(std::path::Path::new(file_path)).ok());
 
+        // A rename or copy target is new in the commit; diff it against its source
+        if file_in_parent.is_none() && file_in_current.is_some() {
+            if let Some(old_path) = self.renamed_from(commit_sha, file_path, parent)? {
+                return self.generate_path_diff(
+                    parent_tree.as_ref(),
+                    &tree,
+                    &old_path,
+                    file_path,
+                    config,
+                );
+            }
+        }
+
         // Handle different scenarios
         match (file_in_parent, file_in_current) {
             (None, Some(entry)) => {
                 // File was added - show all lines as additions
                 let blob = self.repo.find_blob(entry.id())?;
+                if let Some(diff) = self.whole_blob_binary(&blob, file_path, true, config) {
+                    return Ok(diff);
+                }
                 let content = String::from_utf8_lossy(blob.content());
                 let lines: Vec<&str> = content.lines().collect();
                 let line_count = lines.len();
@@ -718,11 +987,16 @@ impl GitRepository {
                     diff,
                     hunks: vec![hunk],
                     file_path: file_path.to_string(),
+                    submodule: None,
+                    binary: None,
                 })
             }
             (Some(entry), None) => {
                 // File was deleted - show all lines as deletions
                 let blob = self.repo.find_blob(entry.id())?;
+                if let Some(diff) = self.whole_blob_binary(&blob, file_path, false, config) {
+                    return Ok(diff);
+                }
                 let content = String::from_utf8_lossy(blob.content());
                 let lines: Vec<&str> = content.lines().collect();
                 let line_count = lines.len();
@@ -747,11 +1021,13 @@ impl GitRepository {
                     diff,
                     hunks: vec![hunk],
                     file_path: file_path.to_string(),
+                    submodule: None,
+                    binary: None,
                 })
             }
             (Some(_), Some(_)) => {
                 // File was modified - generate actual diff
-                self.generate_file_diff(parent_tree.as_ref(), &tree, file_path)
+                self.generate_file_diff(parent_tree.as_ref(), &tree, file_path, config)
             }
             (None, None) => {
                 // File doesn't exist in either - empty diff
@@ -759,26 +1035,146 @@ impl GitRepository {
                     diff: String::new(),
                     hunks: vec![],
                     file_path: file_path.to_string(),
+                    submodule: None,
+                    binary: None,
                 })
             }
         }
     }
 
+    /// Size-only diff of a file added (or removed) whole, when its blob is
+    /// binary, marked `-diff`, or over the size limit
+    fn whole_blob_binary(
+        &self,
+        blob: &git2::Blob<'_>,
+        file_path: &str,
+        added: bool,
+        config: &DiffConfig,
+    ) -> Option<FileDiff> {
+        let size = blob.size() as u64;
+        let too_large = config.too_large(size);
+        let no_diff = self
+            .repo
+            .get_attr(
+                Path::new(file_path),
+                "diff",
+                git2::AttrCheckFlags::FILE_THEN_INDEX,
+            )
+            .ok()
+            .flatten()
+            .map(|value| git2::AttrValue::from_string(Some(value)) == git2::AttrValue::False)
+            .unwrap_or(false);
+        if !too_large && !no_diff && !blob.is_binary() {
+            return None;
+        }
+
+        let (old, new, old_size, new_size) = if added {
+            ("/dev/null".to_string(), format!("b/{}", file_path), None, Some(size))
+        } else {
+            (format!("a/{}", file_path), "/dev/null".to_string(), Some(size), None)
+        };
+        Some(FileDiff {
+            diff: format!("Binary files {} and {} differ\n", old, new),
+            hunks: vec![],
+            file_path: file_path.to_string(),
+            submodule: None,
+            binary: Some(BinaryDiff {
+                old_size,
+                new_size,
+                too_large,
+            }),
+        })
+    }
+
+    /// Source path of `file_path` when the commit renamed or copied it there
+    fn renamed_from(
+        &self,
+        commit_sha: &str,
+        file_path: &str,
+        parent: DiffParent,
+    ) -> Result<Option<String>> {
+        Ok(self
+            .get_commit_changed_files(commit_sha, parent)?
+            .into_iter()
+            .find(|change| change.path == file_path)
+            .and_then(|change| change.old_path))
+    }
+
     /// Generate diff between two trees for a specific file using libgit2
-    fn generate_file_diff(
+    pub(crate) fn generate_file_diff(
         &self,
         old_tree: Option<&git2::Tree>,
         new_tree: &git2::Tree,
         file_path: &str,
+        config: &DiffConfig,
     ) -> Result<FileDiff> {
-        let mut diff_opts = git2::DiffOptions::new();
-        diff_opts.pathspec(file_path);
-        diff_opts.context_lines(3);
+        self.generate_path_diff(old_tree, new_tree, file_path, file_path, config)
+    }
+
+    /// Diff `old_path` in `old_tree` against `file_path` in `new_tree`; the
+    /// paths differ for renames and copies
+    pub(crate) fn generate_path_diff(
+        &self,
+        old_tree: Option<&git2::Tree>,
+        new_tree: &git2::Tree,
+        old_path: &str,
+        file_path: &str,
+        config: &DiffConfig,
+    ) -> Result<FileDiff> {
+        let renamed = old_path != file_path;
+        if config.needs_git_cli() {
+            let old_id = match old_tree {
+                Some(tree) => tree.id(),
+                None => Self::empty_tree_id()?,
+            };
+            let (old_id, new_id) = (old_id.to_string(), new_tree.id().to_string());
+            if renamed {
+                let old_spec = format!("{}:{}", old_id, old_path);
+                let new_spec = format!("{}:{}", new_id, file_path);
+                return self.git_cli_file_diff(&[&old_spec, &new_spec], &[], file_path, config);
+            }
+            return self.git_cli_file_diff(&[&old_id, &new_id], &[file_path], file_path, config);
+        }
 
-        let diff = self
+        let mut diff_opts = config.diff_options(file_path);
+        if renamed {
+            // The source of a copy is unchanged, so it has to be listed explicitly
+            diff_opts.pathspec(old_path).include_unmodified(true);
+        }
+
+        let mut diff = self
             .repo
             .diff_tree_to_tree(old_tree, Some(new_tree), Some(&mut diff_opts))?;
+        if renamed {
+            Self::pair_renamed(&mut diff)?;
+        }
 
+        self.collect_file_diff(&diff, file_path, config)
+    }
+
+    /// Pair the two paths of a known rename or copy in `diff`, which must
+    /// include the unmodified source
+    pub(crate) fn pair_renamed(diff: &mut git2::Diff<'_>) -> Result<()> {
+        // The pair is already known to match; the lowest threshold makes
+        // libgit2 pair it whatever its own similarity estimate
+        let mut find_opts = git2::DiffFindOptions::new();
+        find_opts
+            .renames(true)
+            .copies(true)
+            .copies_from_unmodified(true)
+            .rename_threshold(1)
+            .copy_threshold(1);
+        diff.find_similar(Some(&mut find_opts))?;
+        Ok(())
+    }
+
+    /// Render the patch of `file_path` in `diff` with its parsed hunks
+    pub(crate) fn collect_file_diff(
+        &self,
+        diff: &git2::Diff<'_>,
+        file_path: &str,
+        config: &DiffConfig,
+    ) -> Result<FileDiff> {
         let mut diff_text = String::new();
         let mut hunks: Vec<DiffHunkData> = Vec::new();
         let mut current_hunk_lines: Vec<String> = Vec::new();
@@ -786,6 +1182,10 @@ impl GitRepository {
 
         // Use diff.print to get formatted output
         diff.print(git2::DiffFormat::Patch, |delta, hunk, line| {
+            // Only the target file, not the unchanged or modified copy source
+            if delta.new_file().path() != Some(Path::new(file_path)) {
+                return true;
+            }
             // Build the diff text
             let origin = line.origin();
             let content = std::str::from_utf8(line.content()).unwrap_or("");
@@ -856,6 +1256,7 @@ impl GitRepository {
                     diff_text.push_str(&format!("--- {}\n", old_prefix));
                     diff_text.push_str(&format!("+++ {}\n", new_prefix));
                 }
+                'B' => diff_text.push_str(&binary_line(&delta)),
                 _ => {
                     // Other line types (context info, etc.)
                     diff_text.push_str(content);
@@ -875,14 +1276,43 @@ impl GitRepository {
             diff: diff_text,
             hunks,
             file_path: file_path.to_string(),
+            submodule: None,
+            binary: binary_delta(&self.repo, diff, file_path, config),
         })
     }
 
     /// Get diff for working directory changes (staged or unstaged)
-    pub fn get_working_diff(&self, file_path: &str, staged: bool) -> Result<FileDiff> {
-        let mut diff_opts = git2::DiffOptions::new();
-        diff_opts.pathspec(file_path);
-        diff_opts.context_lines(3);
+    pub fn get_working_diff(
+        &self,
+        file_path: &str,
+        staged: bool,
+        config: &DiffConfig,
+    ) -> Result<FileDiff> {
+        if let Some(diff) = self.working_submodule_diff(file_path, staged)? {
+            return Ok(diff);
+        }
+
+        if config.needs_git_cli() {
+            if staged {
+                return self.git_cli_file_diff(&["--cached"], &[file_path], file_path, config);
+            }
+            let untracked = self
+                .repo
+                .status_file(Path::new(file_path))
+                .map(|status| status.contains(git2::Status::WT_NEW))
+                .unwrap_or(false);
+            if untracked {
+                return self.git_cli_file_diff(
+                    &["--no-index"],
+                    &["/dev/null", file_path],
+                    file_path,
+                    config,
+                );
+            }
+            return self.git_cli_file_diff(&[], &[file_path], file_path, config);
+        }
+
+        let mut diff_opts = config.diff_options(file_path);
 
         let diff = if staged {
             // Staged changes: HEAD to index
@@ -893,7 +1323,12 @@ impl GitRepository {
                 Some(&mut diff_opts),
             )?
         } else {
-            // Unstaged changes: index to workdir
+            // Unstaged changes: index to workdir. Untracked files diff against
+            // empty content so their lines can be staged individually.
+            diff_opts
+                .include_untracked(true)
+                .recurse_untracked_dirs(true)
+                .show_untracked_content(true);
             self.repo
                 .diff_index_to_workdir(None, Some(&mut diff_opts))?
         };
@@ -960,6 +1395,7 @@ impl GitRepository {
                     diff_text.push_str(&format!("--- a/{}\n", old_path));
                     diff_text.push_str(&format!("+++ b/{}\n", new_path));
                 }
+                'B' => diff_text.push_str(&binary_line(&delta)),
                 _ => {}
             }
             true
@@ -975,6 +1411,8 @@ impl GitRepository {
             diff: diff_text,
             hunks,
             file_path: file_path.to_string(),
+            submodule: None,
+            binary: binary_delta(&self.repo, &diff, file_path, config),
         })
     }
 }
@@ -985,6 +1423,9 @@ pub struct FileDiff {
     pub diff: String,
     pub hunks: Vec<DiffHunkData>,
     pub file_path: String,
+    pub submodule: Option<crate::models::SubmoduleChange>,
+    /// Set when the file is shown by size instead of by line
+    pub binary: Option<BinaryDiff>,
 }
 
 /// Hunk data from libgit2 diff
@@ -996,3 +1437,17 @@ pub struct DiffHunkData {
     pub new_count: i32,
     pub lines: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_arguments_git_would_read_as_options() {
+        assert!(check_argument("branch", "--orphan").is_err());
+        assert!(check_argument("branch", " -b").is_err());
+        assert!(check_argument("path", "-").is_err());
+        assert!(check_argument("branch", "feature/-x").is_ok());
+        assert!(check_argument("commit", "HEAD~1").is_ok());
+    }
+}
@@ -0,0 +1,64 @@
+use anyhow::{bail, Result};
+use std::collections::HashSet;
+use std::fs;
+
+use crate::git::repository::GitRepository;
+use crate::models::{ShallowFetchRequest, ShallowInfo};
+
+impl GitRepository {
+    /// Commits at the shallow boundary (listed in `.git/shallow`). Their parents were
+    /// not fetched, so git and libgit2 present them as if they were root commits.
+    pub fn shallow_boundaries(&self) -> HashSet<String> {
+        // Linked worktrees share the main repository's shallow file
+        fs::read_to_string(self.repo.commondir().join("shallow"))
+            .map(|content| {
+                content
+                    .lines()
+                    .map(|l| l.trim())
+                    .filter(|l| !l.is_empty())
+                    .map(|l| l.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn shallow_info(&self) -> ShallowInfo {
+        let mut boundaries: Vec<String> = self.shallow_boundaries().into_iter().collect();
+        boundaries.sort();
+        ShallowInfo {
+            is_shallow: self.repo.is_shallow(),
+            boundaries,
+        }
+    }
+
+    /// Fetch more history: deepen by N commits, deepen to a date, or fetch everything
+    pub fn deepen(&self, req: &ShallowFetchRequest) -> Result<String> {
+        let chosen = [req.deepen.is_some(), req.since.is_some(), req.unshallow]
+            .iter()
+            .filter(|b| **b)
+            .count();
+        if chosen != 1 {
+            bail!("specify exactly one of deepen, since or unshallow");
+        }
+        if !self.repo.is_shallow() {
+            bail!("repository is not shallow");
+        }
+
+        let arg = if let Some(depth) = req.deepen {
+            if depth == 0 {
+                bail!("deepen must be greater than zero");
+            }
+            format!("--deepen={}", depth)
+        } else if let Some(since) = req.since.as_deref() {
+            let since = since.trim();
+            if since.is_empty() {
+                bail!("since must be a date");
+            }
+            format!("--shallow-since={}", since)
+        } else {
+            "--unshallow".to_string()
+        };
+
+        self.run_git(&["fetch", &arg])
+    }
+}
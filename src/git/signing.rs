@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use git2::Oid;
+use moka::sync::Cache;
+use once_cell::sync::Lazy;
+
+use crate::git::repository::{check_argument, GitRepository};
+use crate::models::{Commit, SignatureInfo, SigningConfig, TagCreateRequest};
+
+/// Verified signatures by (repo path, commit SHA). Verification shells out to
+/// gpg/ssh-keygen, so listings reuse earlier results; the TTL lets key trust
+/// changes show up eventually.
+static SIGNATURE_CACHE: Lazy<Cache<(String, String), SignatureInfo>> = Lazy::new(|| {
+    Cache::builder()
+        .time_to_live(Duration::from_secs(600))
+        .max_capacity(100_000)
+        .build()
+});
+
+/// Signed commits verified per listing request; the rest stay "unverified"
+/// until a later request (or the commit detail) gets to them.
+const MAX_VERIFY_PER_LISTING: usize = 200;
+
+fn unsigned() -> SignatureInfo {
+    SignatureInfo {
+        status: "unsigned".to_string(),
+        signer: None,
+        key: None,
+        format: None,
+    }
+}
+
+/// Signature scheme from the armor of the signature block
+fn signature_format(signature: &[u8]) -> Option<String> {
+    let signature = String::from_utf8_lossy(signature);
+    let format = if signature.starts_with("-----BEGIN PGP SIGNATURE") {
+        "openpgp"
+    } else if signature.starts_with("-----BEGIN SSH SIGNATURE") {
+        "ssh"
+    } else if signature.starts_with("-----BEGIN SIGNED MESSAGE") {
+        "x509"
+    } else {
+        return None;
+    };
+    Some(format.to_string())
+}
+
+/// Map `%G?` to a status
+fn signature_status(code: &str) -> &'static str {
+    match code {
+        "G" | "U" => "good",
+        "B" => "bad",
+        "E" => "unknownKey",
+        "X" | "Y" => "expired",
+        "R" => "revoked",
+        _ => "unsigned",
+    }
+}
+
+impl GitRepository {
+    fn signature_format_of(&self, sha: &str) -> Option<String> {
+        let oid = Oid::from_str(sha).ok()?;
+        let (signature, _) = self.repo.extract_signature(&oid, None).ok()?;
+        Some(signature_format(&signature).unwrap_or_else(|| "unknown".to_string()))
+    }
+
+    /// Verify signed commits with `git log --format=%G?`, in one batch
+    fn verify_signatures(&self, shas: &[&str]) -> Result<HashMap<String, SignatureInfo>> {
+        let mut results = HashMap::new();
+        if shas.is_empty() {
+            return Ok(results);
+        }
+
+        let mut args = vec![
+            "log",
+            "--no-walk=unsorted",
+            "--format=%H%x1f%G?%x1f%GS%x1f%GK%x1e",
+        ];
+        args.extend(shas);
+        let output = self.run_git(&args)?;
+        for record in output.split('\x1e') {
+            let parts: Vec<&str> = record.trim().split('\x1f').collect();
+            if parts.len() < 4 {
+                continue;
+            }
+            let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+            results.insert(
+                parts[0].to_string(),
+                SignatureInfo {
+                    status: signature_status(parts[1]).to_string(),
+                    signer: non_empty(parts[2]),
+                    key: non_empty(parts[3]),
+                    format: self.signature_format_of(parts[0]),
+                },
+            );
+        }
+        Ok(results)
+    }
+
+    /// Signature status of one commit, always verified
+    pub fn commit_signature(&self, sha: &str) -> Result<SignatureInfo> {
+        let key = (self.path().to_string_lossy().to_string(), sha.to_string());
+        if let Some(info) = SIGNATURE_CACHE.get(&key) {
+            return Ok(info);
+        }
+        if self.signature_format_of(sha).is_none() {
+            return Ok(unsigned());
+        }
+
+        let info = self
+            .verify_signatures(&[sha])?
+            .remove(sha)
+            .unwrap_or_else(unsigned);
+        SIGNATURE_CACHE.insert(key, info.clone());
+        Ok(info)
+    }
+
+    /// Fill in `Commit.signature` for a listing. Unsigned commits are found
+    /// in-process; signed ones are verified (up to a per-request budget).
+    pub fn annotate_signatures(&self, commits: &mut [Commit]) {
+        let repo_key = self.path().to_string_lossy().to_string();
+        let mut pending = Vec::new();
+
+        for (i, commit) in commits.iter_mut().enumerate() {
+            let key = (repo_key.clone(), commit.sha.clone());
+            if let Some(info) = SIGNATURE_CACHE.get(&key) {
+                commit.signature = Some(info);
+                continue;
+            }
+            match self.signature_format_of(&commit.sha) {
+                None => commit.signature = Some(unsigned()),
+                Some(format) => {
+                    commit.signature = Some(SignatureInfo {
+                        status: "unverified".to_string(),
+                        signer: None,
+                        key: None,
+                        format: Some(format),
+                    });
+                    if pending.len() < MAX_VERIFY_PER_LISTING {
+                        pending.push(i);
+                    }
+                }
+            }
+        }
+
+        let shas: Vec<&str> = pending.iter().map(|&i| commits[i].sha.as_str()).collect();
+        let mut verified = match self.verify_signatures(&shas) {
+            Ok(verified) => verified,
+            Err(e) => {
+                tracing::warn!("Failed to verify commit signatures: {}", e);
+                return;
+            }
+        };
+        for i in pending {
+            if let Some(info) = verified.remove(&commits[i].sha) {
+                SIGNATURE_CACHE.insert((repo_key.clone(), commits[i].sha.clone()), info.clone());
+                commits[i].signature = Some(info);
+            }
+        }
+    }
+
+    /// `commit.gpgsign`, `tag.gpgSign`, `gpg.format` and `user.signingkey`
+    pub fn signing_config(&self) -> Result<SigningConfig> {
+        let config = self.repo.config()?.snapshot()?;
+        Ok(SigningConfig {
+            sign_commits: config.get_bool("commit.gpgsign").unwrap_or(false),
+            sign_tags: config.get_bool("tag.gpgSign").unwrap_or(false),
+            format: config
+                .get_string("gpg.format")
+                .unwrap_or_else(|_| "openpgp".to_string()),
+            signing_key: config.get_string("user.signingkey").ok(),
+        })
+    }
+
+    /// Create a lightweight or annotated tag with `git tag`, so signing
+    /// follows `tag.gpgSign` unless `sign` overrides it
+    pub fn create_tag(&self, req: &TagCreateRequest) -> Result<()> {
+        let name = req.name.trim();
+        check_argument("tag name", name)?;
+        if !git2::Reference::is_valid_name(&format!("refs/tags/{}", name)) {
+            bail!("Invalid tag name: {}", name);
+        }
+        // Resolved up front, so git only ever sees an object id
+        let target = req.target.as_deref().map(str::trim);
+        let target = match target.filter(|t| !t.is_empty()) {
+            Some(target) => {
+                check_argument("tag target", target)?;
+                let object = self
+                    .repo
+                    .revparse_single(target)
+                    .map_err(|e| anyhow!("Unknown revision {}: {}", target, e.message()))?;
+                Some(object.id().to_string())
+            }
+            None => None,
+        };
+
+        let mut args = vec!["tag"];
+        let message = req.message.as_deref().filter(|m| !m.trim().is_empty());
+        match req.sign {
+            Some(true) => args.push("-s"),
+            Some(false) => args.push("--no-sign"),
+            None => {}
+        }
+        // A signed tag is always annotated and needs a message
+        match message {
+            Some(message) => args.extend(["-m", message]),
+            None if req.sign == Some(true) => args.extend(["-m", name]),
+            None => {}
+        }
+        if req.force {
+            args.push("--force");
+        }
+        args.push("--");
+        args.push(name);
+        if let Some(target) = target.as_deref() {
+            args.push(target);
+        }
+
+        self.run_git(&args)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,510 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use git2::{IndexAddOption, IndexEntry, IndexEntryExtendedFlag, IndexTime, ObjectType, Patch};
+
+use crate::git::diff_options::{git_cli_buffer_diff, DiffConfig};
+use crate::git::repository::GitRepository;
+use crate::models::HunkSelection;
+
+/// Hunks and lines picked from a file diff.
+///
+/// Hunk indices follow the diff shown to the user, computed again with the
+/// same `DiffConfig`, and line indices index the whole hunk's `DiffHunk.lines`:
+/// 0 is the `@@` header and the context, removed and added lines start at 1.
+/// Picks from one page of a split hunk are shifted by its `lineOffset`.
+#[derive(Debug, Default)]
+pub struct LineSelection {
+    hunks: HashMap<usize, Option<HashSet<usize>>>,
+    config: DiffConfig,
+}
+
+impl LineSelection {
+    /// Build a selection from whole hunk indices and per-line picks.
+    /// Returns `None` when neither is given, meaning "the whole file".
+    pub fn from_request(
+        hunks: Option<&[usize]>,
+        lines: Option<&[HunkSelection]>,
+        config: DiffConfig,
+    ) -> Option<Self> {
+        if hunks.is_none() && lines.is_none() {
+            return None;
+        }
+
+        let mut selection = Self {
+            config,
+            ..Self::default()
+        };
+        for &hunk in hunks.unwrap_or_default() {
+            selection.hunks.insert(hunk, None);
+        }
+        for pick in lines.unwrap_or_default() {
+            match &pick.lines {
+                None => {
+                    selection.hunks.insert(pick.hunk, None);
+                }
+                Some(lines) => {
+                    if let Some(set) = selection
+                        .hunks
+                        .entry(pick.hunk)
+                        .or_insert_with(|| Some(HashSet::new()))
+                    {
+                        // The header of a page's part is not a line of the hunk
+                        set.extend(
+                            lines
+                                .iter()
+                                .filter(|&&line| line > 0 || pick.line_offset == 0)
+                                .map(|&line| line + pick.line_offset),
+                        );
+                    }
+                }
+            }
+        }
+        Some(selection)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+
+    /// Highest line picked from `hunk`, if single lines were picked
+    fn last_line(&self, hunk: usize) -> Option<usize> {
+        self.hunks.get(&hunk)?.as_ref()?.iter().max().copied()
+    }
+
+    fn contains(&self, hunk: usize, line: usize) -> bool {
+        match self.hunks.get(&hunk) {
+            Some(None) => true,
+            Some(Some(lines)) => lines.contains(&line),
+            None => false,
+        }
+    }
+}
+
+/// Append one line, first terminating a previous line that had no newline
+/// (only possible for the old last line when more lines follow it).
+fn push_line(out: &mut Vec<u8>, line: &[u8]) {
+    if !out.is_empty() && !out.ends_with(b"\n") {
+        out.push(b'\n');
+    }
+    out.extend_from_slice(line);
+}
+
+/// Rebuild a file from the diff `old` -> `new`, taking only some of the changes.
+///
+/// Forward, the selected changes are applied on top of `old` (staging).
+/// Reversed, the selected changes are undone from `new` (unstaging,
+/// discarding). Unselected changes stay on the other side in both cases, and
+/// so do differences the diff options hide, such as ignored whitespace.
+pub fn apply_selection(
+    old: &[u8],
+    new: &[u8],
+    selection: &LineSelection,
+    reverse: bool,
+) -> Result<Vec<u8>> {
+    let base = if reverse { new } else { old };
+    let cli_diff;
+    let patch = if selection.config.needs_git_cli() {
+        cli_diff = git_cli_buffer_diff(old, new, &selection.config)?;
+        if cli_diff.deltas().len() == 0 {
+            return Ok(base.to_vec());
+        }
+        Patch::from_diff(&cli_diff, 0)?.context("Cannot select lines in a binary file")?
+    } else {
+        let mut opts = selection.config.options();
+        Patch::from_buffers(old, None, new, None, Some(&mut opts))?
+    };
+    if patch.delta().flags().is_binary() {
+        bail!("Cannot select lines in a binary file");
+    }
+
+    let base_lines: Vec<&[u8]> = base.split_inclusive(|b| *b == b'\n').collect();
+    let mut out = Vec::with_capacity(new.len().max(old.len()));
+    let mut base_pos = 0;
+
+    for hunk_idx in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_idx)?;
+        let (start, count) = if reverse {
+            (hunk.new_start(), hunk.new_lines())
+        } else {
+            (hunk.old_start(), hunk.old_lines())
+        };
+
+        // A hunk with no lines on the base side starts after the line it names
+        let hunk_start = if count == 0 {
+            start as usize
+        } else {
+            start.saturating_sub(1) as usize
+        };
+        for line in &base_lines[base_pos.min(base_lines.len())..hunk_start.min(base_lines.len())] {
+            push_line(&mut out, line);
+        }
+        base_pos = hunk_start;
+
+        // Index 0 is the hunk header
+        let mut line_idx = 1;
+        let mut pushed_from_patch = false;
+        for i in 0..line_count {
+            let line = patch.line_in_hunk(hunk_idx, i)?;
+            let selected = selection.contains(hunk_idx, line_idx);
+            // Lines on the base side stay unless their change is selected;
+            // lines of the other side are brought in when it is
+            let (on_base, keep) = match (line.origin(), reverse) {
+                (' ', _) => (true, true),
+                ('-', false) | ('+', true) => (true, !selected),
+                ('+', false) | ('-', true) => (false, selected),
+                // "\ No newline at end of file" markers. Patches parsed from
+                // `git diff` output keep the newline on the line before.
+                _ => {
+                    if pushed_from_patch && out.ends_with(b"\n") {
+                        out.pop();
+                    }
+                    continue;
+                }
+            };
+            line_idx += 1;
+            pushed_from_patch = !on_base && keep;
+            if on_base {
+                // Taken from the base even where ignored whitespace differs
+                let content = base_lines.get(base_pos).copied().unwrap_or(line.content());
+                if keep {
+                    push_line(&mut out, content);
+                }
+                base_pos += 1;
+            } else if keep {
+                push_line(&mut out, line.content());
+            }
+        }
+        let last = selection.last_line(hunk_idx).unwrap_or(0);
+        if last >= line_idx {
+            bail!("Hunk {} has no line {}; reload the diff", hunk_idx, last);
+        }
+    }
+
+    for line in &base_lines[base_pos.min(base_lines.len())..] {
+        push_line(&mut out, line);
+    }
+    Ok(out)
+}
+
+/// Blank index entry for `path`; `add_frombuffer` fills in the blob id and size
+pub(crate) fn index_entry(path: &str, mode: u32) -> IndexEntry {
+    IndexEntry {
+        ctime: IndexTime::new(0, 0),
+        mtime: IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode,
+        uid: 0,
+        gid: 0,
+        file_size: 0,
+        id: git2::Oid::zero(),
+        flags: 0,
+        flags_extended: 0,
+        path: path.as_bytes().to_vec(),
+    }
+}
+
+/// Index mode of a regular file: executable or not
+pub(crate) fn file_mode(meta: &fs::Metadata) -> u32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if meta.permissions().mode() & 0o111 != 0 {
+            return 0o100755;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = meta;
+    0o100644
+}
+
+fn is_intent_to_add(entry: &IndexEntry) -> bool {
+    IndexEntryExtendedFlag::from_bits_truncate(entry.flags_extended).is_intent_to_add()
+}
+
+impl GitRepository {
+    /// Index mode for a working tree file that is not in the index yet
+    fn workdir_mode(&self, path: &str) -> Result<u32> {
+        let meta = fs::symlink_metadata(self.path().join(path))?;
+        if meta.file_type().is_symlink() {
+            bail!("Cannot select lines of a symbolic link");
+        }
+        Ok(file_mode(&meta))
+    }
+
+    /// Working tree content of a file as `git add` would store it, through the
+    /// clean filters (`core.autocrlf`, `text`/`eol` attributes, LFS and other
+    /// filter drivers)
+    fn read_workdir_file(&self, path: &str) -> Result<Vec<u8>> {
+        if !self.path().join(path).exists() {
+            return Ok(Vec::new());
+        }
+        let path_arg = format!("--path={}", path);
+        let id = self.run_git(&["hash-object", "-w", &path_arg, "--", path])?;
+        self.blob_content(git2::Oid::from_str(id.trim())?)
+    }
+
+    /// Content as checkout would write it to `path`, through the smudge filters
+    fn smudge(&self, path: &str, content: &[u8]) -> Result<Vec<u8>> {
+        let id = self.repo.blob(content)?.to_string();
+        let path_arg = format!("--path={}", path);
+        self.run_git_bytes(&["cat-file", "--filters", &path_arg, &id])
+    }
+
+    fn blob_content(&self, id: git2::Oid) -> Result<Vec<u8>> {
+        Ok(self.repo.find_blob(id)?.content().to_vec())
+    }
+
+    /// Stage a whole file (`git add`/`git rm --cached` semantics), or only the
+    /// selected index -> working tree changes. Untracked files are staged
+    /// against an empty base, so parts of a new file can be added.
+    pub fn stage_path(&self, path: &str, selection: Option<&LineSelection>) -> Result<()> {
+        let mut index = self.repo.index()?;
+        let rel_path = Path::new(path);
+
+        match selection {
+            None => {
+                // Like `git add <path>`: new and modified files, then deletions
+                index.add_all([path], IndexAddOption::DEFAULT, None)?;
+                index.update_all([path], None)?;
+            }
+            Some(selection) => {
+                let existing = index.get_path(rel_path, 0);
+                let (old, mode) = match &existing {
+                    Some(entry) if !is_intent_to_add(entry) => {
+                        (self.blob_content(entry.id)?, entry.mode)
+                    }
+                    Some(entry) => (Vec::new(), entry.mode),
+                    None => (Vec::new(), self.workdir_mode(path)?),
+                };
+                let new = self.read_workdir_file(path)?;
+                let content = apply_selection(&old, &new, selection, false)?;
+
+                if content.is_empty() && !self.path().join(path).exists() {
+                    index.remove_path(rel_path)?;
+                } else {
+                    index.add_frombuffer(&index_entry(path, mode), &content)?;
+                }
+            }
+        }
+
+        index.write()?;
+        Ok(())
+    }
+
+    /// Record an untracked file as intent-to-add (`git add -N`), so its
+    /// content shows up as an unstaged addition that can be staged in parts.
+    pub fn intent_to_add(&self, path: &str) -> Result<()> {
+        let mut index = self.repo.index()?;
+        if index.get_path(Path::new(path), 0).is_some() {
+            bail!("{} is already tracked", path);
+        }
+
+        let mut entry = index_entry(path, self.workdir_mode(path)?);
+        entry.flags_extended = IndexEntryExtendedFlag::INTENT_TO_ADD.bits();
+        index.add_frombuffer(&entry, &[])?;
+        index.write()?;
+        Ok(())
+    }
+
+    /// Unstage a whole file (reset its index entry to HEAD), or only the
+    /// selected HEAD -> index changes.
+    pub fn unstage_path(&self, path: &str, selection: Option<&LineSelection>) -> Result<()> {
+        let head = self
+            .repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel(ObjectType::Commit).ok());
+
+        let selection = match selection {
+            Some(selection) => selection,
+            None => {
+                self.repo.reset_default(head.as_ref(), [path])?;
+                return Ok(());
+            }
+        };
+
+        let head_entry = match &head {
+            Some(commit) => commit
+                .peel_to_tree()?
+                .get_path(Path::new(path))
+                .ok()
+                .map(|entry| (entry.id(), entry.filemode() as u32)),
+            None => None,
+        };
+
+        let mut index = self.repo.index()?;
+        let rel_path = Path::new(path);
+        let index_entry_now = index.get_path(rel_path, 0);
+        if index_entry_now.is_none() && head_entry.is_none() {
+            bail!("{} has no staged changes", path);
+        }
+
+        let old = match head_entry {
+            Some((id, _)) => self.blob_content(id)?,
+            None => Vec::new(),
+        };
+        let new = match &index_entry_now {
+            Some(entry) => self.blob_content(entry.id)?,
+            None => Vec::new(),
+        };
+        let mode = index_entry_now
+            .as_ref()
+            .map(|entry| entry.mode)
+            .or(head_entry.map(|(_, mode)| mode))
+            .unwrap_or(0o100644);
+
+        let content = apply_selection(&old, &new, selection, true)?;
+        if content.is_empty() && head_entry.is_none() {
+            index.remove_path(rel_path)?;
+        } else {
+            index.add_frombuffer(&index_entry(path, mode), &content)?;
+        }
+
+        index.write()?;
+        Ok(())
+    }
+
+    /// Throw away the selected index -> working tree changes of a file,
+    /// rewriting it in the working tree. The index is left untouched.
+    pub fn discard_lines(&self, path: &str, selection: &LineSelection) -> Result<()> {
+        let index = self.repo.index()?;
+        let old = match index.get_path(Path::new(path), 0) {
+            Some(entry) if !is_intent_to_add(&entry) => self.blob_content(entry.id)?,
+            _ => Vec::new(),
+        };
+        let new = self.read_workdir_file(path)?;
+        let content = apply_selection(&old, &new, selection, true)?;
+        let content = self.smudge(path, &content)?;
+
+        let full_path = self.path().join(path);
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(full_path, content)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DiffSettings;
+
+    fn select(hunks: &[usize], lines: &[(usize, &[usize])]) -> LineSelection {
+        select_with(hunks, lines, DiffSettings::default())
+    }
+
+    fn select_with(
+        hunks: &[usize],
+        lines: &[(usize, &[usize])],
+        settings: DiffSettings,
+    ) -> LineSelection {
+        let lines: Vec<HunkSelection> = lines
+            .iter()
+            .map(|(hunk, lines)| HunkSelection {
+                hunk: *hunk,
+                lines: Some(lines.to_vec()),
+                line_offset: 0,
+            })
+            .collect();
+        let config = DiffConfig::parse(&settings).unwrap();
+        LineSelection::from_request(Some(hunks), Some(&lines), config).unwrap()
+    }
+
+    fn apply(old: &str, new: &str, selection: &LineSelection, reverse: bool) -> String {
+        let out = apply_selection(old.as_bytes(), new.as_bytes(), selection, reverse).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    // "@@ -1,2 +1,3 @@", " a", "-b", "+B", "+c"
+    const OLD: &str = "a\nb\n";
+    const NEW: &str = "a\nB\nc\n";
+
+    #[test]
+    fn nothing_picked_means_the_whole_file() {
+        let config = DiffConfig::default();
+        assert!(LineSelection::from_request(None, None, config).is_none());
+        assert!(select(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn line_indices_count_the_hunk_header() {
+        assert_eq!(
+            apply(OLD, NEW, &select(&[], &[(0, &[3])]), false),
+            "a\nb\nB\n"
+        );
+        assert_eq!(
+            apply(OLD, NEW, &select(&[], &[(0, &[2, 3])]), false),
+            "a\nB\n"
+        );
+        // Picking the header or context lines changes nothing
+        assert_eq!(apply(OLD, NEW, &select(&[], &[(0, &[0, 1])]), false), OLD);
+        assert_eq!(apply(OLD, NEW, &select(&[0], &[]), false), NEW);
+    }
+
+    #[test]
+    fn reverse_undoes_picked_changes_from_the_new_side() {
+        assert_eq!(apply(OLD, NEW, &select(&[], &[(0, &[4])]), true), "a\nB\n");
+        assert_eq!(
+            apply(OLD, NEW, &select(&[], &[(0, &[2])]), true),
+            "a\nb\nB\nc\n"
+        );
+        assert_eq!(apply(OLD, NEW, &select(&[0], &[]), true), OLD);
+    }
+
+    #[test]
+    fn keeps_lines_without_a_final_newline_apart() {
+        // "@@ -1,2 +1,2 @@", " a", "-b", "+c"
+        let (old, new) = ("a\nb", "a\nc");
+        assert_eq!(
+            apply(old, new, &select(&[], &[(0, &[3])]), false),
+            "a\nb\nc"
+        );
+        assert_eq!(apply(old, new, &select(&[], &[(0, &[2, 3])]), false), new);
+        assert_eq!(apply(old, new, &select(&[], &[(0, &[2])]), false), "a\n");
+        assert_eq!(apply(old, "a\nc\n", &select(&[0], &[]), false), "a\nc\n");
+    }
+
+    #[test]
+    fn leaves_ignored_whitespace_on_the_base_side() {
+        let settings = DiffSettings {
+            whitespace: Some("ignore-all-space".to_string()),
+            ..DiffSettings::default()
+        };
+        let selection = select_with(&[0], &[], settings);
+        assert_eq!(apply("a \nb\n", "a\nB\n", &selection, false), "a \nB\n");
+    }
+
+    #[test]
+    fn picks_lines_of_a_git_cli_diff() {
+        let settings = DiffSettings {
+            algorithm: Some("histogram".to_string()),
+            ..DiffSettings::default()
+        };
+        let selection = select_with(&[], &[(0, &[3])], settings);
+        assert_eq!(apply(OLD, NEW, &selection, false), "a\nb\nB\n");
+    }
+
+    #[test]
+    fn shifts_picks_from_a_page_by_its_line_offset() {
+        // Page part "@@ ... @@", "+B", "+c" starts at line 3 of the hunk
+        let lines = [HunkSelection {
+            hunk: 0,
+            lines: Some(vec![0, 2]),
+            line_offset: 2,
+        }];
+        let selection =
+            LineSelection::from_request(None, Some(&lines), DiffConfig::default()).unwrap();
+        assert_eq!(apply(OLD, NEW, &selection, false), "a\nb\nc\n");
+    }
+
+    #[test]
+    fn rejects_lines_past_the_hunk() {
+        let selection = select(&[], &[(0, &[5])]);
+        assert!(apply_selection(OLD.as_bytes(), NEW.as_bytes(), &selection, false).is_err());
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::DateTime;
+use git2::{Commit, Delta, Diff, DiffFindOptions, DiffOptions, Patch, Tree};
+
+use crate::git::diff_options::DiffConfig;
+use crate::git::repository::{FileDiff, GitRepository};
+use crate::models::{StashApplyResponse, StashDetail, StashFileChange};
+
+/// Which part of a stash to look at.
+///
+/// A stash commit `W` has the base commit `B` as its first parent, the index
+/// commit `I` as its second and, with `-u`/`-a`, the untracked files commit `U`
+/// as its third.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StashPart {
+    /// Everything tracked, B -> W (what `git stash show` displays)
+    Combined,
+    /// Staged changes, B -> I
+    Index,
+    /// Unstaged changes, I -> W
+    Worktree,
+    /// Untracked files, empty -> U
+    Untracked,
+}
+
+impl StashPart {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "combined" | "all" => Some(Self::Combined),
+            "index" | "staged" => Some(Self::Index),
+            "worktree" | "unstaged" => Some(Self::Worktree),
+            "untracked" => Some(Self::Untracked),
+            _ => None,
+        }
+    }
+}
+
+/// Branch name from a stash message ("WIP on main: ..." or "On main: ...")
+fn stash_branch(message: &str) -> Option<String> {
+    let rest = message
+        .strip_prefix("WIP on ")
+        .or_else(|| message.strip_prefix("On "))?;
+    let branch = rest.split(':').next()?.trim();
+    if branch.is_empty() || branch == "(no branch)" {
+        None
+    } else {
+        Some(branch.to_string())
+    }
+}
+
+fn delta_status(status: Delta) -> &'static str {
+    match status {
+        Delta::Added | Delta::Untracked => "added",
+        Delta::Deleted => "removed",
+        Delta::Renamed => "renamed",
+        Delta::Copied => "copied",
+        _ => "modified",
+    }
+}
+
+impl GitRepository {
+    /// Resolve `stash@{N}` (or the SHA of a stash entry) to its stash commit.
+    /// Only entries of the stash reflog are accepted.
+    pub fn find_stash(&self, stash_ref: &str) -> Result<(String, Commit<'_>)> {
+        let reflog = self.repo.reflog("refs/stash")?;
+        let stash_ref = stash_ref.trim();
+
+        let index = if let Some(n) = stash_ref
+            .strip_prefix("stash@{")
+            .and_then(|s| s.strip_suffix('}'))
+        {
+            n.parse::<usize>()
+                .map_err(|_| anyhow!("Invalid stash reference: {}", stash_ref))?
+        } else {
+            (0..reflog.len())
+                .find(|i| {
+                    reflog
+                        .get(*i)
+                        .map(|e| e.id_new().to_string().starts_with(stash_ref))
+                        .unwrap_or(false)
+                })
+                .filter(|_| stash_ref.len() >= 7)
+                .ok_or_else(|| anyhow!("No stash matches {}", stash_ref))?
+        };
+
+        let entry = reflog
+            .get(index)
+            .ok_or_else(|| anyhow!("No stash entry stash@{{{}}}", index))?;
+        let commit = self.repo.find_commit(entry.id_new())?;
+        Ok((format!("stash@{{{}}}", index), commit))
+    }
+
+    fn stash_trees<'a>(
+        &'a self,
+        stash: &Commit<'a>,
+        part: StashPart,
+    ) -> Result<(Option<Tree<'a>>, Option<Tree<'a>>)> {
+        let parent_tree = |n: usize| -> Result<Option<Tree<'a>>> {
+            if stash.parent_count() > n {
+                Ok(Some(stash.parent(n)?.tree()?))
+            } else {
+                Ok(None)
+            }
+        };
+
+        Ok(match part {
+            StashPart::Combined => (parent_tree(0)?, Some(stash.tree()?)),
+            StashPart::Index => (parent_tree(0)?, parent_tree(1)?),
+            StashPart::Worktree => (parent_tree(1)?, Some(stash.tree()?)),
+            StashPart::Untracked => (None, parent_tree(2)?),
+        })
+    }
+
+    fn stash_part_diff(&self, stash: &Commit<'_>, part: StashPart) -> Result<Option<Diff<'_>>> {
+        let (old_tree, new_tree) = self.stash_trees(stash, part)?;
+        let new_tree = match new_tree {
+            Some(tree) => tree,
+            None => return Ok(None),
+        };
+        let mut diff = self
+            .repo
+            .diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), Some(&mut DiffOptions::new()))?;
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+        Ok(Some(diff))
+    }
+
+    fn stash_part_files(&self, stash: &Commit<'_>, part: StashPart) -> Result<Vec<StashFileChange>> {
+        let diff = match self.stash_part_diff(stash, part)? {
+            Some(diff) => diff,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut files = Vec::new();
+        for idx in 0..diff.deltas().len() {
+            let delta = match diff.get_delta(idx) {
+                Some(delta) => delta,
+                None => continue,
+            };
+            let path = delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let old_path = match delta.status() {
+                Delta::Renamed | Delta::Copied => delta
+                    .old_file()
+                    .path()
+                    .map(|p| p.to_string_lossy().to_string()),
+                _ => None,
+            };
+
+            let (additions, deletions, is_binary) = match Patch::from_diff(&diff, idx)? {
+                Some(patch) => {
+                    let (_, additions, deletions) = patch.line_stats()?;
+                    (additions, deletions, delta.flags().is_binary())
+                }
+                None => (0, 0, true),
+            };
+
+            files.push(StashFileChange {
+                path,
+                old_path,
+                status: delta_status(delta.status()).to_string(),
+                additions,
+                deletions,
+                is_binary,
+            });
+        }
+        Ok(files)
+    }
+
+    /// Everything needed to decide whether to apply or drop a stash
+    pub fn stash_detail(&self, stash_ref: &str) -> Result<StashDetail> {
+        let (stash_ref, stash) = self.find_stash(stash_ref)?;
+        if stash.parent_count() < 2 {
+            bail!("{} is not a stash commit", stash_ref);
+        }
+        let base = stash.parent(0)?;
+        let message = stash.message().unwrap_or_default().trim().to_string();
+        let date = DateTime::from_timestamp(stash.time().seconds(), 0)
+            .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap())
+            .to_rfc3339();
+
+        Ok(StashDetail {
+            stash_ref,
+            sha: stash.id().to_string(),
+            branch: stash_branch(&message),
+            message,
+            date,
+            base_commit: base.id().to_string(),
+            base_message: base.summary().unwrap_or_default().to_string(),
+            has_untracked: stash.parent_count() > 2,
+            files: self.stash_part_files(&stash, StashPart::Combined)?,
+            index_files: self.stash_part_files(&stash, StashPart::Index)?,
+            worktree_files: self.stash_part_files(&stash, StashPart::Worktree)?,
+            untracked_files: self.stash_part_files(&stash, StashPart::Untracked)?,
+        })
+    }
+
+    /// Diff of one file in one part of a stash
+    pub fn stash_file_diff(
+        &self,
+        stash_ref: &str,
+        file_path: &str,
+        part: StashPart,
+        config: &DiffConfig,
+    ) -> Result<FileDiff> {
+        let (_, stash) = self.find_stash(stash_ref)?;
+        let (old_tree, new_tree) = self.stash_trees(&stash, part)?;
+        match new_tree {
+            Some(new_tree) => {
+                self.generate_file_diff(old_tree.as_ref(), &new_tree, file_path, config)
+            }
+            None => Ok(FileDiff {
+                diff: String::new(),
+                hunks: vec![],
+                file_path: file_path.to_string(),
+                submodule: None,
+                binary: None,
+            }),
+        }
+    }
+
+    /// Create `branch` at the stash's base commit, check it out and apply the stash
+    /// there with its index restored. The stash is dropped when it applies cleanly.
+    pub fn stash_branch(&self, branch: &str, stash_ref: &str) -> Result<String> {
+        let branch = branch.trim();
+        if branch.is_empty() {
+            bail!("branch name is required");
+        }
+        if branch.starts_with('-') || git2::Branch::name_is_valid(branch).map(|v| !v).unwrap_or(true) {
+            bail!("Invalid branch name: {}", branch);
+        }
+        let (stash_ref, _) = self.find_stash(stash_ref)?;
+        self.run_git(&["stash", "branch", branch, &stash_ref])
+    }
+
+    /// Run a stash apply/pop/branch and turn its result into a response, listing
+    /// the paths it left conflicted when git stopped on a merge conflict
+    pub fn stash_outcome(
+        &self,
+        run: impl FnOnce() -> Result<String>,
+        message: String,
+        drops_on_success: bool,
+    ) -> StashApplyResponse {
+        // Paths that were unmerged before are not the stash's conflicts
+        let unmerged_before: HashSet<String> = self
+            .conflicted_files()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|file| file.path)
+            .collect();
+        let result = run();
+        let conflicts = match &result {
+            Ok(_) => Vec::new(),
+            Err(_) => self
+                .conflicted_files()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|file| !unmerged_before.contains(&file.path))
+                .collect(),
+        };
+        match result {
+            Ok(output) => StashApplyResponse {
+                success: true,
+                message: Some(message),
+                output: Some(output),
+                error: None,
+                conflicts,
+                dropped: drops_on_success,
+            },
+            Err(e) => {
+                // git reports merge conflicts on stdout, leaving stderr empty
+                let mut error = e.to_string().trim().to_string();
+                if error.is_empty() && !conflicts.is_empty() {
+                    error = format!("Merge conflict in {} file(s)", conflicts.len());
+                } else if error.is_empty() && !unmerged_before.is_empty() {
+                    error = format!(
+                        "{} path(s) are unmerged; resolve them before applying a stash",
+                        unmerged_before.len()
+                    );
+                }
+                StashApplyResponse {
+                    success: false,
+                    message: if conflicts.is_empty() {
+                        None
+                    } else {
+                        Some("Stash applied with conflicts; the stash entry was kept".to_string())
+                    },
+                    output: None,
+                    error: Some(error),
+                    conflicts,
+                    dropped: false,
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use anyhow::{bail, Result};
+
+use crate::git::repository::GitRepository;
+use crate::models::{StatusFile, SubmoduleState};
+
+/// Name of a porcelain v2 change letter; `.` (unchanged) has none
+fn change_name(code: char) -> Option<&'static str> {
+    match code {
+        'M' => Some("modified"),
+        'T' => Some("typechange"),
+        'A' => Some("added"),
+        'D' => Some("deleted"),
+        'R' => Some("renamed"),
+        'C' => Some("copied"),
+        'U' => Some("unmerged"),
+        _ => None,
+    }
+}
+
+/// Which sides of an unmerged path are present, from its `XY` code
+fn conflict_kind(xy: &str) -> &'static str {
+    match xy {
+        "DD" => "both-deleted",
+        "AU" => "added-by-us",
+        "UD" => "deleted-by-them",
+        "UA" => "added-by-them",
+        "DU" => "deleted-by-us",
+        "AA" => "both-added",
+        _ => "both-modified",
+    }
+}
+
+/// `N...` for a plain file, `S<c><m><u>` for a submodule
+fn submodule_change(field: &str) -> Option<SubmoduleState> {
+    let flags: Vec<char> = field.strip_prefix('S')?.chars().collect();
+    Some(SubmoduleState {
+        commit_changed: flags.first() == Some(&'C'),
+        modified: flags.get(1) == Some(&'M'),
+        untracked: flags.get(2) == Some(&'U'),
+    })
+}
+
+fn tracked_entry(xy: &str, sub: &str, path: &str) -> StatusFile {
+    let mut codes = xy.chars();
+    let x = codes.next().unwrap_or('.');
+    let y = codes.next().unwrap_or('.');
+    // `.A` is an intent-to-add entry (`git add -N`): new, but not staged yet
+    let file_type = if x == 'A' || y == 'A' {
+        "added"
+    } else if x == 'D' || y == 'D' {
+        "deleted"
+    } else {
+        "modified"
+    };
+    let submodule = submodule_change(sub);
+    StatusFile {
+        path: path.to_string(),
+        old_path: None,
+        status: xy.replace('.', " "),
+        staged: x != '.',
+        unstaged: y != '.',
+        r#type: file_type.to_string(),
+        index_status: change_name(x).map(str::to_string),
+        worktree_status: change_name(y).map(str::to_string),
+        similarity: None,
+        is_submodule: submodule.is_some(),
+        submodule,
+        conflict: None,
+        ignore_rule: None,
+    }
+}
+
+/// An untracked (`?`) or ignored (`!`) path
+fn untracked_entry(path: &str, ignored: bool) -> StatusFile {
+    let kind = if ignored { "ignored" } else { "untracked" };
+    StatusFile {
+        path: path.to_string(),
+        old_path: None,
+        status: if ignored { "!!" } else { "??" }.to_string(),
+        staged: false,
+        unstaged: false,
+        r#type: kind.to_string(),
+        index_status: None,
+        worktree_status: Some(kind.to_string()),
+        similarity: None,
+        is_submodule: false,
+        submodule: None,
+        conflict: None,
+        ignore_rule: None,
+    }
+}
+
+/// Parse `git status --porcelain=v2 -z`. Paths are NUL-terminated and never
+/// quoted, so spaces, unicode and " -> " in file names come through intact.
+fn parse_porcelain_v2(output: &[u8]) -> Result<Vec<StatusFile>> {
+    let mut records = output
+        .split(|b| *b == 0)
+        .map(|record| String::from_utf8_lossy(record).to_string());
+    let mut files = Vec::new();
+
+    while let Some(record) = records.next() {
+        if record.is_empty() {
+            continue;
+        }
+        let (kind, rest) = record.split_at(1);
+        let rest = rest.strip_prefix(' ').unwrap_or(rest);
+        match kind {
+            // 1 XY sub mH mI mW hH hI path
+            "1" => {
+                let fields: Vec<&str> = rest.splitn(8, ' ').collect();
+                if fields.len() < 8 {
+                    bail!("Unexpected status record: {}", record);
+                }
+                files.push(tracked_entry(fields[0], fields[1], fields[7]));
+            }
+            // 2 XY sub mH mI mW hH hI Xscore path NUL origPath
+            "2" => {
+                let fields: Vec<&str> = rest.splitn(9, ' ').collect();
+                if fields.len() < 9 {
+                    bail!("Unexpected status record: {}", record);
+                }
+                let mut file = tracked_entry(fields[0], fields[1], fields[8]);
+                let score = fields[7];
+                file.r#type = if score.starts_with('C') {
+                    "copied"
+                } else {
+                    "renamed"
+                }
+                .to_string();
+                file.similarity = score[1..].parse().ok();
+                file.old_path = records.next();
+                files.push(file);
+            }
+            // u XY sub m1 m2 m3 mW h1 h2 h3 path
+            "u" => {
+                let fields: Vec<&str> = rest.splitn(10, ' ').collect();
+                if fields.len() < 10 {
+                    bail!("Unexpected status record: {}", record);
+                }
+                let mut file = tracked_entry(fields[0], fields[1], fields[9]);
+                file.r#type = "conflicted".to_string();
+                file.conflict = Some(conflict_kind(fields[0]).to_string());
+                files.push(file);
+            }
+            "?" => files.push(untracked_entry(rest, false)),
+            "!" => files.push(untracked_entry(rest, true)),
+            // Headers are not requested
+            _ => {}
+        }
+    }
+    Ok(files)
+}
+
+impl GitRepository {
+    /// Working tree status from `git status --porcelain=v2 -z`, one entry per
+    /// path with index and working tree changes reported separately. Ignored
+    /// paths (whole directories collapsed) come with the rule that ignores them.
+    pub fn status_files(&self, include_ignored: bool) -> Result<Vec<StatusFile>> {
+        let mut args = vec!["status", "--porcelain=v2", "-z"];
+        if include_ignored {
+            args.push("--ignored");
+        }
+        let output = self.run_git_bytes(&args)?;
+        let mut files = parse_porcelain_v2(&output)?;
+
+        if include_ignored {
+            let ignored: Vec<&str> = files
+                .iter()
+                .filter(|file| file.r#type == "ignored")
+                .map(|file| file.path.as_str())
+                .collect();
+            let mut rules = self.ignore_rules(&ignored)?;
+            for file in files.iter_mut().filter(|file| file.r#type == "ignored") {
+                file.ignore_rule = rules.remove(&file.path);
+            }
+        }
+        Ok(files)
+    }
+
+    /// Unmerged paths, with the conflict type in `StatusFile.conflict`
+    pub fn conflicted_files(&self) -> Result<Vec<StatusFile>> {
+        Ok(self
+            .status_files(false)?
+            .into_iter()
+            .filter(|file| file.conflict.is_some())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OID: &str = "0123456789012345678901234567890123456789";
+
+    fn ordinary(xy: &str, sub: &str, path: &str) -> String {
+        format!(
+            "1 {} {} 100644 100644 100644 {} {} {}",
+            xy, sub, OID, OID, path
+        )
+    }
+
+    #[test]
+    fn parses_paths_with_spaces_and_arrows() {
+        let output = format!("{}\0", ordinary(".M", "N...", "dir/a -> b.txt"));
+        let files = parse_porcelain_v2(output.as_bytes()).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "dir/a -> b.txt");
+        assert_eq!(files[0].r#type, "modified");
+        assert!(!files[0].staged);
+        assert!(files[0].unstaged);
+        assert_eq!(files[0].worktree_status.as_deref(), Some("modified"));
+    }
+
+    #[test]
+    fn reports_staged_and_intent_to_add_entries_as_added() {
+        let output = format!(
+            "{}\0{}\0",
+            ordinary("A.", "N...", "new.txt"),
+            ordinary(".A", "N...", "intent.txt")
+        );
+        let files = parse_porcelain_v2(output.as_bytes()).unwrap();
+        assert_eq!(files[0].r#type, "added");
+        assert!(files[0].staged);
+        assert_eq!(files[1].r#type, "added");
+        assert!(!files[1].staged);
+        assert!(files[1].unstaged);
+    }
+
+    #[test]
+    fn reads_the_original_path_of_a_rename() {
+        let output = format!(
+            "2 R. N... 100644 100644 100644 {} {} R87 new name.txt\0old name.txt\0",
+            OID, OID
+        );
+        let files = parse_porcelain_v2(output.as_bytes()).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].r#type, "renamed");
+        assert_eq!(files[0].path, "new name.txt");
+        assert_eq!(files[0].old_path.as_deref(), Some("old name.txt"));
+        assert_eq!(files[0].similarity, Some(87));
+    }
+
+    #[test]
+    fn classifies_conflicts() {
+        let output = format!(
+            "u AA N... 100644 100644 100644 100644 {} {} {} both.txt\0\
+             u UD N... 100644 100644 000000 100644 {} {} {} theirs.txt\0",
+            OID, OID, OID, OID, OID, OID
+        );
+        let files = parse_porcelain_v2(output.as_bytes()).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|file| file.r#type == "conflicted"));
+        assert_eq!(files[0].conflict.as_deref(), Some("both-added"));
+        assert_eq!(files[1].conflict.as_deref(), Some("deleted-by-them"));
+    }
+
+    #[test]
+    fn parses_submodule_and_untracked_entries() {
+        let output = format!(
+            "{}\0? untracked file\0! target/\0",
+            ordinary(".M", "SC.U", "vendor/lib")
+        );
+        let files = parse_porcelain_v2(output.as_bytes()).unwrap();
+        assert_eq!(files.len(), 3);
+        let submodule = files[0].submodule.as_ref().unwrap();
+        assert!(files[0].is_submodule);
+        assert!(submodule.commit_changed);
+        assert!(!submodule.modified);
+        assert!(submodule.untracked);
+        assert_eq!(files[1].r#type, "untracked");
+        assert_eq!(files[1].path, "untracked file");
+        assert_eq!(files[2].r#type, "ignored");
+        assert_eq!(files[2].path, "target/");
+    }
+
+    #[test]
+    fn rejects_truncated_records() {
+        assert!(parse_porcelain_v2(b"1 .M N... 100644\0").is_err());
+    }
+}
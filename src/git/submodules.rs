@@ -0,0 +1,261 @@
+use anyhow::Result;
+use git2::{ObjectType, Oid, Repository, SubmoduleIgnore, SubmoduleStatus};
+use std::path::Path;
+
+use crate::git::discovery::make_repo_id;
+use crate::git::repository::{FileDiff, GitRepository};
+use crate::models::{SubmoduleChange, SubmoduleCommit, SubmoduleInfo};
+
+/// Maximum number of commits listed for a submodule pointer change
+const MAX_SUBMODULE_LOG: usize = 200;
+
+const GITLINK_MODE: u32 = 0o160000;
+
+fn short(oid: Option<Oid>) -> String {
+    oid.map(|o| o.to_string()[..7].to_string())
+        .unwrap_or_else(|| "0000000".to_string())
+}
+
+impl GitRepository {
+    /// List submodules with their configuration and checkout state
+    pub fn list_submodules(&self) -> Result<Vec<SubmoduleInfo>> {
+        let workdir = self.repo.workdir().unwrap_or_else(|| self.path());
+        let mut result = Vec::new();
+
+        for sm in self.repo.submodules()? {
+            let name = sm.name().unwrap_or_default().to_string();
+            let path = sm.path().to_string_lossy().replace('\\', "/");
+            let status = self
+                .repo
+                .submodule_status(&name, SubmoduleIgnore::None)
+                .ok();
+
+            let checked_out = status
+                .map(|s| s.is_in_wd() && !s.is_wd_uninitialized())
+                .unwrap_or(false);
+            let repo_id = if checked_out {
+                let full = workdir.join(sm.path());
+                Some(make_repo_id(&full.canonicalize().unwrap_or(full)))
+            } else {
+                None
+            };
+
+            result.push(SubmoduleInfo {
+                name,
+                path,
+                url: sm.url().map(|u| u.to_string()),
+                branch: sm.branch().map(|b| b.to_string()),
+                recorded_commit: sm.head_id().map(|o| o.to_string()),
+                index_commit: sm.index_id().map(|o| o.to_string()),
+                checked_out_commit: sm.workdir_id().map(|o| o.to_string()),
+                initialized: status.map(|s| s.is_in_config()).unwrap_or(false),
+                checked_out,
+                out_of_date: status.map(|s| s.is_wd_modified()).unwrap_or(false),
+                dirty: status
+                    .map(|s| {
+                        s.contains(SubmoduleStatus::WD_INDEX_MODIFIED) || s.is_wd_wd_modified() || s.is_wd_untracked()
+                    })
+                    .unwrap_or(false),
+                repo_id,
+            });
+        }
+
+        Ok(result)
+    }
+
+    fn submodule_args<'a>(base: &[&'a str], paths: &'a [String]) -> Vec<&'a str> {
+        let mut args = base.to_vec();
+        if !paths.is_empty() {
+            args.push("--");
+            args.extend(paths.iter().map(|p| p.as_str()));
+        }
+        args
+    }
+
+    /// Register submodules from .gitmodules in .git/config
+    pub fn submodule_init(&self, paths: &[String]) -> Result<String> {
+        self.run_git(&Self::submodule_args(&["submodule", "init"], paths))
+    }
+
+    /// Check out the commits recorded in the superproject
+    pub fn submodule_update(&self, paths: &[String], init: bool, recursive: bool) -> Result<String> {
+        let mut base = vec!["submodule", "update"];
+        if init {
+            base.push("--init");
+        }
+        if recursive {
+            base.push("--recursive");
+        }
+        self.run_git(&Self::submodule_args(&base, paths))
+    }
+
+    /// Copy submodule URLs from .gitmodules into .git/config and the submodules' remotes
+    pub fn submodule_sync(&self, paths: &[String], recursive: bool) -> Result<String> {
+        let mut base = vec!["submodule", "sync"];
+        if recursive {
+            base.push("--recursive");
+        }
+        self.run_git(&Self::submodule_args(&base, paths))
+    }
+
+    /// Open the submodule's own repository, if it is checked out
+    fn open_submodule_repo(&self, path: &str) -> Option<Repository> {
+        if let Ok(sm) = self.repo.find_submodule(path) {
+            if let Ok(repo) = sm.open() {
+                return Some(repo);
+            }
+        }
+        let workdir = self.repo.workdir()?;
+        Repository::open(workdir.join(path)).ok()
+    }
+
+    /// Describe a submodule pointer change as a commit range, in the style of
+    /// `git diff --submodule=log`.
+    pub fn submodule_diff(&self, path: &str, old: Option<Oid>, new: Option<Oid>) -> FileDiff {
+        let sub_repo = self.open_submodule_repo(path);
+        let repo_id = sub_repo
+            .as_ref()
+            .and_then(|r| r.workdir())
+            .map(|p| make_repo_id(&p.canonicalize().unwrap_or_else(|_| p.to_path_buf())));
+
+        let mut commits = Vec::new();
+        let mut commits_available = false;
+        if let (Some(repo), Some(old), Some(new)) = (sub_repo.as_ref(), old, new) {
+            let mut collect = |from: Oid, hide: Oid, direction: &str| -> Result<(), git2::Error> {
+                let mut walk = repo.revwalk()?;
+                walk.push(from)?;
+                walk.hide(hide)?;
+                for oid in walk.take(MAX_SUBMODULE_LOG) {
+                    let commit = repo.find_commit(oid?)?;
+                    commits.push(SubmoduleCommit {
+                        sha: commit.id().to_string(),
+                        message: commit.summary().unwrap_or_default().to_string(),
+                        direction: direction.to_string(),
+                    });
+                }
+                Ok(())
+            };
+            commits_available = collect(new, old, "added").is_ok() && collect(old, new, "removed").is_ok();
+            if !commits_available {
+                commits.clear();
+            }
+        }
+
+        let mut diff = match (old, new) {
+            (None, _) => format!("Submodule {} {}...{} (new submodule)\n", path, short(old), short(new)),
+            (_, None) => format!("Submodule {} {}...{} (submodule deleted)\n", path, short(old), short(new)),
+            _ if !commits_available => format!(
+                "Submodule {} {}...{} (commits not present)\n",
+                path,
+                short(old),
+                short(new)
+            ),
+            _ => {
+                let rewind = commits.iter().all(|c| c.direction == "removed");
+                format!(
+                    "Submodule {} {}..{}{}:\n",
+                    path,
+                    short(old),
+                    short(new),
+                    if rewind && !commits.is_empty() { " (rewind)" } else { "" }
+                )
+            }
+        };
+        for commit in &commits {
+            let marker = if commit.direction == "added" { '>' } else { '<' };
+            diff.push_str(&format!("  {} {}\n", marker, commit.message));
+        }
+
+        FileDiff {
+            diff,
+            hunks: vec![],
+            file_path: path.to_string(),
+            submodule: Some(SubmoduleChange {
+                path: path.to_string(),
+                old_commit: old.map(|o| o.to_string()),
+                new_commit: new.map(|o| o.to_string()),
+                commits,
+                commits_available,
+                repo_id,
+            }),
+            binary: None,
+        }
+    }
+
+    /// Commit range diff for a submodule changed by a commit (against the parent at
+    /// `parent_index`). `None` if `path` is not a submodule on either side.
+    pub fn commit_submodule_diff(
+        &self,
+        commit_sha: &str,
+        path: &str,
+        parent_index: usize,
+    ) -> Result<Option<FileDiff>> {
+        let commit = self.repo.find_commit(Oid::from_str(commit_sha)?)?;
+        let tree = commit.tree()?;
+        let parent_tree = if commit.parent_count() > 0 {
+            Some(commit.parent(parent_index)?.tree()?)
+        } else {
+            None
+        };
+
+        let old = Self::gitlink_id(parent_tree.as_ref(), path);
+        let new = Self::gitlink_id(Some(&tree), path);
+        if old.is_none() && new.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(self.submodule_diff(path, old, new)))
+    }
+
+    /// Commit range diff for a submodule in the working tree (staged: HEAD vs index,
+    /// unstaged: index vs the submodule's checked-out commit). `None` if `path` is not a submodule.
+    pub fn working_submodule_diff(&self, path: &str, staged: bool) -> Result<Option<FileDiff>> {
+        let index = self.repo.index()?;
+        let index_id = index
+            .get_path(Path::new(path), 0)
+            .filter(|e| e.mode == GITLINK_MODE)
+            .map(|e| e.id);
+        let head_tree = self.repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+        let head_id = Self::gitlink_id(head_tree.as_ref(), path);
+
+        if index_id.is_none() && head_id.is_none() {
+            return Ok(None);
+        }
+
+        if staged {
+            return Ok(Some(self.submodule_diff(path, head_id, index_id)));
+        }
+
+        let sm = self.repo.find_submodule(path).ok();
+        let workdir_id = sm.as_ref().and_then(|s| s.workdir_id());
+        let name = sm
+            .as_ref()
+            .and_then(|s| s.name().map(|n| n.to_string()))
+            .unwrap_or_else(|| path.to_string());
+        let mut diff = if workdir_id.is_some() && workdir_id != index_id {
+            self.submodule_diff(path, index_id, workdir_id)
+        } else {
+            let mut diff = self.submodule_diff(path, index_id, index_id);
+            diff.diff.clear();
+            diff
+        };
+
+        // Uncommitted changes inside the submodule, as reported by `git diff`
+        if let Ok(status) = self.repo.submodule_status(&name, SubmoduleIgnore::None) {
+            if status.contains(SubmoduleStatus::WD_INDEX_MODIFIED) || status.is_wd_wd_modified() {
+                diff.diff.push_str(&format!("Submodule {} contains modified content\n", path));
+            }
+            if status.is_wd_untracked() {
+                diff.diff.push_str(&format!("Submodule {} contains untracked content\n", path));
+            }
+        }
+
+        Ok(Some(diff))
+    }
+
+    /// Tree entry id of a gitlink (submodule pointer) at `path`, if that is what the tree holds
+    fn gitlink_id(tree: Option<&git2::Tree>, path: &str) -> Option<Oid> {
+        tree.and_then(|t| t.get_path(Path::new(path)).ok())
+            .filter(|e| e.kind() == Some(ObjectType::Commit))
+            .map(|e| e.id())
+    }
+}
@@ -0,0 +1,310 @@
+use anyhow::{bail, Result};
+use git2::Repository;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::git::discovery::make_repo_id;
+use crate::git::repository::{check_argument, GitRepository};
+use crate::models::{Repo, WorktreeAddRequest, WorktreeInfo};
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Parse `git worktree list --porcelain`. Records are separated by blank lines;
+/// the first record is always the main worktree.
+fn parse_worktree_list(output: &str, current: &Path) -> Vec<WorktreeInfo> {
+    let mut worktrees = Vec::new();
+
+    for (idx, record) in output.split("\n\n").filter(|r| !r.trim().is_empty()).enumerate() {
+        let mut info = WorktreeInfo {
+            path: String::new(),
+            head: None,
+            branch: None,
+            detached: false,
+            bare: false,
+            is_main: idx == 0,
+            is_current: false,
+            locked: false,
+            lock_reason: None,
+            prunable: false,
+            prune_reason: None,
+            repo_id: String::new(),
+        };
+
+        for line in record.lines() {
+            let (key, value) = match line.split_once(' ') {
+                Some((k, v)) => (k, Some(v.to_string())),
+                None => (line, None),
+            };
+            match key {
+                "worktree" => info.path = value.unwrap_or_default(),
+                "HEAD" => info.head = value,
+                "branch" => {
+                    info.branch = value.map(|b| {
+                        b.strip_prefix("refs/heads/").map(|s| s.to_string()).unwrap_or(b)
+                    })
+                }
+                "detached" => info.detached = true,
+                "bare" => info.bare = true,
+                "locked" => {
+                    info.locked = true;
+                    info.lock_reason = value;
+                }
+                "prunable" => {
+                    info.prunable = true;
+                    info.prune_reason = value;
+                }
+                _ => {}
+            }
+        }
+
+        if info.path.is_empty() {
+            continue;
+        }
+        let path = PathBuf::from(&info.path);
+        info.is_current = same_path(&path, current);
+        info.repo_id = make_repo_id(&path);
+        worktrees.push(info);
+    }
+
+    worktrees
+}
+
+impl GitRepository {
+    fn worktree_root(&self) -> &Path {
+        self.repo.workdir().unwrap_or_else(|| self.path())
+    }
+
+    /// List all worktrees of the repository (main first), with branch, HEAD and lock/prune state
+    pub fn list_worktrees(&self) -> Result<Vec<WorktreeInfo>> {
+        let output = self.run_git(&["worktree", "list", "--porcelain"])?;
+        Ok(parse_worktree_list(&output, self.worktree_root()))
+    }
+
+    /// Branch name -> worktree path, for branches checked out in a worktree other than this one
+    pub fn branches_checked_out_elsewhere(&self) -> HashMap<String, String> {
+        // Skip spawning git for the common case of a repo without linked worktrees
+        let has_linked = self.repo.is_worktree()
+            || self.repo.worktrees().map(|w| !w.is_empty()).unwrap_or(false);
+        if !has_linked {
+            return HashMap::new();
+        }
+
+        self.list_worktrees()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|w| !w.is_current)
+            .filter_map(|w| w.branch.map(|b| (b, w.path)))
+            .collect()
+    }
+
+    /// Create a linked worktree. Relative paths are placed next to the repository,
+    /// so the new worktree shows up in the same repo list.
+    pub fn add_worktree(&self, req: &WorktreeAddRequest) -> Result<String> {
+        if req.path.trim().is_empty() {
+            bail!("worktree path is required");
+        }
+        if req.branch.is_some() && (req.commit.is_some() || req.new_branch.is_some()) {
+            bail!("specify either an existing branch, or a commit and/or a new branch");
+        }
+        check_argument("worktree path", &req.path)?;
+        for (what, value) in [
+            ("branch", &req.branch),
+            ("commit", &req.commit),
+            ("branch name", &req.new_branch),
+        ] {
+            if let Some(value) = value {
+                check_argument(what, value)?;
+            }
+        }
+
+        let path = Path::new(req.path.trim());
+        let target = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            let base = self.worktree_root();
+            base.parent().unwrap_or(base).join(path)
+        };
+        let target = target.to_string_lossy().to_string();
+
+        let mut args = vec!["worktree", "add"];
+        if req.force {
+            args.push("--force");
+        }
+        if let Some(new_branch) = req.new_branch.as_deref() {
+            args.push("-b");
+            args.push(new_branch);
+        } else if req.commit.is_some() {
+            args.push("--detach");
+        }
+        args.push(&target);
+        if let Some(branch) = req.branch.as_deref() {
+            args.push(branch);
+        } else if let Some(commit) = req.commit.as_deref() {
+            args.push(commit);
+        }
+
+        self.run_git(&args)
+    }
+
+    /// Remove a linked worktree; `force` also discards its uncommitted changes
+    pub fn remove_worktree(&self, path: &str, force: bool) -> Result<String> {
+        check_argument("worktree path", path)?;
+        let mut args = vec!["worktree", "remove"];
+        if force {
+            args.push("--force");
+        }
+        args.push(path);
+        self.run_git(&args)
+    }
+
+    /// Clean up administrative data for worktrees whose directories are gone
+    pub fn prune_worktrees(&self) -> Result<String> {
+        self.run_git(&["worktree", "prune"])
+    }
+}
+
+/// Mark linked worktrees in a repo list and add the worktrees of each listed repository
+/// as related entries right after it.
+pub fn add_worktree_entries(repos: Vec<Repo>) -> Vec<Repo> {
+    let mut seen: Vec<String> = repos.iter().map(|r| r.id.clone()).collect();
+    let mut result = Vec::with_capacity(repos.len());
+
+    for mut entry in repos {
+        let repo = match Repository::open(&entry.id) {
+            Ok(repo) => repo,
+            Err(_) => {
+                result.push(entry);
+                continue;
+            }
+        };
+
+        if repo.is_worktree() {
+            // commondir is the main repository's .git directory
+            entry.worktree_of = repo.commondir().parent().map(make_repo_id);
+            result.push(entry);
+            continue;
+        }
+
+        let main_id = entry.id.clone();
+        result.push(entry);
+
+        let names = match repo.worktrees() {
+            Ok(names) => names,
+            Err(_) => continue,
+        };
+        for name in names.iter().flatten() {
+            let path = match repo.find_worktree(name) {
+                Ok(wt) => wt.path().to_path_buf(),
+                Err(_) => continue,
+            };
+            if !path.exists() {
+                continue;
+            }
+            let id = make_repo_id(&path.canonicalize().unwrap_or(path.clone()));
+            if seen.contains(&id) {
+                continue;
+            }
+            seen.push(id.clone());
+            result.push(Repo {
+                id,
+                name: path
+                    .file_name()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_else(|| name.to_string()),
+                worktree_of: Some(main_id.clone()),
+            });
+        }
+    }
+
+    // Group worktrees discovered on their own (e.g. siblings under repos_root) with their main repo
+    let (mains, mut linked): (Vec<Repo>, Vec<Repo>) =
+        result.into_iter().partition(|r| r.worktree_of.is_none());
+    let mut grouped = Vec::with_capacity(mains.len() + linked.len());
+    for main in mains {
+        let (own, rest): (Vec<Repo>, Vec<Repo>) = linked
+            .into_iter()
+            .partition(|r| r.worktree_of.as_deref() == Some(main.id.as_str()));
+        linked = rest;
+        grouped.push(main);
+        grouped.extend(own);
+    }
+    grouped.extend(linked);
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_main_linked_and_detached_worktrees() {
+        let output = "worktree /work/main\n\
+                      HEAD 1111111111111111111111111111111111111111\n\
+                      branch refs/heads/main\n\
+                      \n\
+                      worktree /work/feature x\n\
+                      HEAD 2222222222222222222222222222222222222222\n\
+                      branch refs/heads/feature/x\n\
+                      locked moved to a usb drive\n\
+                      \n\
+                      worktree /work/detached\n\
+                      HEAD 3333333333333333333333333333333333333333\n\
+                      detached\n\
+                      prunable gitdir file points to non-existent location\n\
+                      \n";
+        let worktrees = parse_worktree_list(output, Path::new("/work/feature x"));
+        assert_eq!(worktrees.len(), 3);
+
+        let main = &worktrees[0];
+        assert!(main.is_main);
+        assert!(!main.is_current);
+        assert_eq!(main.path, "/work/main");
+        assert_eq!(main.branch.as_deref(), Some("main"));
+
+        let feature = &worktrees[1];
+        assert!(!feature.is_main);
+        assert!(feature.is_current);
+        assert_eq!(feature.branch.as_deref(), Some("feature/x"));
+        assert!(feature.locked);
+        assert_eq!(feature.lock_reason.as_deref(), Some("moved to a usb drive"));
+        assert_eq!(feature.repo_id, "/work/feature x");
+
+        let detached = &worktrees[2];
+        assert!(detached.detached);
+        assert_eq!(detached.branch, None);
+        assert_eq!(
+            detached.head.as_deref(),
+            Some("3333333333333333333333333333333333333333")
+        );
+        assert!(detached.prunable);
+        assert!(detached
+            .prune_reason
+            .as_deref()
+            .is_some_and(|reason| reason.starts_with("gitdir")));
+    }
+
+    #[test]
+    fn parses_a_bare_main_repository_and_a_lock_without_reason() {
+        let output = "worktree /work/repo.git\nbare\n\nworktree /work/wt\n\
+                      HEAD 1111111111111111111111111111111111111111\n\
+                      branch refs/heads/topic\nlocked\n";
+        let worktrees = parse_worktree_list(output, Path::new("/elsewhere"));
+        assert_eq!(worktrees.len(), 2);
+        assert!(worktrees[0].bare);
+        assert!(worktrees[0].head.is_none());
+        assert!(worktrees[1].locked);
+        assert_eq!(worktrees[1].lock_reason, None);
+        assert!(worktrees.iter().all(|wt| !wt.is_current));
+    }
+
+    #[test]
+    fn skips_empty_records() {
+        assert!(parse_worktree_list("", Path::new("/work")).is_empty());
+        assert!(parse_worktree_list("\n\n\n", Path::new("/work")).is_empty());
+    }
+}
@@ -0,0 +1,24 @@
+use crate::config::Config;
+use crate::handlers::{api_error, run_blocking, ApiError};
+use crate::models::{CloneRequest, CloneStatus};
+use crate::services::clone;
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+
+/// Start cloning a repository into repos_root. Poll the returned job for progress.
+pub async fn post_clone(
+    State(config): State<Config>,
+    Json(req): Json<CloneRequest>,
+) -> Result<Json<CloneStatus>, ApiError> {
+    run_blocking(move || clone::start_clone(&config.repos_root, &req)).await
+}
+
+pub async fn get_clone(Path(id): Path<String>) -> Result<Json<CloneStatus>, ApiError> {
+    clone::clone_status(&id).map(Json).map_err(api_error)
+}
+
+pub async fn post_clone_cancel(Path(id): Path<String>) -> Result<Json<CloneStatus>, ApiError> {
+    clone::cancel_clone(&id).map(Json).map_err(api_error)
+}
@@ -0,0 +1,63 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+
+use crate::config::Config;
+use crate::handlers::{run_blocking, ApiError};
+use crate::models::{CompareFilesResponse, DiffResponse, DiffSettings};
+use crate::services::compare;
+
+#[derive(serde::Deserialize)]
+pub struct CompareQuery {
+    /// A revision, INDEX or WORKTREE
+    from: Option<String>,
+    to: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct CompareDiffQuery {
+    from: Option<String>,
+    to: Option<String>,
+    path: String,
+    /// Path on the `from` side of a renamed or copied file
+    #[serde(rename = "oldPath")]
+    old_path: Option<String>,
+    #[serde(flatten)]
+    options: DiffSettings,
+}
+
+pub async fn get_compare_files(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    Query(params): Query<CompareQuery>,
+) -> Result<Json<CompareFilesResponse>, ApiError> {
+    run_blocking(move || {
+        compare::get_compare_files(
+            &config.repos_root,
+            &repo,
+            params.from.as_deref(),
+            params.to.as_deref(),
+        )
+    })
+    .await
+}
+
+pub async fn get_compare_diff(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    Query(params): Query<CompareDiffQuery>,
+) -> Result<Json<DiffResponse>, ApiError> {
+    run_blocking(move || {
+        compare::get_compare_diff(
+            &config.repos_root,
+            &repo,
+            params.from.as_deref(),
+            params.to.as_deref(),
+            &params.path,
+            params.old_path.as_deref(),
+            &params.options,
+        )
+    })
+    .await
+}
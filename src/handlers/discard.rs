@@ -0,0 +1,44 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+
+use crate::config::Config;
+use crate::handlers::{run_blocking, ApiError};
+use crate::models::{
+    CleanRequest, DiscardRequest, DiscardResponse, DiscardRestoreRequest, DiscardSnapshot,
+};
+use crate::services::discard;
+
+/// Discard working tree changes (`dryRun` only reports them)
+pub async fn discard_changes(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    Json(req): Json<DiscardRequest>,
+) -> Result<Json<DiscardResponse>, ApiError> {
+    run_blocking(move || discard::discard(&config.repos_root, &repo, &req)).await
+}
+
+/// Remove untracked and/or ignored files
+pub async fn clean(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    Json(req): Json<CleanRequest>,
+) -> Result<Json<DiscardResponse>, ApiError> {
+    run_blocking(move || discard::clean(&config.repos_root, &repo, &req)).await
+}
+
+pub async fn get_discard_snapshots(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+) -> Result<Json<Vec<DiscardSnapshot>>, ApiError> {
+    run_blocking(move || discard::get_snapshots(&config.repos_root, &repo)).await
+}
+
+pub async fn restore_discarded(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    Json(req): Json<DiscardRestoreRequest>,
+) -> Result<Json<DiscardResponse>, ApiError> {
+    run_blocking(move || discard::restore(&config.repos_root, &repo, &req)).await
+}
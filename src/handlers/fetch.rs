@@ -1,35 +1,73 @@
-use crate::git::repository::GitRepository;
 use crate::config::Config;
+use crate::handlers::{api_error, run_blocking, ApiError};
+use crate::models::{
+    FetchPolicy, FetchPolicyRequest, RepoFetchStatus, ShallowFetchRequest, ShallowInfo,
+};
+use crate::services::{fetch, resolve_root};
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Json},
 };
-use std::path::PathBuf;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct FetchStatusQuery {
+    repos_root: Option<String>,
+}
 
+/// Fetch all remotes now; replies with a plain-text status message
 pub async fn fetch_repo(
     State(config): State<Config>,
     Path(repo_name): Path<String>,
 ) -> impl IntoResponse {
-    let repo_path = PathBuf::from(&config.repos_root).join(repo_name);
-
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return (StatusCode::NOT_FOUND, "Repository not found").into_response();
+    match run_blocking(move || fetch::fetch_repo(&config.repos_root, &repo_name)).await {
+        Ok(Json(message)) => (StatusCode::OK, message).into_response(),
+        Err((status, Json(error))) => (status, error.error).into_response(),
     }
+}
 
-    match GitRepository::open(&repo_path) {
-        Ok(repo) => match repo.fetch_all() {
-            Ok(_) => (StatusCode::OK, "Fetch successful").into_response(),
-            Err(e) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to fetch: {}", e),
-            )
-                .into_response(),
-        },
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Failed to open repository: {}", e),
-        )
-            .into_response(),
-    }
-}
\ No newline at end of file
+/// Fetch status (last fetch, duration, result, next run) for every repo under repos_root
+pub async fn get_fetch_status(
+    State(config): State<Config>,
+    Query(query): Query<FetchStatusQuery>,
+) -> Result<Json<Vec<RepoFetchStatus>>, ApiError> {
+    let repos_root = resolve_root(query.repos_root.as_deref(), &config.repos_root);
+    run_blocking(move || fetch::get_fetch_status(&repos_root)).await
+}
+
+/// Get the background fetch policy for a repository
+pub async fn get_fetch_policy(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+) -> Json<FetchPolicy> {
+    Json(fetch::get_fetch_policy(&config.repos_root, &repo))
+}
+
+/// Change the background fetch interval for a repository, or turn it off
+pub async fn set_fetch_policy(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    Json(req): Json<FetchPolicyRequest>,
+) -> Result<Json<FetchPolicy>, ApiError> {
+    fetch::set_fetch_policy(&config.repos_root, &repo, req.enabled, req.interval_secs)
+        .map(Json)
+        .map_err(api_error)
+}
+
+/// Whether the repository is a shallow clone, and where its history stops
+pub async fn get_shallow_info(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+) -> Result<Json<ShallowInfo>, ApiError> {
+    run_blocking(move || fetch::get_shallow_info(&config.repos_root, &repo)).await
+}
+
+/// Deepen a shallow clone by N commits or to a date, or unshallow it completely
+pub async fn deepen_history(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    Json(req): Json<ShallowFetchRequest>,
+) -> Result<Json<ShallowInfo>, ApiError> {
+    run_blocking(move || fetch::deepen_history(&config.repos_root, &repo, &req)).await
+}
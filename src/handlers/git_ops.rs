@@ -1,15 +1,15 @@
 use crate::config::Config;
-use crate::git::repository::GitRepository;
+use crate::handlers::{run_blocking, ApiError};
 use crate::models::{
-    BranchStatusResponse, ErrorResponse, GitOperationResponse, StashListResponse,
+    BranchStatusResponse, DiffResponse, DiffSettings, GitOperationResponse, StashApplyResponse,
+    StashBranchRequest, StashDetail, StashListResponse, StashPushRequest,
 };
+use crate::services::{branches, git_ops};
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
     response::Json,
 };
 use serde::Deserialize;
-use std::path::PathBuf;
 
 #[derive(Deserialize)]
 pub struct StashPushQuery {
@@ -20,195 +20,51 @@ pub struct StashPushQuery {
 pub struct StashRefQuery {
     #[serde(rename = "ref")]
     stash_ref: Option<String>,
+    /// Restore the staged state as well (`--index`)
+    index: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct StashDiffQuery {
+    #[serde(rename = "ref")]
+    stash_ref: Option<String>,
+    path: String,
+    /// "combined" (default), "index", "worktree" or "untracked"
+    part: Option<String>,
+    #[serde(flatten)]
+    options: DiffSettings,
 }
 
 /// Get the current branch status including ahead/behind counts and stash info
 pub async fn get_branch_status(
     State(config): State<Config>,
     Path(repo_name): Path<String>,
-) -> Result<Json<BranchStatusResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let repo_path = PathBuf::from(&config.repos_root).join(&repo_name);
-
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "Repository not found".to_string(),
-            }),
-        ));
-    }
-
-    let git_repo = GitRepository::open(&repo_path).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to open repository: {}", e),
-            }),
-        )
-    })?;
-
-    let branch = git_repo.get_current_branch().unwrap_or_else(|_| "HEAD".to_string());
-    let has_upstream = git_repo.has_upstream().unwrap_or(false);
-    let (ahead, behind) = if has_upstream {
-        git_repo.get_ahead_behind_upstream().unwrap_or((0, 0))
-    } else {
-        (0, 0)
-    };
-    let has_uncommitted = git_repo.has_uncommitted_changes().unwrap_or(false);
-    let stash_count = git_repo.stash_list().map(|l| l.len()).unwrap_or(0);
-
-    Ok(Json(BranchStatusResponse {
-        branch,
-        has_upstream,
-        ahead,
-        behind,
-        has_uncommitted,
-        stash_count,
-    }))
+) -> Result<Json<BranchStatusResponse>, ApiError> {
+    run_blocking(move || branches::branch_status(&config.repos_root, &repo_name)).await
 }
 
 /// Pull changes from remote
 pub async fn pull_repo(
     State(config): State<Config>,
     Path(repo_name): Path<String>,
-) -> Result<Json<GitOperationResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let repo_path = PathBuf::from(&config.repos_root).join(&repo_name);
-
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "Repository not found".to_string(),
-            }),
-        ));
-    }
-
-    let git_repo = GitRepository::open(&repo_path).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to open repository: {}", e),
-            }),
-        )
-    })?;
-
-    // Check if there's an upstream configured
-    if !git_repo.has_upstream().unwrap_or(false) {
-        return Ok(Json(GitOperationResponse {
-            success: false,
-            message: Some("No upstream branch configured".to_string()),
-            output: None,
-            error: Some("No upstream branch configured. Push first or set upstream manually.".to_string()),
-        }));
-    }
-
-    match git_repo.pull() {
-        Ok(output) => Ok(Json(GitOperationResponse {
-            success: true,
-            message: Some("Pull successful".to_string()),
-            output: Some(output),
-            error: None,
-        })),
-        Err(e) => Ok(Json(GitOperationResponse {
-            success: false,
-            message: None,
-            output: None,
-            error: Some(e.to_string()),
-        })),
-    }
+) -> Result<Json<GitOperationResponse>, ApiError> {
+    run_blocking(move || git_ops::pull_repo(&config.repos_root, &repo_name)).await
 }
 
 /// Push changes to remote
 pub async fn push_repo(
     State(config): State<Config>,
     Path(repo_name): Path<String>,
-) -> Result<Json<GitOperationResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let repo_path = PathBuf::from(&config.repos_root).join(&repo_name);
-
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "Repository not found".to_string(),
-            }),
-        ));
-    }
-
-    let git_repo = GitRepository::open(&repo_path).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to open repository: {}", e),
-            }),
-        )
-    })?;
-
-    // Check if there's an upstream configured
-    let has_upstream = git_repo.has_upstream().unwrap_or(false);
-
-    if has_upstream {
-        match git_repo.push() {
-            Ok(output) => Ok(Json(GitOperationResponse {
-                success: true,
-                message: Some("Push successful".to_string()),
-                output: Some(output),
-                error: None,
-            })),
-            Err(e) => Ok(Json(GitOperationResponse {
-                success: false,
-                message: None,
-                output: None,
-                error: Some(e.to_string()),
-            })),
-        }
-    } else {
-        // Try to push with upstream tracking
-        let branch = git_repo.get_current_branch().unwrap_or_else(|_| "HEAD".to_string());
-        match git_repo.push_set_upstream(&branch) {
-            Ok(output) => Ok(Json(GitOperationResponse {
-                success: true,
-                message: Some(format!("Pushed and set upstream for branch '{}'", branch)),
-                output: Some(output),
-                error: None,
-            })),
-            Err(e) => Ok(Json(GitOperationResponse {
-                success: false,
-                message: None,
-                output: None,
-                error: Some(e.to_string()),
-            })),
-        }
-    }
+) -> Result<Json<GitOperationResponse>, ApiError> {
+    run_blocking(move || git_ops::push_repo(&config.repos_root, &repo_name)).await
 }
 
 /// List all stashes
 pub async fn stash_list(
     State(config): State<Config>,
     Path(repo_name): Path<String>,
-) -> Result<Json<StashListResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let repo_path = PathBuf::from(&config.repos_root).join(&repo_name);
-
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "Repository not found".to_string(),
-            }),
-        ));
-    }
-
-    let git_repo = GitRepository::open(&repo_path).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to open repository: {}", e),
-            }),
-        )
-    })?;
-
-    let entries = git_repo.stash_list().unwrap_or_else(|_| Vec::new());
-
-    Ok(Json(StashListResponse { entries }))
+) -> Result<Json<StashListResponse>, ApiError> {
+    run_blocking(move || git_ops::stash_list(&config.repos_root, &repo_name)).await
 }
 
 /// Push changes to stash
@@ -216,103 +72,30 @@ pub async fn stash_push(
     State(config): State<Config>,
     Path(repo_name): Path<String>,
     Query(params): Query<StashPushQuery>,
-) -> Result<Json<GitOperationResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let repo_path = PathBuf::from(&config.repos_root).join(&repo_name);
-
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "Repository not found".to_string(),
-            }),
-        ));
-    }
-
-    let git_repo = GitRepository::open(&repo_path).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to open repository: {}", e),
-            }),
-        )
-    })?;
-
-    // Check if there are changes to stash
-    if !git_repo.has_uncommitted_changes().unwrap_or(false) {
-        return Ok(Json(GitOperationResponse {
-            success: false,
-            message: Some("No local changes to stash".to_string()),
-            output: None,
-            error: None,
-        }));
-    }
-
-    match git_repo.stash_push(params.message.as_deref()) {
-        Ok(output) => Ok(Json(GitOperationResponse {
-            success: true,
-            message: Some("Changes stashed".to_string()),
-            output: Some(output),
-            error: None,
-        })),
-        Err(e) => Ok(Json(GitOperationResponse {
-            success: false,
-            message: None,
-            output: None,
-            error: Some(e.to_string()),
-        })),
+    req: Option<Json<StashPushRequest>>,
+) -> Result<Json<GitOperationResponse>, ApiError> {
+    let mut req = req.map(|Json(r)| r).unwrap_or_default();
+    if req.message.is_none() {
+        req.message = params.message;
     }
+    run_blocking(move || git_ops::stash_push(&config.repos_root, &repo_name, &req)).await
 }
 
-/// Pop the most recent stash
+/// Pop a stash (the most recent one by default)
 pub async fn stash_pop(
     State(config): State<Config>,
     Path(repo_name): Path<String>,
-) -> Result<Json<GitOperationResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let repo_path = PathBuf::from(&config.repos_root).join(&repo_name);
-
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "Repository not found".to_string(),
-            }),
-        ));
-    }
-
-    let git_repo = GitRepository::open(&repo_path).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to open repository: {}", e),
-            }),
+    Query(params): Query<StashRefQuery>,
+) -> Result<Json<StashApplyResponse>, ApiError> {
+    run_blocking(move || {
+        git_ops::stash_pop(
+            &config.repos_root,
+            &repo_name,
+            params.stash_ref.as_deref(),
+            params.index.unwrap_or(false),
         )
-    })?;
-
-    // Check if there are stashes to pop
-    let stashes = git_repo.stash_list().unwrap_or_else(|_| Vec::new());
-    if stashes.is_empty() {
-        return Ok(Json(GitOperationResponse {
-            success: false,
-            message: Some("No stashes to pop".to_string()),
-            output: None,
-            error: None,
-        }));
-    }
-
-    match git_repo.stash_pop() {
-        Ok(output) => Ok(Json(GitOperationResponse {
-            success: true,
-            message: Some("Stash popped".to_string()),
-            output: Some(output),
-            error: None,
-        })),
-        Err(e) => Ok(Json(GitOperationResponse {
-            success: false,
-            message: None,
-            output: None,
-            error: Some(e.to_string()),
-        })),
-    }
+    })
+    .await
 }
 
 /// Apply a specific stash
@@ -320,43 +103,33 @@ pub async fn stash_apply(
     State(config): State<Config>,
     Path(repo_name): Path<String>,
     Query(params): Query<StashRefQuery>,
-) -> Result<Json<GitOperationResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let repo_path = PathBuf::from(&config.repos_root).join(&repo_name);
-
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "Repository not found".to_string(),
-            }),
-        ));
-    }
-
-    let stash_ref = params.stash_ref.unwrap_or_else(|| "stash@{0}".to_string());
-
-    let git_repo = GitRepository::open(&repo_path).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to open repository: {}", e),
-            }),
+) -> Result<Json<StashApplyResponse>, ApiError> {
+    run_blocking(move || {
+        git_ops::stash_apply(
+            &config.repos_root,
+            &repo_name,
+            params.stash_ref.as_deref(),
+            params.index.unwrap_or(false),
         )
-    })?;
+    })
+    .await
+}
 
-    match git_repo.stash_apply(&stash_ref) {
-        Ok(output) => Ok(Json(GitOperationResponse {
-            success: true,
-            message: Some(format!("Stash {} applied", stash_ref)),
-            output: Some(output),
-            error: None,
-        })),
-        Err(e) => Ok(Json(GitOperationResponse {
-            success: false,
-            message: None,
-            output: None,
-            error: Some(e.to_string()),
-        })),
-    }
+/// Create a branch at a stash's base commit and restore the stash there
+pub async fn stash_branch(
+    State(config): State<Config>,
+    Path(repo_name): Path<String>,
+    Json(req): Json<StashBranchRequest>,
+) -> Result<Json<StashApplyResponse>, ApiError> {
+    run_blocking(move || {
+        git_ops::stash_branch(
+            &config.repos_root,
+            &repo_name,
+            &req.branch,
+            req.stash_ref.as_deref(),
+        )
+    })
+    .await
 }
 
 /// Drop a specific stash
@@ -364,41 +137,41 @@ pub async fn stash_drop(
     State(config): State<Config>,
     Path(repo_name): Path<String>,
     Query(params): Query<StashRefQuery>,
-) -> Result<Json<GitOperationResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let repo_path = PathBuf::from(&config.repos_root).join(&repo_name);
-
-    if !repo_path.exists() || !repo_path.is_dir() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "Repository not found".to_string(),
-            }),
-        ));
-    }
+) -> Result<Json<GitOperationResponse>, ApiError> {
+    run_blocking(move || {
+        git_ops::stash_drop(&config.repos_root, &repo_name, params.stash_ref.as_deref())
+    })
+    .await
+}
 
-    let stash_ref = params.stash_ref.unwrap_or_else(|| "stash@{0}".to_string());
+/// Show a stash: base commit, branch and the files it touches, split by index,
+/// worktree and untracked parts
+pub async fn stash_show(
+    State(config): State<Config>,
+    Path(repo_name): Path<String>,
+    Query(params): Query<StashRefQuery>,
+) -> Result<Json<StashDetail>, ApiError> {
+    run_blocking(move || {
+        git_ops::stash_show(&config.repos_root, &repo_name, params.stash_ref.as_deref())
+    })
+    .await
+}
 
-    let git_repo = GitRepository::open(&repo_path).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to open repository: {}", e),
-            }),
+/// Diff of a single file in a stash
+pub async fn stash_diff(
+    State(config): State<Config>,
+    Path(repo_name): Path<String>,
+    Query(params): Query<StashDiffQuery>,
+) -> Result<Json<DiffResponse>, ApiError> {
+    run_blocking(move || {
+        git_ops::stash_diff(
+            &config.repos_root,
+            &repo_name,
+            params.stash_ref.as_deref(),
+            &params.path,
+            params.part.as_deref(),
+            &params.options,
         )
-    })?;
-
-    match git_repo.stash_drop(&stash_ref) {
-        Ok(output) => Ok(Json(GitOperationResponse {
-            success: true,
-            message: Some(format!("Stash {} dropped", stash_ref)),
-            output: Some(output),
-            error: None,
-        })),
-        Err(e) => Ok(Json(GitOperationResponse {
-            success: false,
-            message: None,
-            output: None,
-            error: Some(e.to_string()),
-        })),
-    }
+    })
+    .await
 }
@@ -0,0 +1,24 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+
+use crate::config::Config;
+use crate::handlers::{run_blocking, ApiError};
+use crate::models::{HookInfo, HookListResponse, HookToggleRequest};
+use crate::services::hooks;
+
+pub async fn get_hooks(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+) -> Result<Json<HookListResponse>, ApiError> {
+    run_blocking(move || hooks::get_hooks(&config.repos_root, &repo)).await
+}
+
+pub async fn set_hook_enabled(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    Json(req): Json<HookToggleRequest>,
+) -> Result<Json<HookInfo>, ApiError> {
+    run_blocking(move || hooks::set_hook_enabled(&config.repos_root, &repo, &req)).await
+}
@@ -1,12 +1,51 @@
 pub mod branches;
+pub mod clone;
 pub mod commits;
+pub mod compare;
 pub mod conflicts;
 pub mod diff;
+pub mod discard;
 pub mod explorer;
 pub mod browse;
 pub mod files;
 pub mod git_ops;
+pub mod hooks;
+pub mod patch;
 pub mod rebase;
 pub mod repos;
 pub mod staging;
 pub mod fetch;
+pub mod submodules;
+pub mod worktrees;
+
+use axum::{http::StatusCode, response::Json};
+
+use crate::models::ErrorResponse;
+use crate::services::{ServiceError, ServiceResult};
+
+pub type ApiError = (StatusCode, Json<ErrorResponse>);
+
+pub fn api_error(e: ServiceError) -> ApiError {
+    let status = match e {
+        ServiceError::NotFound(_) => StatusCode::NOT_FOUND,
+        ServiceError::BadRequest(_) => StatusCode::BAD_REQUEST,
+        ServiceError::HookFailed(..) | ServiceError::LintFailed(..) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        ServiceError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(ErrorResponse::from(e)))
+}
+
+/// Run a service call on the blocking thread pool and map its result to a JSON response
+pub async fn run_blocking<T, F>(f: F) -> Result<Json<T>, ApiError>
+where
+    F: FnOnce() -> ServiceResult<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| api_error(ServiceError::Internal(format!("Task join error: {}", e))))?
+        .map(Json)
+        .map_err(api_error)
+}
@@ -0,0 +1,46 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+
+use crate::config::Config;
+use crate::handlers::{run_blocking, ApiError};
+use crate::models::{
+    DiffSettings, FormatPatchRequest, FormatPatchResponse, PatchApplyRequest, PatchApplyResponse,
+    PatchResponse,
+};
+use crate::services::patch;
+
+#[derive(serde::Deserialize)]
+pub struct PatchQuery {
+    /// A commit or a range such as `main..topic`
+    #[serde(rename = "ref")]
+    ref_: String,
+    #[serde(flatten)]
+    options: DiffSettings,
+}
+
+pub async fn get_patch(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    Query(params): Query<PatchQuery>,
+) -> Result<Json<PatchResponse>, ApiError> {
+    run_blocking(move || patch::get_patch(&config.repos_root, &repo, &params.ref_, &params.options))
+        .await
+}
+
+pub async fn format_patch(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    Json(req): Json<FormatPatchRequest>,
+) -> Result<Json<FormatPatchResponse>, ApiError> {
+    run_blocking(move || patch::format_patch(&config.repos_root, &repo, &req)).await
+}
+
+pub async fn apply_patch(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    Json(req): Json<PatchApplyRequest>,
+) -> Result<Json<PatchApplyResponse>, ApiError> {
+    run_blocking(move || patch::apply_patch(&config.repos_root, &repo, &req)).await
+}
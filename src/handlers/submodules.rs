@@ -0,0 +1,54 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+
+use crate::config::Config;
+use crate::handlers::{run_blocking, ApiError};
+use crate::models::{SubmoduleInfo, SubmoduleUpdateRequest};
+use crate::services::submodules::{self, SubmoduleAction};
+
+/// List submodules with recorded vs checked-out commits and dirty state
+pub async fn get_submodules(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+) -> Result<Json<Vec<SubmoduleInfo>>, ApiError> {
+    run_blocking(move || submodules::get_submodules(&config.repos_root, &repo)).await
+}
+
+async fn run_submodule_action(
+    config: Config,
+    repo: String,
+    req: Option<Json<SubmoduleUpdateRequest>>,
+    action: SubmoduleAction,
+) -> Result<Json<Vec<SubmoduleInfo>>, ApiError> {
+    let req = req.map(|Json(r)| r).unwrap_or_default();
+    run_blocking(move || {
+        submodules::run_submodule_action(&config.repos_root, &repo, &req, action)
+    })
+    .await
+}
+
+pub async fn submodule_init(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    req: Option<Json<SubmoduleUpdateRequest>>,
+) -> Result<Json<Vec<SubmoduleInfo>>, ApiError> {
+    run_submodule_action(config, repo, req, SubmoduleAction::Init).await
+}
+
+pub async fn submodule_update(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    req: Option<Json<SubmoduleUpdateRequest>>,
+) -> Result<Json<Vec<SubmoduleInfo>>, ApiError> {
+    run_submodule_action(config, repo, req, SubmoduleAction::Update).await
+}
+
+pub async fn submodule_sync(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    req: Option<Json<SubmoduleUpdateRequest>>,
+) -> Result<Json<Vec<SubmoduleInfo>>, ApiError> {
+    run_submodule_action(config, repo, req, SubmoduleAction::Sync).await
+}
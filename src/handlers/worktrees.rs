@@ -0,0 +1,48 @@
+use axum::{
+    extract::{Path, State},
+    response::Json,
+};
+
+use crate::config::Config;
+use crate::handlers::{run_blocking, ApiError};
+use crate::models::{WorktreeAddRequest, WorktreeInfo, WorktreeRemoveRequest};
+use crate::services::worktrees::{self, WorktreeAction};
+
+async fn run_worktree_action(
+    config: Config,
+    repo: String,
+    action: WorktreeAction,
+) -> Result<Json<Vec<WorktreeInfo>>, ApiError> {
+    run_blocking(move || worktrees::run_worktree_action(&config.repos_root, &repo, action)).await
+}
+
+/// List the repository's worktrees (main first)
+pub async fn get_worktrees(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+) -> Result<Json<Vec<WorktreeInfo>>, ApiError> {
+    run_blocking(move || worktrees::get_worktrees(&config.repos_root, &repo)).await
+}
+
+pub async fn add_worktree(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    Json(req): Json<WorktreeAddRequest>,
+) -> Result<Json<Vec<WorktreeInfo>>, ApiError> {
+    run_worktree_action(config, repo, WorktreeAction::Add(req)).await
+}
+
+pub async fn remove_worktree(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+    Json(req): Json<WorktreeRemoveRequest>,
+) -> Result<Json<Vec<WorktreeInfo>>, ApiError> {
+    run_worktree_action(config, repo, WorktreeAction::Remove(req)).await
+}
+
+pub async fn prune_worktrees(
+    State(config): State<Config>,
+    Path(repo): Path<String>,
+) -> Result<Json<Vec<WorktreeInfo>>, ApiError> {
+    run_worktree_action(config, repo, WorktreeAction::Prune).await
+}
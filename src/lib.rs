@@ -2,6 +2,7 @@ pub mod config;
 pub mod git;
 pub mod handlers;
 pub mod models;
+pub mod services;
 pub mod utils;
 
 
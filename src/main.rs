@@ -2,6 +2,7 @@ mod config;
 mod git;
 mod handlers;
 mod models;
+mod services;
 mod utils;
 
 use axum::{
@@ -16,24 +17,39 @@ use tower_http::timeout::TimeoutLayer;
 use std::time::Duration;
 
 use config::Config;
+use git::fetch_scheduler::{run_scheduler, FETCH_SCHEDULER};
 use handlers::branches::{get_branch_ahead_behind, get_branch_creation, get_branches};
 use handlers::browse::browse_projects_root;
+use handlers::clone::{get_clone, post_clone, post_clone_cancel};
 use handlers::commits::{
-    get_commit_metrics, get_commits, get_commits_all_branches, get_commits_between, get_tags,
+    create_tag, get_commit_detail, get_commit_metrics, get_commits, get_commits_all_branches,
+    get_commits_between, get_signing_config, get_tags,
 };
+use handlers::compare::{get_compare_diff, get_compare_files};
 use handlers::conflicts::{get_conflict_file, get_conflicts, resolve_conflict};
 use handlers::diff::get_diff;
+use handlers::discard::{clean, discard_changes, get_discard_snapshots, restore_discarded};
 use handlers::explorer::open_explorer;
 use handlers::files::{
     get_commit_files, get_file, get_file_creation, get_file_creation_batch, get_files, get_image,
 };
 use handlers::rebase::{get_rebase_preview, post_rebase_plan};
 use handlers::repos::{get_config, get_repos};
-use handlers::staging::{commit, get_status, stage, unstage};
-use handlers::fetch::fetch_repo;
+use handlers::hooks::{get_hooks, set_hook_enabled};
+use handlers::patch::{apply_patch, format_patch, get_patch};
+use handlers::staging::{
+    add_ignore_pattern, commit, get_commit_lint_rules, get_status, lint_commit_message, set_commit_lint_rules, stage,
+    unstage,
+};
+use handlers::worktrees::{add_worktree, get_worktrees, prune_worktrees, remove_worktree};
+use handlers::submodules::{get_submodules, submodule_init, submodule_sync, submodule_update};
+use handlers::fetch::{
+    deepen_history, fetch_repo, get_fetch_policy, get_fetch_status, get_shallow_info,
+    set_fetch_policy,
+};
 use handlers::git_ops::{
-    get_branch_status, pull_repo, push_repo, stash_apply, stash_drop, stash_list, stash_pop,
-    stash_push,
+    get_branch_status, pull_repo, push_repo, stash_apply, stash_branch, stash_diff, stash_drop,
+    stash_list, stash_pop, stash_push, stash_show,
 };
 
 #[tokio::main]
@@ -48,6 +64,10 @@ async fn main() {
     let config = Config::init();
     let app_state = config.clone();
 
+    // Background fetch scheduler for every repo under repos_root
+    FETCH_SCHEDULER.set_default_interval(config.fetch_interval_secs);
+    tokio::spawn(run_scheduler(config.repos_root.clone()));
+
     let static_dir = ServeDir::new("./static");
     let static_service = ServiceBuilder::new()
         .layer(SetResponseHeaderLayer::if_not_present(
@@ -60,6 +80,9 @@ async fn main() {
         .route("/api/config", get(get_config))
         .route("/api/browse/projects-root", get(browse_projects_root))
         .route("/api/repos", get(get_repos))
+        .route("/api/clone", post(post_clone))
+        .route("/api/clone/:id", get(get_clone))
+        .route("/api/clone/:id/cancel", post(post_clone_cancel))
         .route("/api/repos/:repo/branches", get(get_branches))
         // Ahead/behind endpoint uses query parameters for both repo and branch
         // to avoid any routing edge cases with slashes in branch names.
@@ -74,8 +97,11 @@ async fn main() {
         .route("/api/repos/:repo/commits-between", get(get_commits_between))
         .route("/api/repos/:repo/commits/metrics", get(get_commit_metrics))
         .route("/api/repos/:repo/tags", get(get_tags))
+        .route("/api/repos/:repo/tags/create", post(create_tag))
+        .route("/api/repos/:repo/signing", get(get_signing_config))
         .route("/api/repos/:repo/files", get(get_files))
         .route("/api/repos/:repo/commit/files", get(get_commit_files))
+        .route("/api/repos/:repo/commit/detail", get(get_commit_detail))
         .route("/api/repos/:repo/file", get(get_file))
         .route("/api/repos/:repo/file-creation", get(get_file_creation))
         .route(
@@ -84,11 +110,35 @@ async fn main() {
         )
         .route("/api/repos/:repo/image", get(get_image))
         .route("/api/repos/:repo/diff", get(get_diff))
+        .route("/api/repos/:repo/compare/files", get(get_compare_files))
+        .route("/api/repos/:repo/compare/diff", get(get_compare_diff))
+        .route("/api/repos/:repo/patch", get(get_patch))
+        .route("/api/repos/:repo/patch/export", post(format_patch))
+        .route("/api/repos/:repo/patch/apply", post(apply_patch))
         .route("/api/repos/:repo/status", get(get_status))
+        .route("/api/repos/:repo/ignore", post(add_ignore_pattern))
         .route("/api/repos/:repo/stage", post(stage))
         .route("/api/repos/:repo/unstage", post(unstage))
+        .route("/api/repos/:repo/discard", post(discard_changes))
+        .route("/api/repos/:repo/discard/snapshots", get(get_discard_snapshots))
+        .route("/api/repos/:repo/discard/restore", post(restore_discarded))
+        .route("/api/repos/:repo/clean", post(clean))
         .route("/api/repos/:repo/commit", post(commit))
+        .route("/api/repos/:repo/commit/lint", post(lint_commit_message))
+        .route(
+            "/api/repos/:repo/commit/rules",
+            get(get_commit_lint_rules).post(set_commit_lint_rules),
+        )
+        .route("/api/repos/:repo/hooks", get(get_hooks))
+        .route("/api/repos/:repo/hooks/toggle", post(set_hook_enabled))
         .route("/api/repos/:repo/fetch", post(fetch_repo))
+        .route(
+            "/api/repos/:repo/fetch/policy",
+            get(get_fetch_policy).post(set_fetch_policy),
+        )
+        .route("/api/fetch/status", get(get_fetch_status))
+        .route("/api/repos/:repo/shallow", get(get_shallow_info))
+        .route("/api/repos/:repo/shallow/deepen", post(deepen_history))
         .route("/api/repos/:repo/pull", post(pull_repo))
         .route("/api/repos/:repo/push", post(push_repo))
         .route("/api/repos/:repo/branch-status", get(get_branch_status))
@@ -97,6 +147,17 @@ async fn main() {
         .route("/api/repos/:repo/stash/pop", post(stash_pop))
         .route("/api/repos/:repo/stash/apply", post(stash_apply))
         .route("/api/repos/:repo/stash/drop", post(stash_drop))
+        .route("/api/repos/:repo/stash/branch", post(stash_branch))
+        .route("/api/repos/:repo/stash/show", get(stash_show))
+        .route("/api/repos/:repo/stash/diff", get(stash_diff))
+        .route("/api/repos/:repo/submodules", get(get_submodules))
+        .route("/api/repos/:repo/submodules/init", post(submodule_init))
+        .route("/api/repos/:repo/submodules/update", post(submodule_update))
+        .route("/api/repos/:repo/submodules/sync", post(submodule_sync))
+        .route("/api/repos/:repo/worktrees", get(get_worktrees))
+        .route("/api/repos/:repo/worktrees/add", post(add_worktree))
+        .route("/api/repos/:repo/worktrees/remove", post(remove_worktree))
+        .route("/api/repos/:repo/worktrees/prune", post(prune_worktrees))
         .route("/api/repos/:repo/rebase/preview", get(get_rebase_preview))
         .route("/api/repos/:repo/rebase/plan", post(post_rebase_plan))
         .route("/api/repos/:repo/conflicts", get(get_conflicts))
@@ -1,10 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Repo {
     pub id: String,
     pub name: String,
+    /// For a linked worktree, the id of the repository's main working tree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worktree_of: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -15,6 +18,9 @@ pub struct BranchMetadata {
     pub is_unborn: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_commit_date: Option<String>,
+    /// Path of another worktree that has this branch checked out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checked_out_in: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -53,6 +59,9 @@ pub struct BranchCreationInfo {
     pub commit_message: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_root_commit: Option<bool>,
+    /// The commit is where a shallow clone's history stops, not the real root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_shallow_boundary: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
 }
@@ -84,6 +93,24 @@ pub struct Commit {
     pub branch_base: Option<String>, // Base branch name (main, develop, etc.)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub branch_divergence_age_days: Option<f64>, // Days since divergence
+    /// Set on commits at the edge of a shallow clone: parents exist but weren't fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_shallow_boundary: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureInfo>,
+}
+
+/// GPG/SSH/X.509 signature of a commit, as verified by `git`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureInfo {
+    pub status: String, // "good", "bad", "unknownKey", "expired", "revoked", "unsigned", "unverified"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>, // "openpgp", "ssh", "x509"
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -111,7 +138,15 @@ pub struct CommitsResponse {
 #[serde(rename_all = "camelCase")]
 pub struct FileChange {
     pub path: String,
-    pub status: String, // added, modified, removed
+    /// Source of a rename or copy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    pub status: String, // added, modified, removed, renamed, copied
+    /// Rename or copy similarity in percent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<u8>,
+    #[serde(default)]
+    pub is_submodule: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -123,6 +158,166 @@ pub struct DiffHunk {
     pub new_count: i32,
     pub lines: Vec<String>,
     pub line_start: i32,
+    /// Word-level changes within paired removed/added lines
+    #[serde(default)]
+    pub spans: Vec<DiffSpan>,
+    /// Set when a long hunk is split across pages: `lines[i]` (after the
+    /// header of this part) is line `i + lineOffset` of the whole hunk
+    #[serde(default)]
+    pub line_offset: usize,
+}
+
+/// Changed characters `start..end` (0-based, end exclusive) of `lines[line]`,
+/// counted after the `-`/`+` prefix
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffSpan {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// How a file diff is computed. Values are strings as they arrive in query
+/// strings, but JSON bodies may also send numbers and booleans; unset fields
+/// keep the defaults (3 context lines, Myers, whitespace
+/// significant). Stage, unstage and discard requests take them too, as the
+/// settings of the diff their hunk and line selections refer to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffSettings {
+    /// Context lines around each change, or "full" for the whole file
+    #[serde(
+        default,
+        deserialize_with = "string_or_scalar",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub context: Option<String>,
+    /// "ignore-all-space", "ignore-space-change" or "ignore-space-at-eol"
+    #[serde(
+        default,
+        deserialize_with = "string_or_scalar",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub whitespace: Option<String>,
+    /// "true" to ignore changes whose lines are all blank
+    #[serde(
+        default,
+        deserialize_with = "string_or_scalar",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ignore_blank_lines: Option<String>,
+    /// "myers" (default), "minimal", "patience" or "histogram"
+    #[serde(
+        default,
+        deserialize_with = "string_or_scalar",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub algorithm: Option<String>,
+    /// "true" to shift hunk boundaries to match indentation, as git does by default
+    #[serde(
+        default,
+        deserialize_with = "string_or_scalar",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub indent_heuristic: Option<String>,
+    /// Bytes above which a file is reported by size only, or "0" for no limit;
+    /// defaults to `gitpow.diffMaxFileSize`
+    #[serde(
+        default,
+        deserialize_with = "string_or_scalar",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_file_size: Option<String>,
+    /// Diff lines returned per page of hunks, or "0" for no limit; defaults to
+    /// `gitpow.diffMaxLines`
+    #[serde(
+        default,
+        deserialize_with = "string_or_scalar",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_lines: Option<String>,
+    /// First hunk of the page, as given by `nextHunk` of the previous one
+    #[serde(
+        default,
+        deserialize_with = "string_or_scalar",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hunk_offset: Option<String>,
+    /// Line of that hunk to start at, as given by `nextLine` when the previous
+    /// page split it
+    #[serde(
+        default,
+        deserialize_with = "string_or_scalar",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hunk_line: Option<String>,
+}
+
+/// Read a query-string style setting that a JSON body may send as a number or
+/// boolean instead of a string
+fn string_or_scalar<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct Visitor;
+
+    impl<'de> serde::de::Visitor<'de> for Visitor {
+        type Value = Option<String>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a string, number or boolean")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            Ok(Some(v.to_string()))
+        }
+
+        fn visit_bool<E: serde::de::Error>(self, v: bool) -> Result<Self::Value, E> {
+            Ok(Some(v.to_string()))
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            Ok(Some(v.to_string()))
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok(Some(v.to_string()))
+        }
+
+        fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Self::Value, E> {
+            Ok(Some(v.to_string()))
+        }
+
+        fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: serde::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+            d.deserialize_any(self)
+        }
+    }
+
+    deserializer.deserialize_any(Visitor)
+}
+
+/// Lines picked from one hunk of a file diff for partial stage, unstage or discard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HunkSelection {
+    /// Index of the hunk in the diff shown to the user
+    pub hunk: usize,
+    /// Indices into the hunk's `lines`, where 0 is the `@@` header (the whole
+    /// hunk when absent). Indices past the end of the hunk are rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lines: Option<Vec<usize>>,
+    /// `lineOffset` of the page the lines were picked from when the hunk was
+    /// split across pages; `lines` then index that page's part of the hunk
+    #[serde(default)]
+    pub line_offset: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -131,18 +326,103 @@ pub struct DiffResponse {
     pub diff: String,
     pub hunks: Vec<DiffHunk>,
     pub file_path: String,
+    /// Set when the path is a submodule; the change is a commit range, not a blob diff
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submodule: Option<SubmoduleChange>,
+    /// Parent count of a combined merge diff; its lines then start with one
+    /// `+`/`-`/space column per parent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub combined_parents: Option<usize>,
+    /// Set instead of hunks when the file is binary or too large to diff
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary: Option<BinaryDiff>,
+    /// Hunks in the whole diff; `hunks` holds one page of them
+    #[serde(default)]
+    pub total_hunks: usize,
+    /// Hunks or lines past the line limit were left out
+    #[serde(default)]
+    pub truncated: bool,
+    /// First hunk of the next page, when there is one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_hunk: Option<usize>,
+    /// Line of `nextHunk` the next page starts at, when this page split it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_line: Option<usize>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A file diffed by size only: binary content, `-diff` in `.gitattributes`, or
+/// larger than the size limit. A size is absent when the file does not exist
+/// on that side or git did not report it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryDiff {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_size: Option<u64>,
+    /// Over the size limit, whatever the content
+    #[serde(default)]
+    pub too_large: bool,
+}
+
+/// One changed path, as reported by `git status` and shared by the status,
+/// conflict and stash responses
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct StatusFile {
     pub path: String,
+    /// Source of a rename or copy
     #[serde(skip_serializing_if = "Option::is_none")]
     pub old_path: Option<String>,
+    /// Short `XY` code as in `git status --porcelain`
     pub status: String,
     pub staged: bool,
     pub unstaged: bool,
-    pub r#type: String, // modified, added, deleted, untracked, renamed
+    pub r#type: String, // modified, added, deleted, untracked, renamed, copied, conflicted
+    /// HEAD -> index change: "added", "modified", "deleted", "renamed", "copied",
+    /// "typechange" or "unmerged"; absent when the index matches HEAD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_status: Option<String>,
+    /// Index -> working tree change, with the same values plus "untracked"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_status: Option<String>,
+    /// Rename or copy similarity in percent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<u8>,
+    #[serde(default)]
+    pub is_submodule: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submodule: Option<SubmoduleState>,
+    /// Unmerged entry: "both-modified", "both-added", "both-deleted",
+    /// "added-by-us", "added-by-them", "deleted-by-us" or "deleted-by-them"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<String>,
+    /// Rule that ignores an ignored path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_rule: Option<IgnoreRule>,
+}
+
+/// An ignore pattern and where it is defined, as `git check-ignore -v` reports it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnoreRule {
+    /// File the pattern is in: a `.gitignore`, `.git/info/exclude` or `core.excludesFile`
+    pub source: String,
+    /// 1-based line of the pattern in `source`
+    pub line: usize,
+    pub pattern: String,
+}
+
+/// How a submodule differs from what the superproject records
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmoduleState {
+    /// A different commit is checked out
+    pub commit_changed: bool,
+    /// Tracked files inside the submodule are modified
+    pub modified: bool,
+    /// The submodule has untracked files
+    pub untracked: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -244,17 +524,11 @@ pub struct RebasePlanResponse {
     pub error: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct ConflictFile {
-    pub path: String,
-    pub r#type: String,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConflictsResponse {
-    pub files: Vec<ConflictFile>,
+    /// Unmerged paths; `conflict` tells which sides are present
+    pub files: Vec<StatusFile>,
     pub has_conflicts: bool,
 }
 
@@ -296,6 +570,12 @@ pub struct BrowseFolderResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
+    /// Hooks run by the failed operation, when a hook made it fail
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<HookRun>,
+    /// Rules the commit message broke, when they made a commit fail
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lint: Option<CommitLintResult>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -316,6 +596,9 @@ pub struct GitOperationResponse {
     pub output: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Hooks the operation ran (push)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<HookRun>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -334,3 +617,718 @@ pub struct BranchStatusResponse {
 pub struct StashListResponse {
     pub entries: Vec<StashEntry>,
 }
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct StashPushRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Also stash untracked files (`--include-untracked`)
+    #[serde(default)]
+    pub include_untracked: bool,
+    /// Also stash untracked and ignored files (`--all`)
+    #[serde(default)]
+    pub all: bool,
+    /// Leave staged changes in the index (`--keep-index`)
+    #[serde(default)]
+    pub keep_index: bool,
+    /// Stash only the staged changes (`--staged`)
+    #[serde(default)]
+    pub staged: bool,
+    /// Limit the stash to these paths (everything when empty)
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StashBranchRequest {
+    pub branch: String,
+    /// Stash to restore (defaults to stash@{0})
+    #[serde(default, rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub stash_ref: Option<String>,
+}
+
+/// Result of applying, popping or branching from a stash
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StashApplyResponse {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Paths left conflicted in the working tree; the stash is kept when this is non-empty
+    pub conflicts: Vec<StatusFile>,
+    /// Whether the stash entry was removed (pop / branch)
+    pub dropped: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StashFileChange {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    pub status: String, // "added", "modified", "removed", "renamed", "copied"
+    pub additions: usize,
+    pub deletions: usize,
+    pub is_binary: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StashDetail {
+    #[serde(rename = "ref")]
+    pub stash_ref: String,
+    pub sha: String,
+    pub message: String,
+    pub date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub base_commit: String,
+    pub base_message: String,
+    pub has_untracked: bool,
+    /// All tracked changes (base -> stash), as shown by `git stash show`
+    pub files: Vec<StashFileChange>,
+    /// Changes that were staged when the stash was created
+    pub index_files: Vec<StashFileChange>,
+    /// Changes that were unstaged when the stash was created
+    pub worktree_files: Vec<StashFileChange>,
+    /// Untracked files saved with `--include-untracked` / `--all`
+    pub untracked_files: Vec<StashFileChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchPolicy {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// True when the repo has no explicit setting and follows the app-wide default
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoFetchStatus {
+    pub id: String,
+    pub name: String,
+    pub policy: FetchPolicy,
+    pub in_progress: bool,
+    pub consecutive_failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_fetch: Option<String>, // RFC3339 start time of the last fetch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_duration_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_result: Option<String>, // success, auth-error, network-error, error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_run: Option<String>, // RFC3339, absent when background fetch is off
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchPolicyRequest {
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneRequest {
+    pub url: String,
+    /// Folder name to clone into, directly under the repos root
+    pub directory: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repos_root: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub single_branch: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneStatus {
+    pub id: String,
+    pub url: String,
+    pub path: String,
+    pub state: String, // running, succeeded, failed, cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>, // e.g. "Receiving objects", "Resolving deltas"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo: Option<Repo>, // set once the clone succeeded
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmoduleInfo {
+    pub name: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recorded_commit: Option<String>, // commit recorded in HEAD
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_commit: Option<String>, // commit recorded in the index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checked_out_commit: Option<String>, // HEAD of the submodule's working tree
+    pub initialized: bool,  // registered in .git/config
+    pub checked_out: bool,  // working tree is populated
+    pub out_of_date: bool,  // checked-out commit differs from the recorded one
+    pub dirty: bool,        // modified or untracked files inside the submodule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo_id: Option<String>, // open the submodule as its own repo with this id
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmoduleCommit {
+    pub sha: String,
+    pub message: String,
+    pub direction: String, // added (only in new), removed (only in old, i.e. rewound)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmoduleChange {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_commit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_commit: Option<String>,
+    pub commits: Vec<SubmoduleCommit>,
+    /// False when the submodule isn't checked out or lacks the commits, so no log is available
+    pub commits_available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmoduleUpdateRequest {
+    /// Limit the operation to these submodule paths (all submodules when empty)
+    #[serde(default)]
+    pub paths: Vec<String>,
+    #[serde(default)]
+    pub init: bool,
+    #[serde(default)]
+    pub recursive: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeInfo {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub detached: bool,
+    pub bare: bool,
+    pub is_main: bool,
+    pub is_current: bool,
+    pub locked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_reason: Option<String>,
+    pub prunable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prune_reason: Option<String>,
+    /// Open the worktree as its own repo with this id
+    pub repo_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeAddRequest {
+    /// Absolute path, or relative to the folder containing the repository
+    pub path: String,
+    /// Existing branch to check out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Commit-ish to check out (detached unless `new_branch` is set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Create this branch at `commit` (or HEAD) and check it out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_branch: Option<String>,
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeRemoveRequest {
+    pub path: String,
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShallowInfo {
+    pub is_shallow: bool,
+    pub boundaries: Vec<String>, // SHAs whose parents were not fetched
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShallowFetchRequest {
+    /// Fetch this many more commits beyond the current boundary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deepen: Option<u32>,
+    /// Fetch history back to this date (anything `git fetch --shallow-since` accepts)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(default)]
+    pub unshallow: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscardRequest {
+    /// Files or directories whose working tree changes are thrown away.
+    /// Tracked files go back to their staged content, untracked ones are deleted.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Only these hunks of the single path in `paths`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hunks: Option<Vec<usize>>,
+    /// Only these lines of the single path in `paths`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lines: Option<Vec<HunkSelection>>,
+    /// Report what would be discarded without touching anything
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Go ahead even when some files cannot be saved to a snapshot first
+    #[serde(default)]
+    pub force: bool,
+    /// Also delete untracked nested repositories (directories with their own
+    /// `.git`), like `git clean -ff`
+    #[serde(default)]
+    pub remove_nested_repos: bool,
+    /// Settings of the diff `hunks` and `lines` refer to
+    #[serde(flatten)]
+    pub options: DiffSettings,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanRequest {
+    /// Limit the clean to these paths (the whole repository when empty)
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Also remove ignored files (`git clean -x`)
+    #[serde(default)]
+    pub ignored: bool,
+    /// Remove only ignored files (`git clean -X`)
+    #[serde(default)]
+    pub only_ignored: bool,
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Go ahead even when some files cannot be saved to a snapshot first
+    #[serde(default)]
+    pub force: bool,
+    /// Also delete untracked nested repositories (directories with their own
+    /// `.git`), like `git clean -ff`
+    #[serde(default)]
+    pub remove_nested_repos: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscardedFile {
+    pub path: String,
+    pub action: String, // "reverted", "deleted", "rewritten", "restored"
+    /// Whether the content was saved to a snapshot before being touched
+    pub saved: bool,
+    /// The content cannot be saved (too large, or not a file or symlink) and
+    /// is gone for good once discarded
+    #[serde(default)]
+    pub lost: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscardResponse {
+    pub dry_run: bool,
+    pub files: Vec<DiscardedFile>,
+    /// Snapshot holding the previous content, for restore
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<String>,
+}
+
+/// Working tree content saved before a discard, clean or restore
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscardSnapshot {
+    pub id: String,
+    pub message: String,
+    pub date: String,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscardRestoreRequest {
+    /// Snapshot id (the latest snapshot when absent)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<String>,
+    /// Restore only these files (all files in the snapshot when empty)
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Overwrite files even when their current content cannot be saved first
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitRequest {
+    pub message: String,
+    /// Add a `Signed-off-by` trailer (`--signoff`)
+    #[serde(default)]
+    pub sign_off: bool,
+    /// Author as "Name <email>" (`--author`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Author date, in any format `git commit --date` accepts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(default)]
+    pub allow_empty: bool,
+    /// Skip the pre-commit and commit-msg hooks (`--no-verify`); the
+    /// repository's commit message rules still apply
+    #[serde(default)]
+    pub no_verify: bool,
+    /// Skip the repository's commit message rules (`gitpow.lint.*`), including
+    /// the WIP markers forbidden on protected branches
+    #[serde(default)]
+    pub skip_lint: bool,
+    /// Commit only these paths as they are in the working tree (`--only`);
+    /// other staged changes stay staged
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Force signing on (`-S`) or off (`--no-gpg-sign`); `commit.gpgsign` decides when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sign: Option<bool>,
+}
+
+/// The commit just created
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitResult {
+    pub sha: String,
+    pub short_sha: String,
+    pub summary: String,
+    pub author: String,
+    pub email: String,
+    pub date: String,
+    pub signature: SignatureInfo,
+    /// Hooks that ran for this commit, with their output
+    pub hooks: Vec<HookRun>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCreateRequest {
+    pub name: String,
+    /// Commit-ish to tag (HEAD when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Makes the tag annotated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Force signing on (`-s`) or off (`--no-sign`); `tag.gpgSign` decides when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sign: Option<bool>,
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Effective signing configuration of a repository
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningConfig {
+    /// `commit.gpgsign`
+    pub sign_commits: bool,
+    /// `tag.gpgSign`
+    pub sign_tags: bool,
+    /// `gpg.format`: "openpgp", "ssh" or "x509"
+    pub format: String,
+    /// `user.signingkey`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key: Option<String>,
+}
+
+/// Per-repo commit message rules, stored in the repository's git config
+/// under `gitpow.lint.*`. Every rule is off until configured.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitLintRules {
+    /// Subject must read `type(scope)!: description` (`gitpow.lint.conventional`)
+    #[serde(default)]
+    pub conventional: bool,
+    /// Allowed conventional types; empty allows any (`gitpow.lint.types`)
+    #[serde(default)]
+    pub types: Vec<String>,
+    /// Allowed conventional scopes; empty allows any (`gitpow.lint.scopes`)
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// `gitpow.lint.requireScope`
+    #[serde(default)]
+    pub require_scope: bool,
+    /// Longest allowed subject in characters, 0 for no limit (`gitpow.lint.subjectMaxLength`)
+    #[serde(default)]
+    pub subject_max_length: usize,
+    /// Second line must be empty when there is a body (`gitpow.lint.blankSecondLine`)
+    #[serde(default)]
+    pub blank_second_line: bool,
+    /// Trailer keys every message needs, e.g. "Signed-off-by" (`gitpow.lint.requiredTrailers`)
+    #[serde(default)]
+    pub required_trailers: Vec<String>,
+    /// Regex a trailer value must match, e.g. `[A-Z]+-\d+` for ticket keys
+    /// (`gitpow.lint.ticketPattern`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticket_pattern: Option<String>,
+    /// Subject markers refused on protected branches (`gitpow.lint.wipMarkers`)
+    #[serde(default)]
+    pub wip_markers: Vec<String>,
+    /// Branch names or `*` globs that refuse WIP markers (`gitpow.lint.protectedBranches`)
+    #[serde(default)]
+    pub protected_branches: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitLintRequest {
+    pub message: String,
+    /// Branch the commit goes to (the current branch when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// A `Signed-off-by` trailer will be added on commit
+    #[serde(default)]
+    pub sign_off: bool,
+}
+
+/// One broken rule. `line` is 1-based; `start`..`end` are 0-based character
+/// columns in that line, so the UI can underline the offending text.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LintViolation {
+    pub rule: String,
+    pub message: String,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitLintResult {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub violations: Vec<LintViolation>,
+}
+
+/// One hook run by a git command, with its captured output
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HookRun {
+    /// Hook name, e.g. "pre-commit"
+    pub hook: String,
+    pub success: bool,
+    /// `None` when the hook was killed by a signal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookInfo {
+    pub name: String,
+    pub path: String,
+    /// Disabled hooks are renamed to `<name>.disabled` (or not executable)
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookListResponse {
+    /// `core.hooksPath`, or the repository's `hooks` folder
+    pub hooks_path: String,
+    pub hooks: Vec<HookInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookToggleRequest {
+    pub name: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnorePatternRequest {
+    pub pattern: String,
+    /// `.gitignore` path relative to the working tree, or "info/exclude";
+    /// the top-level `.gitignore` when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Only report which files the pattern would hide
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnorePatternResponse {
+    pub dry_run: bool,
+    pub file: String,
+    pub pattern: String,
+    /// Currently untracked files the pattern hides
+    pub hidden: Vec<String>,
+}
+
+/// Lines added and removed in one file of a patch
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchFileStat {
+    pub path: String,
+    /// Source of a rename or copy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    pub additions: usize,
+    pub deletions: usize,
+    pub binary: bool,
+}
+
+/// Every file of a commit (against its first parent) or of a range, as one patch
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchResponse {
+    /// Old side of the diff; absent for a root commit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    pub to: String,
+    pub files: Vec<PatchFileStat>,
+    pub additions: usize,
+    pub deletions: usize,
+    /// `--stat`/`--summary` header followed by the unified diff of all files
+    pub patch: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatPatchRequest {
+    /// A commit (exported alone) or a range such as `main..topic`
+    pub range: String,
+    #[serde(default)]
+    pub cover_letter: bool,
+    /// One mbox with the whole series instead of one file per patch
+    #[serde(default)]
+    pub mbox: bool,
+    /// Replaces "PATCH" in the subject lines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject_prefix: Option<String>,
+}
+
+/// One file written by `git format-patch`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedPatch {
+    pub file_name: String,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatPatchResponse {
+    /// Cover letter first when requested, then the patches in order
+    pub files: Vec<ExportedPatch>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchApplyRequest {
+    /// A unified diff, or an mbox as written by `git format-patch`
+    pub patch: String,
+    /// "worktree" (default), "index", or "commits" to create one commit per
+    /// mbox message as `git am` does
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Fall back to a 3-way merge for hunks that do not apply; on the working
+    /// tree this records the merged files in the index too
+    #[serde(default)]
+    pub three_way: bool,
+}
+
+/// Whether one hunk of a patch applies to the target as it is
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchHunkResult {
+    /// The hunk's `@@` header
+    pub header: String,
+    pub applies: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchApplyFile {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    /// "applied", "rejected" (left untouched), "merged" (3-way, cleanly) or
+    /// "conflicted" (3-way, with conflict markers)
+    pub status: String,
+    pub hunks: Vec<PatchHunkResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchApplyResponse {
+    pub applied: bool,
+    pub target: String,
+    /// For "commits", the files of the message that failed to apply
+    pub files: Vec<PatchApplyFile>,
+    /// Commits created for target "commits", oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commits: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<HookRun>,
+}
+
+/// Files that differ between two revisions, the index or the working tree
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareFilesResponse {
+    /// Resolved `from`: a commit (or tree) id, INDEX or WORKTREE
+    pub from: String,
+    pub to: String,
+    pub files: Vec<FileChange>,
+}
@@ -0,0 +1,319 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::git::fetch_scheduler::{FetchTrigger, FETCH_SCHEDULER};
+use crate::git::repository::GitRepository;
+use crate::models::{BranchAheadBehind, BranchCreationInfo, BranchInfo, BranchStatusResponse};
+use crate::services::{internal, open_repo, ServiceResult};
+use crate::utils::get_repo_path;
+
+/// Local and cached remote branches. With `auto_fetch` a background fetch is
+/// requested from the scheduler, which applies the repo's fetch policy, cooldown
+/// and failure backoff so frequent polling cannot cause fetch storms.
+pub fn get_branches(repos_root: &Path, repo: &str, auto_fetch: bool) -> ServiceResult<BranchInfo> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let branch_info = git_repo
+        .get_branch_info()
+        .map_err(|e| internal("Failed to get branches", e))?;
+
+    // The UI renders the immediately available branch info while the fetch
+    // happens in the background.
+    if auto_fetch {
+        let repo_path = get_repo_path(repo, repos_root);
+        std::thread::spawn(move || {
+            FETCH_SCHEDULER.fetch(&repo_path, FetchTrigger::OnDemand);
+        });
+    }
+
+    Ok(branch_info)
+}
+
+/// Resolve the tip of a local branch, then a remote branch, then any revision
+fn resolve_branch_tip(git_repo: &GitRepository, branch: &str) -> (Option<String>, bool) {
+    // Try local branch first (single lookup, reuse result for both is_local and branch_sha)
+    let local_branch = git_repo.repo.find_branch(branch, git2::BranchType::Local);
+    let is_local = local_branch.is_ok();
+
+    let branch_sha = if let Ok(local_branch) = local_branch {
+        local_branch
+            .get()
+            .peel_to_commit()
+            .ok()
+            .map(|c| c.id().to_string())
+    } else if let Ok(remote_branch) = git_repo
+        .repo
+        .find_branch(branch, git2::BranchType::Remote)
+    {
+        remote_branch
+            .get()
+            .peel_to_commit()
+            .ok()
+            .map(|c| c.id().to_string())
+    } else {
+        git_repo.rev_parse(branch).ok()
+    };
+
+    (branch_sha, is_local)
+}
+
+/// Ahead/behind counts of a branch against its upstream (or main). Resolution
+/// failures degrade to zero counts instead of an error, since this feeds
+/// UX-critical hover tooltips.
+pub fn branch_ahead_behind(
+    repos_root: &Path,
+    repo: &str,
+    branch: &str,
+) -> ServiceResult<BranchAheadBehind> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let (branch_sha, is_local) = resolve_branch_tip(&git_repo, branch);
+
+    let branch_sha = match branch_sha {
+        Some(sha) => sha,
+        None => {
+            return Ok(BranchAheadBehind {
+                ahead: 0,
+                behind: 0,
+                upstream: "main".to_string(),
+                is_local,
+            })
+        }
+    };
+
+    let upstream_name = if is_local {
+        git_repo
+            .get_upstream(branch)
+            .unwrap_or_default()
+            .unwrap_or_else(|| "main".to_string())
+    } else {
+        "main".to_string()
+    };
+
+    let upstream_sha = match git_repo.rev_parse(&upstream_name) {
+        Ok(sha) => sha,
+        Err(_) => {
+            return Ok(BranchAheadBehind {
+                ahead: 0,
+                behind: 0,
+                upstream: upstream_name,
+                is_local,
+            })
+        }
+    };
+
+    let (ahead, behind) = git_repo
+        .ahead_behind(&branch_sha, &upstream_sha)
+        .unwrap_or((0, 0));
+
+    Ok(BranchAheadBehind {
+        ahead: ahead as i32,
+        behind: behind as i32,
+        upstream: upstream_name,
+        is_local,
+    })
+}
+
+/// Current branch with ahead/behind against its upstream, uncommitted changes
+/// and stash count, for the git operations toolbar
+pub fn branch_status(repos_root: &Path, repo: &str) -> ServiceResult<BranchStatusResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+
+    let branch = git_repo.get_current_branch().unwrap_or_else(|_| "HEAD".to_string());
+    let has_upstream = git_repo.has_upstream().unwrap_or(false);
+    let (ahead, behind) = if has_upstream {
+        git_repo.get_ahead_behind_upstream().unwrap_or((0, 0))
+    } else {
+        (0, 0)
+    };
+    let has_uncommitted = git_repo.has_uncommitted_changes().unwrap_or(false);
+    let stash_count = git_repo.stash_list().map(|l| l.len()).unwrap_or(0);
+
+    Ok(BranchStatusResponse {
+        branch,
+        has_upstream,
+        ahead,
+        behind,
+        has_uncommitted,
+        stash_count,
+    })
+}
+
+fn not_found(error: String) -> BranchCreationInfo {
+    BranchCreationInfo {
+        found: false,
+        commit_sha: None,
+        commit_date: None,
+        commit_message: None,
+        is_root_commit: None,
+        is_shallow_boundary: None,
+        error: Some(error),
+    }
+}
+
+/// Describe the commit a branch was created at. Details that cannot be read
+/// are reported in `error` while still returning the SHA.
+fn creation_commit(
+    git_repo: &GitRepository,
+    sha: &str,
+    is_root_commit: bool,
+    at_boundary: bool,
+) -> BranchCreationInfo {
+    let details_result = git_repo.run_git(&["log", "-1", "--format=%H%x1f%aI%x1f%s", sha]);
+    let is_shallow_boundary = at_boundary.then_some(true);
+
+    match details_result {
+        Ok(details) => {
+            let parts: Vec<&str> = details.trim().split('\x1f').collect();
+            BranchCreationInfo {
+                found: true,
+                commit_sha: parts.first().map(|s| s.to_string()),
+                commit_date: parts.get(1).map(|s| s.to_string()),
+                commit_message: parts.get(2).map(|s| s.to_string()),
+                is_root_commit: Some(is_root_commit),
+                is_shallow_boundary,
+                error: None,
+            }
+        }
+        Err(e) => BranchCreationInfo {
+            found: true,
+            commit_sha: Some(sha.to_string()),
+            commit_date: None,
+            commit_message: None,
+            is_root_commit: Some(is_root_commit),
+            is_shallow_boundary,
+            error: Some(format!("Failed to get commit details: {}", e)),
+        },
+    }
+}
+
+/// Pick the root commit from `rev-list --max-parents=0` output. Real roots are
+/// preferred over shallow boundaries, which rev-list also reports as parentless.
+fn pick_root<'a>(output: &'a str, shallow: &HashSet<String>) -> (&'a str, bool) {
+    let roots: Vec<&str> = output
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .collect();
+    let root_sha = roots
+        .iter()
+        .find(|sha| !shallow.contains(**sha))
+        .or(roots.first())
+        .copied()
+        .unwrap_or("");
+    (root_sha, shallow.contains(root_sha))
+}
+
+/// Get the creation info for a branch.
+/// For main-like branches, returns the repository's root commit.
+/// For feature branches, returns the first commit unique to that branch (merge-base with main).
+pub fn branch_creation(
+    repos_root: &Path,
+    repo: &str,
+    branch: &str,
+) -> ServiceResult<BranchCreationInfo> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let shallow = git_repo.shallow_boundaries();
+
+    if is_main_like_branch(branch) {
+        // For main branches, get the root commit (first commit in the repository)
+        return Ok(
+            match git_repo.run_git(&["rev-list", "--max-parents=0", "HEAD"]) {
+                Ok(output) => {
+                    let (root_sha, at_boundary) = pick_root(&output, &shallow);
+                    if root_sha.is_empty() {
+                        not_found("No root commit found".to_string())
+                    } else {
+                        creation_commit(&git_repo, root_sha, !at_boundary, at_boundary)
+                    }
+                }
+                Err(e) => not_found(format!("Failed to find root commit: {}", e)),
+            },
+        );
+    }
+
+    // For feature branches, find the merge-base with main/master
+    // This is the point where the branch diverged from the main line
+    let main_ref = find_main_ref(&git_repo);
+
+    match git_repo.run_git(&["merge-base", branch, &main_ref]) {
+        Ok(merge_base) => {
+            let merge_base_sha = merge_base.trim();
+            if merge_base_sha.is_empty() {
+                return Ok(not_found("No merge-base found".to_string()));
+            }
+
+            // Find the first commit on this branch after the merge-base
+            // This is the actual "creation" commit of the branch
+            let first_commit_result = git_repo.run_git(&[
+                "rev-list",
+                "--ancestry-path",
+                &format!("{}..{}", merge_base_sha, branch),
+                "--reverse",
+            ]);
+
+            let creation_sha = match first_commit_result {
+                Ok(output) => {
+                    let first_line = output.lines().next().unwrap_or("").trim();
+                    if first_line.is_empty() {
+                        // Branch hasn't diverged yet, use merge-base
+                        merge_base_sha.to_string()
+                    } else {
+                        first_line.to_string()
+                    }
+                }
+                Err(_) => merge_base_sha.to_string(),
+            };
+
+            Ok(creation_commit(&git_repo, &creation_sha, false, false))
+        }
+        Err(e) => {
+            // No merge-base found - this might be an orphan branch or unrelated history
+            // Fall back to finding the oldest commit on this branch
+            let no_merge_base = format!("No merge-base with {}: {}", main_ref, e);
+            Ok(
+                match git_repo.run_git(&["rev-list", "--max-parents=0", branch]) {
+                    Ok(output) => {
+                        let (oldest_sha, at_boundary) = pick_root(&output, &shallow);
+                        if oldest_sha.is_empty() {
+                            not_found(no_merge_base)
+                        } else {
+                            creation_commit(&git_repo, oldest_sha, !at_boundary, at_boundary)
+                        }
+                    }
+                    Err(_) => not_found(no_merge_base),
+                },
+            )
+        }
+    }
+}
+
+/// Check if a branch name is a main-like branch (main, master, develop, etc.)
+fn is_main_like_branch(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    let base_name = lower.split('/').last().unwrap_or(&lower);
+    matches!(
+        base_name,
+        "main" | "master" | "develop" | "development" | "trunk"
+    )
+}
+
+/// Find the main reference in the repository (main, master, origin/main, etc.)
+fn find_main_ref(git_repo: &GitRepository) -> String {
+    // Try common main branch names in order of preference
+    let candidates = [
+        "main",
+        "master",
+        "origin/main",
+        "origin/master",
+        "develop",
+        "origin/develop",
+    ];
+
+    for candidate in candidates {
+        if git_repo.rev_parse(candidate).is_ok() {
+            return candidate.to_string();
+        }
+    }
+
+    // Default to main if nothing found
+    "main".to_string()
+}
@@ -0,0 +1,25 @@
+use std::path::Path;
+
+use crate::git::clone;
+use crate::models::{CloneRequest, CloneStatus};
+use crate::services::{resolve_root, ServiceError, ServiceResult};
+
+fn clone_not_found(id: &str) -> ServiceError {
+    ServiceError::NotFound(format!("Clone job not found: {}", id))
+}
+
+/// Start cloning a repository into repos_root (or the request's own root).
+/// Poll the returned job for progress.
+pub fn start_clone(repos_root: &Path, req: &CloneRequest) -> ServiceResult<CloneStatus> {
+    let repos_root = resolve_root(req.repos_root.as_deref(), repos_root);
+    clone::start_clone(&repos_root, req)
+        .map_err(|e| ServiceError::BadRequest(format!("Failed to start clone: {}", e)))
+}
+
+pub fn clone_status(id: &str) -> ServiceResult<CloneStatus> {
+    clone::clone_status(id).ok_or_else(|| clone_not_found(id))
+}
+
+pub fn cancel_clone(id: &str) -> ServiceResult<CloneStatus> {
+    clone::cancel_clone(id).ok_or_else(|| clone_not_found(id))
+}
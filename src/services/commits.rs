@@ -0,0 +1,279 @@
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::git::repository::GitRepository;
+use crate::models::{
+    Commit, CommitMetric, CommitsBetweenResponse, SigningConfig, Tag, TagCreateRequest,
+};
+use crate::services::{internal, open_repo, ServiceError, ServiceResult};
+use crate::utils::{get_repo_path, normalize_sha};
+
+/// Commit history of one branch. `mode=local` (used by graph "All branches"
+/// mode) only marks commits with that branch; other modes use the fuller
+/// branch-head annotations.
+pub fn get_commits(
+    repos_root: &Path,
+    repo: &str,
+    branch: Option<&str>,
+    limit: Option<usize>,
+    mode: Option<&str>,
+) -> ServiceResult<Vec<Commit>> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let branch_name = branch.unwrap_or("HEAD");
+    let limit = limit.unwrap_or(2000);
+
+    let mut commits = if mode.unwrap_or("full").eq_ignore_ascii_case("local") {
+        git_repo.get_commits_local(branch_name, limit)
+    } else {
+        git_repo.get_commits(branch_name, limit)
+    }
+    .map_err(|e| internal("Failed to get commits", e))?;
+
+    git_repo.annotate_signatures(&mut commits);
+    Ok(commits)
+}
+
+/// One commit with its verified signature
+pub fn get_commit(repos_root: &Path, repo: &str, reference: &str) -> ServiceResult<Commit> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let spec = normalize_sha(reference.trim());
+    git_repo
+        .repo
+        .revparse_single(&spec)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|e| ServiceError::NotFound(format!("Unknown revision {}: {}", spec, e)))?;
+    git_repo
+        .get_commit(&spec)
+        .map_err(|e| internal("Failed to get commit", e))
+}
+
+/// Aggregated all-branches commit history for graph "All" mode.
+/// Walks per-branch local histories and merges the results by SHA so the
+/// frontend can render per-branch lanes without issuing one request per branch.
+pub fn get_commits_all_branches(
+    repos_root: &Path,
+    repo: &str,
+    limit: Option<usize>,
+) -> ServiceResult<Vec<Commit>> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let repo_path = get_repo_path(repo, repos_root);
+
+    // Discover all local + remote branches so the aggregation matches what the
+    // branch picker shows in the frontend.
+    let branches = git_repo
+        .get_branch_info()
+        .map_err(|e| internal("Failed to get branches", e))?
+        .branches;
+    if branches.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let max_total = limit.unwrap_or(2000);
+    let branch_count = branches.len().max(1);
+
+    // Calculate per-branch limit with a minimum to ensure meaningful history.
+    // Without a minimum, repos with many branches (e.g., 300) would only get
+    // ~6 commits per branch (2000/300), and after SHA deduplication across
+    // branches that share commits, you could end up with very few unique commits.
+    // The final truncation to max_total keeps the result bounded.
+    const MIN_PER_BRANCH: usize = 50;
+    // Keep per-branch limits reasonable so very large repos don't explode.
+    let per_branch_limit = (max_total / branch_count).clamp(MIN_PER_BRANCH, 500);
+
+    // Use a Mutex-protected HashMap for thread-safe accumulation
+    // HashSet for branch membership checks (O(1) vs O(N) for Vec::contains)
+    let combined: Mutex<HashMap<String, (Commit, HashSet<String>)>> = Mutex::new(HashMap::new());
+
+    // Process branches in parallel using rayon
+    branches.par_iter().try_for_each(|branch| {
+        // Each thread opens its own repo connection (libgit2 pattern)
+        let git_repo = GitRepository::open(&repo_path)
+            .map_err(|e| internal("Failed to open repository", e))?;
+
+        let commits_for_branch = git_repo
+            .get_commits_local(branch, per_branch_limit)
+            .map_err(|e| {
+                ServiceError::Internal(format!(
+                    "Failed to get commits for branch {}: {}",
+                    branch, e
+                ))
+            })?;
+
+        let mut map = combined.lock().unwrap();
+        for commit in commits_for_branch {
+            let sha = commit.sha.clone();
+            let entry = map.entry(sha).or_insert_with(|| (commit, HashSet::new()));
+            entry.1.insert(branch.clone());
+        }
+
+        Ok::<(), ServiceError>(())
+    })?;
+
+    let mut all_commits: Vec<Commit> = combined
+        .into_inner()
+        .unwrap()
+        .into_values()
+        .map(|(mut commit, branch_set)| {
+            commit.branches = branch_set.into_iter().collect();
+            commit
+        })
+        .collect();
+
+    // Sort newest-first by date string (RFC3339) which compares lexicographically.
+    all_commits.sort_by(|a, b| b.date.cmp(&a.date));
+    all_commits.truncate(max_total);
+
+    git_repo.annotate_signatures(&mut all_commits);
+    Ok(all_commits)
+}
+
+pub fn get_commits_between(
+    repos_root: &Path,
+    repo: &str,
+    from: &str,
+    to: &str,
+) -> ServiceResult<CommitsBetweenResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let from_sha = normalize_sha(from);
+    let to_sha = normalize_sha(to);
+
+    if git_repo.is_ancestor(&to_sha, &from_sha).unwrap_or(false) {
+        let count = git_repo
+            .count_commits_between(&from_sha, &to_sha)
+            .unwrap_or(0);
+        return Ok(CommitsBetweenResponse {
+            count: count as i32,
+            note: None,
+            from: Some(from_sha),
+            to: Some(to_sha),
+            error: None,
+        });
+    }
+
+    if git_repo.is_ancestor(&from_sha, &to_sha).unwrap_or(false) {
+        return Ok(CommitsBetweenResponse {
+            count: 0,
+            note: Some("Creation commit is after current commit".to_string()),
+            from: Some(from_sha),
+            to: Some(to_sha),
+            error: None,
+        });
+    }
+
+    Ok(CommitsBetweenResponse {
+        count: -1,
+        note: Some("Could not find common ancestor".to_string()),
+        from: Some(from_sha),
+        to: Some(to_sha),
+        error: None,
+    })
+}
+
+/// Size metrics for the most recent commits of a branch, computed in parallel
+pub fn get_commit_metrics(
+    repos_root: &Path,
+    repo: &str,
+    branch: Option<&str>,
+    limit: Option<usize>,
+) -> ServiceResult<Vec<CommitMetric>> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let repo_path = get_repo_path(repo, repos_root);
+    let branch_name = branch.unwrap_or("HEAD");
+    let limit = limit.unwrap_or(100);
+
+    // Use libgit2 revwalk instead of spawning git log
+    let target = git_repo.repo.revparse_single(branch_name).map_err(|e| {
+        ServiceError::Internal(format!("Failed to resolve branch '{}': {}", branch_name, e))
+    })?;
+
+    let mut revwalk = git_repo
+        .repo
+        .revwalk()
+        .map_err(|e| internal("Failed to create revwalk", e))?;
+    revwalk
+        .push(target.id())
+        .map_err(|e| internal("Failed to start revwalk", e))?;
+    revwalk
+        .set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)
+        .map_err(|e| internal("Failed to set revwalk sorting", e))?;
+
+    let oids: Vec<git2::Oid> = revwalk.take(limit).filter_map(|r| r.ok()).collect();
+
+    let metrics = oids
+        .par_iter()
+        .map_init(
+            // Each rayon worker opens its own repo connection
+            || GitRepository::open(&repo_path).ok(),
+            |git_repo, oid| {
+                // Use libgit2-based stats instead of spawning git show
+                let (files_changed, lines_changed) = git_repo
+                    .as_ref()
+                    .and_then(|r| r.get_commit_stats(*oid).ok())
+                    .unwrap_or((0, 0));
+
+                let weight = lines_changed as f64 * 0.7 + files_changed as f64 * 10.0 * 0.3;
+
+                CommitMetric {
+                    sha: oid.to_string(),
+                    lines_changed,
+                    files_changed,
+                    impact_score: weight * weight,
+                }
+            },
+        )
+        .collect();
+
+    Ok(metrics)
+}
+
+pub fn get_tags(repos_root: &Path, repo: &str) -> ServiceResult<Vec<Tag>> {
+    let git_repo = open_repo(repos_root, repo)?;
+
+    let tags_out = git_repo.run_git(&[
+        "for-each-ref",
+        "refs/tags",
+        "--format=%(refname:short)%00%(objectname)%00%(creatordate:iso-strict)",
+    ]);
+
+    let mut tags = Vec::new();
+    if let Ok(output) = tags_out {
+        for line in output.lines() {
+            let parts: Vec<&str> = line.split('\0').collect();
+            if parts.len() >= 3 {
+                tags.push(Tag {
+                    name: parts[0].to_string(),
+                    sha: parts[1].to_string(),
+                    date: parts[2].to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(tags)
+}
+
+/// Create a tag and return it as listed by `get_tags`
+pub fn create_tag(repos_root: &Path, repo: &str, req: &TagCreateRequest) -> ServiceResult<Tag> {
+    let name = req.name.trim();
+    if name.is_empty() {
+        return Err(ServiceError::BadRequest("tag name required".to_string()));
+    }
+
+    let git_repo = open_repo(repos_root, repo)?;
+    git_repo
+        .create_tag(req)
+        .map_err(|e| ServiceError::BadRequest(format!("Failed to create tag: {}", e)))?;
+
+    get_tags(repos_root, repo)?
+        .into_iter()
+        .find(|tag| tag.name == name)
+        .ok_or_else(|| ServiceError::Internal(format!("Tag {} not found after creation", name)))
+}
+
+pub fn get_signing_config(repos_root: &Path, repo: &str) -> ServiceResult<SigningConfig> {
+    open_repo(repos_root, repo)?
+        .signing_config()
+        .map_err(|e| internal("Failed to read signing config", e))
+}
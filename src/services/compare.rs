@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use crate::git::compare::DiffSide;
+use crate::git::repository::GitRepository;
+use crate::models::{CompareFilesResponse, DiffResponse, DiffSettings};
+use crate::services::diff::{diff_config, diff_response};
+use crate::services::{internal, open_repo, ServiceError, ServiceResult};
+
+/// Resolve the `from` or `to` end of a comparison
+fn diff_side(git_repo: &GitRepository, name: &str, spec: Option<&str>) -> ServiceResult<DiffSide> {
+    let spec = spec
+        .map(str::trim)
+        .filter(|spec| !spec.is_empty())
+        .ok_or_else(|| {
+            ServiceError::BadRequest(format!("Missing {} (a revision, INDEX or WORKTREE)", name))
+        })?;
+    git_repo
+        .diff_side(spec)
+        .map_err(|e| ServiceError::NotFound(e.to_string()))
+}
+
+/// Files changed between any two of a revision, the index and the working tree
+pub fn get_compare_files(
+    repos_root: &Path,
+    repo: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> ServiceResult<CompareFilesResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let from = diff_side(&git_repo, "from", from)?;
+    let to = diff_side(&git_repo, "to", to)?;
+    let files = git_repo
+        .changed_files_between(from, to)
+        .map_err(|e| internal("Failed to compare", e))?;
+    Ok(CompareFilesResponse {
+        from: from.label(),
+        to: to.label(),
+        files,
+    })
+}
+
+/// Diff of one file between any two of a revision, the index and the working
+/// tree. `old_path` is the file's path on the `from` side after a rename.
+pub fn get_compare_diff(
+    repos_root: &Path,
+    repo: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+    path: &str,
+    old_path: Option<&str>,
+    settings: &DiffSettings,
+) -> ServiceResult<DiffResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let config = diff_config(&git_repo, settings)?;
+    let from = diff_side(&git_repo, "from", from)?;
+    let to = diff_side(&git_repo, "to", to)?;
+    let file_diff = git_repo
+        .file_diff_between(from, to, path, old_path, &config)
+        .map_err(|e| internal("Failed to get diff", e))?;
+    Ok(diff_response(file_diff, &config))
+}
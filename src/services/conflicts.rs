@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::Path;
+
+use crate::models::{ConflictFileResponse, ConflictsResponse, SuccessResponse};
+use crate::services::{internal, open_repo, ServiceError, ServiceResult};
+use crate::utils::get_repo_path;
+
+/// Paths left unmerged in the index, classified by which sides are present
+pub fn get_conflicts(repos_root: &Path, repo: &str) -> ServiceResult<ConflictsResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+
+    let files = git_repo
+        .conflicted_files()
+        .map_err(|e| internal("Failed to read conflicts", e))?;
+
+    Ok(ConflictsResponse {
+        has_conflicts: !files.is_empty(),
+        files,
+    })
+}
+
+/// Base, ours and theirs versions of a conflicted file plus its current
+/// (marker-annotated) working tree content
+pub fn get_conflict_file(
+    repos_root: &Path,
+    repo: &str,
+    path: &str,
+) -> ServiceResult<ConflictFileResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let full_path = get_repo_path(repo, repos_root).join(path);
+
+    // Get Base (common ancestor), Mine (current/ours), and Theirs (incoming)
+    // :1: = base, :2: = ours, :3: = theirs
+    let base = git_repo
+        .run_git(&["show", &format!(":1:{}", path)])
+        .unwrap_or_default();
+
+    let mine = git_repo
+        .run_git(&["show", &format!(":2:{}", path)])
+        .unwrap_or_else(|_| {
+            // Fallback to working tree
+            fs::read_to_string(&full_path).unwrap_or_default()
+        });
+
+    let theirs = git_repo
+        .run_git(&["show", &format!(":3:{}", path)])
+        .unwrap_or_default();
+
+    // Get current conflicted content (working tree)
+    let result = fs::read_to_string(&full_path).unwrap_or_default();
+
+    Ok(ConflictFileResponse {
+        base,
+        mine,
+        theirs,
+        result,
+        file_path: path.to_string(),
+    })
+}
+
+/// Write the resolved content of a file and stage it
+pub fn resolve_conflict(
+    repos_root: &Path,
+    repo: &str,
+    path: &str,
+    content: &str,
+) -> ServiceResult<SuccessResponse> {
+    if path.is_empty() || content.is_empty() {
+        return Err(ServiceError::BadRequest("path and content required".to_string()));
+    }
+
+    let git_repo = open_repo(repos_root, repo)?;
+
+    // Write resolved content to file
+    let full_path = get_repo_path(repo, repos_root).join(path);
+    if let Some(parent) = full_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| internal("Failed to create directory", e))?;
+    }
+    fs::write(&full_path, content).map_err(|e| internal("Failed to write file", e))?;
+
+    // Stage the resolved file
+    git_repo
+        .run_git(&["add", path])
+        .map_err(|e| internal("Failed to stage file", e))?;
+
+    Ok(SuccessResponse { success: true })
+}
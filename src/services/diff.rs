@@ -0,0 +1,189 @@
+use std::path::Path;
+
+use crate::git::diff_options::{hunk_part, DiffConfig, DiffParent};
+use crate::git::intraline::hunk_spans;
+use crate::git::repository::{DiffHunkData, FileDiff, GitRepository};
+use crate::models::{DiffHunk, DiffResponse, DiffSettings};
+use crate::services::{internal, open_repo, ServiceError, ServiceResult};
+use crate::utils::normalize_sha;
+
+/// The hunks of one page: those from hunk `offset`, line `line_offset` that fit
+/// in `max_lines` lines (0 for no limit), with their index in the whole diff
+/// and the lines of it left out before them
+struct HunkPage {
+    hunks: Vec<(usize, usize, DiffHunkData)>,
+    truncated: bool,
+    next_hunk: Option<usize>,
+    next_line: Option<usize>,
+}
+
+fn page_hunks(
+    hunks: Vec<DiffHunkData>,
+    offset: usize,
+    line_offset: usize,
+    max_lines: usize,
+) -> HunkPage {
+    let mut budget = if max_lines == 0 {
+        usize::MAX
+    } else {
+        max_lines
+    };
+    let mut page = HunkPage {
+        hunks: Vec::new(),
+        truncated: false,
+        next_hunk: None,
+        next_line: None,
+    };
+    for (index, hunk) in hunks.into_iter().enumerate().skip(offset) {
+        let len = hunk.lines.len();
+        let from = if index == offset {
+            line_offset.min(len)
+        } else {
+            0
+        };
+        // The rest of a split hunk comes with a header of its own
+        let size = if from == 0 { len } else { 1 + len - from };
+        if size <= budget {
+            budget -= size;
+            let hunk = if from == 0 {
+                hunk
+            } else {
+                hunk_part(&hunk, from..len)
+            };
+            page.hunks.push((index, from.saturating_sub(1), hunk));
+            continue;
+        }
+        page.truncated = true;
+        if page.hunks.is_empty() {
+            // A hunk longer than a whole page is split; the next page goes on
+            // from `next_line`
+            let from = from.max(1);
+            let to = from + budget.saturating_sub(1).max(1);
+            let part = hunk_part(&hunk, from..to);
+            page.hunks.push((index, from - 1, part));
+            page.next_line = Some(to);
+        }
+        page.next_hunk = Some(index);
+        break;
+    }
+    page
+}
+
+/// Convert a file diff into the response shape shared by all diff endpoints,
+/// keeping the page of hunks `config` asks for
+pub fn diff_response(file_diff: FileDiff, config: &DiffConfig) -> DiffResponse {
+    let total_hunks = file_diff.hunks.len();
+    let page = page_hunks(
+        file_diff.hunks,
+        config.hunk_offset,
+        config.hunk_line,
+        config.max_lines,
+    );
+    let hunks: Vec<DiffHunk> = page
+        .hunks
+        .into_iter()
+        .map(|(i, line_offset, h)| DiffHunk {
+            old_start: h.old_start,
+            old_count: h.old_count,
+            new_start: h.new_start,
+            new_count: h.new_count,
+            spans: hunk_spans(&h.lines),
+            lines: h.lines,
+            line_start: i as i32,
+            line_offset,
+        })
+        .collect();
+
+    // A partial diff keeps the file header and the text of the page's hunks
+    let diff = if config.hunk_offset > 0 || config.hunk_line > 0 || page.truncated {
+        let mut text: String = file_diff
+            .diff
+            .lines()
+            .take_while(|line| !line.starts_with("@@"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        for line in hunks.iter().flat_map(|hunk| &hunk.lines) {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text
+    } else {
+        file_diff.diff
+    };
+
+    DiffResponse {
+        diff,
+        hunks,
+        file_path: file_diff.file_path,
+        submodule: file_diff.submodule,
+        combined_parents: None,
+        binary: file_diff.binary,
+        total_hunks,
+        truncated: page.truncated,
+        next_hunk: page.next_hunk,
+        next_line: page.next_line,
+    }
+}
+
+/// Validate the diff options of a request against the repository's limits
+pub fn diff_config(git_repo: &GitRepository, settings: &DiffSettings) -> ServiceResult<DiffConfig> {
+    git_repo
+        .diff_config(settings)
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))
+}
+
+/// Parse a parent selection ("1".."N" or "combined") and check it against the
+/// commit's parents
+pub fn diff_parent(commit: &git2::Commit<'_>, parent: Option<&str>) -> ServiceResult<DiffParent> {
+    DiffParent::parse(parent)
+        .and_then(|parent| parent.resolve(commit.parent_count()))
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))
+}
+
+/// Diff of one file: in commit `reference` against one of its parents (the
+/// first by default, or all of them combined) when given, otherwise in the
+/// working directory (`staged` selects index vs HEAD).
+pub fn get_diff(
+    repos_root: &Path,
+    repo: &str,
+    path: &str,
+    reference: Option<&str>,
+    parent: Option<&str>,
+    staged: bool,
+    settings: &DiffSettings,
+) -> ServiceResult<DiffResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let config = diff_config(&git_repo, settings)?;
+
+    let mut combined_parents = None;
+    let file_diff = match reference {
+        Some(reference) => {
+            let spec = normalize_sha(reference.trim());
+            let commit = git_repo
+                .repo
+                .revparse_single(&spec)
+                .and_then(|obj| obj.peel_to_commit())
+                .map_err(|e| ServiceError::NotFound(format!("Unknown revision {}: {}", spec, e)))?;
+            let parent = diff_parent(&commit, parent)?;
+            if parent == DiffParent::Combined {
+                combined_parents = Some(commit.parent_count());
+            }
+            git_repo
+                .get_file_diff(&commit.id().to_string(), path, parent, &config)
+                .map_err(|e| internal("Failed to get diff", e))?
+        }
+        None => git_repo
+            .get_working_diff(path, staged, &config)
+            .map_err(|e| internal("Failed to get working diff", e))?,
+    };
+
+    let mut response = diff_response(file_diff, &config);
+    if combined_parents.is_some() {
+        // Intraline spans assume a single +/- column
+        for hunk in &mut response.hunks {
+            hunk.spans.clear();
+        }
+        response.combined_parents = combined_parents;
+    }
+    Ok(response)
+}
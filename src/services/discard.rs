@@ -0,0 +1,42 @@
+use std::path::Path;
+
+use crate::models::{
+    CleanRequest, DiscardRequest, DiscardResponse, DiscardRestoreRequest, DiscardSnapshot,
+};
+use crate::services::{open_repo, ServiceError, ServiceResult};
+
+/// Discard working tree changes of files, or of selected hunks/lines of one file
+pub fn discard(
+    repos_root: &Path,
+    repo: &str,
+    req: &DiscardRequest,
+) -> ServiceResult<DiscardResponse> {
+    open_repo(repos_root, repo)?
+        .discard_changes(req)
+        .map_err(|e| ServiceError::BadRequest(format!("Failed to discard changes: {}", e)))
+}
+
+/// Remove untracked and/or ignored files
+pub fn clean(repos_root: &Path, repo: &str, req: &CleanRequest) -> ServiceResult<DiscardResponse> {
+    open_repo(repos_root, repo)?
+        .clean(req)
+        .map_err(|e| ServiceError::BadRequest(format!("Failed to clean: {}", e)))
+}
+
+/// Snapshots of discarded content, newest first
+pub fn get_snapshots(repos_root: &Path, repo: &str) -> ServiceResult<Vec<DiscardSnapshot>> {
+    open_repo(repos_root, repo)?
+        .discard_snapshots()
+        .map_err(|e| ServiceError::Internal(format!("Failed to list snapshots: {}", e)))
+}
+
+/// Put discarded content back into the working tree
+pub fn restore(
+    repos_root: &Path,
+    repo: &str,
+    req: &DiscardRestoreRequest,
+) -> ServiceResult<DiscardResponse> {
+    open_repo(repos_root, repo)?
+        .restore_snapshot(req.snapshot.as_deref(), &req.paths, req.force)
+        .map_err(|e| ServiceError::BadRequest(format!("Failed to restore: {}", e)))
+}
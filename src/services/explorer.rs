@@ -0,0 +1,89 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::models::{BrowseFolderResponse, SuccessResponse};
+use crate::services::{ServiceError, ServiceResult};
+use crate::utils::get_repo_path;
+
+/// Reveal a file of the repository in the OS file manager. When the file no
+/// longer exists, the directory that contained it is opened instead.
+pub fn open_explorer(repos_root: &Path, repo: &str, path: &str) -> ServiceResult<SuccessResponse> {
+    let full_path = get_repo_path(repo, repos_root).join(path);
+
+    if full_path.exists() {
+        open_file(&full_path);
+    } else if let Some(dir_path) = full_path.parent() {
+        if dir_path.exists() {
+            open_directory(dir_path, &full_path);
+        }
+    }
+
+    Ok(SuccessResponse { success: true })
+}
+
+/// Open a native folder selection dialog on the host OS and
+/// return the chosen path as a string. Intended for local use.
+pub fn browse_projects_root() -> ServiceResult<BrowseFolderResponse> {
+    match rfd::FileDialog::new().pick_folder() {
+        Some(path) => Ok(BrowseFolderResponse {
+            path: path.to_string_lossy().to_string(),
+        }),
+        None => Err(ServiceError::BadRequest(
+            "Folder selection was cancelled".to_string(),
+        )),
+    }
+}
+
+fn open_file(full_path: &Path) {
+    #[cfg(target_os = "windows")]
+    {
+        let path_str = full_path.to_string_lossy().replace('/', "\\");
+        let _ = Command::new("explorer")
+            .args(&["/select,", &path_str])
+            .spawn();
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let _ = Command::new("open")
+            .args(&["-R", full_path.to_str().unwrap_or("")])
+            .spawn();
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(dir) = full_path.parent() {
+            let _ = Command::new("xdg-open")
+                .arg(dir.to_str().unwrap_or(""))
+                .spawn();
+        }
+    }
+}
+
+fn open_directory(dir_path: &Path, full_path: &Path) {
+    #[cfg(target_os = "windows")]
+    {
+        let path_str = full_path.to_string_lossy().replace('/', "\\");
+        let mut cmd = Command::new("explorer");
+        cmd.args(&["/select,", &path_str]);
+        if cmd.spawn().is_err() {
+            // If file doesn't exist, just open the directory
+            let dir_str = dir_path.to_string_lossy().replace('/', "\\");
+            let _ = Command::new("explorer").arg(&dir_str).spawn();
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let _ = Command::new("open")
+            .args(&["-R", full_path.to_str().unwrap_or("")])
+            .spawn();
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let _ = Command::new("xdg-open")
+            .arg(dir_path.to_str().unwrap_or(""))
+            .spawn();
+    }
+}
@@ -0,0 +1,63 @@
+use std::path::Path;
+
+use crate::git::fetch_scheduler::{FetchTrigger, FETCH_SCHEDULER};
+use crate::models::{FetchPolicy, RepoFetchStatus, ShallowFetchRequest, ShallowInfo};
+use crate::services::{internal, open_repo, ServiceError, ServiceResult};
+use crate::utils::get_repo_path;
+
+/// Fetch all remotes now, through the scheduler so it is recorded in the fetch status
+pub fn fetch_repo(repos_root: &Path, repo: &str) -> ServiceResult<String> {
+    let repo_path = get_repo_path(repo, repos_root);
+    if !repo_path.is_dir() {
+        return Err(ServiceError::NotFound("Repository not found".to_string()));
+    }
+
+    match FETCH_SCHEDULER.fetch(&repo_path, FetchTrigger::Manual) {
+        Some(Ok(())) => Ok("Fetch successful".to_string()),
+        Some(Err(e)) => Err(internal("Failed to fetch", e)),
+        // A background fetch for this repo is already running
+        None => Ok("Fetch already in progress".to_string()),
+    }
+}
+
+/// Fetch status (last fetch, duration, result, next run) for every repo under repos_root
+pub fn get_fetch_status(repos_root: &Path) -> ServiceResult<Vec<RepoFetchStatus>> {
+    FETCH_SCHEDULER
+        .statuses(repos_root)
+        .map_err(|e| internal("Failed to read repos directory", e))
+}
+
+/// Background fetch policy for a repository
+pub fn get_fetch_policy(repos_root: &Path, repo: &str) -> FetchPolicy {
+    FETCH_SCHEDULER.policy(&get_repo_path(repo, repos_root))
+}
+
+/// Change the background fetch interval for a repository, or turn it off
+pub fn set_fetch_policy(
+    repos_root: &Path,
+    repo: &str,
+    enabled: bool,
+    interval_secs: Option<u64>,
+) -> ServiceResult<FetchPolicy> {
+    FETCH_SCHEDULER
+        .set_policy(&get_repo_path(repo, repos_root), enabled, interval_secs)
+        .map_err(|e| internal("Failed to update fetch policy", e))
+}
+
+/// Whether the repository is a shallow clone, and where its history stops
+pub fn get_shallow_info(repos_root: &Path, repo: &str) -> ServiceResult<ShallowInfo> {
+    Ok(open_repo(repos_root, repo)?.shallow_info())
+}
+
+/// Deepen a shallow clone by N commits or to a date, or unshallow it completely
+pub fn deepen_history(
+    repos_root: &Path,
+    repo: &str,
+    req: &ShallowFetchRequest,
+) -> ServiceResult<ShallowInfo> {
+    let git_repo = open_repo(repos_root, repo)?;
+    git_repo
+        .deepen(req)
+        .map_err(|e| internal("Failed to fetch more history", e))?;
+    Ok(git_repo.shallow_info())
+}
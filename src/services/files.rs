@@ -0,0 +1,256 @@
+use base64::{engine::general_purpose, Engine as _};
+use moka::sync::Cache;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::git::diff_options::DiffParent;
+use crate::git::repository::GitRepository;
+use crate::models::{FileChange, FileCreationInfo, ImageResponse};
+use crate::services::diff::diff_parent;
+use crate::services::{internal, open_repo, ServiceError, ServiceResult};
+use crate::utils::{get_repo_path, normalize_sha};
+
+/// Commits beyond this threshold are considered a "large repo", and some
+/// expensive operations like finding a file's creation date will be disabled.
+const LARGE_REPO_COMMIT_THRESHOLD: usize = 20_000;
+
+/// Cache for file creation info with automatic TTL-based eviction.
+/// Key: (repo_path, file_path), Value: FileCreationInfo
+/// Entries expire after 60 seconds, max 10,000 entries.
+static FILE_CREATION_CACHE: Lazy<Cache<(String, String), FileCreationInfo>> = Lazy::new(|| {
+    Cache::builder()
+        .time_to_live(Duration::from_secs(60))
+        .max_capacity(10_000)
+        .build()
+});
+
+fn creation_not_found() -> FileCreationInfo {
+    FileCreationInfo {
+        found: false,
+        commit_sha: None,
+        commit_date: None,
+        date: None,
+        message: Some("File creation commit not found".to_string()),
+        error: None,
+    }
+}
+
+fn compute_file_creation_info(
+    git_repo: &GitRepository,
+    repo_key: &str,
+    path: &str,
+) -> FileCreationInfo {
+    let cache_key = (repo_key.to_string(), path.to_string());
+
+    // Try cache first (moka handles TTL automatically)
+    if let Some(cached) = FILE_CREATION_CACHE.get(&cache_key) {
+        return cached;
+    }
+
+    // For large repos, disable this expensive operation
+    if let Ok(count) = git_repo.count_all_commits() {
+        if count > LARGE_REPO_COMMIT_THRESHOLD {
+            let info = FileCreationInfo {
+                found: false,
+                commit_sha: None,
+                commit_date: None,
+                date: None,
+                message: Some("Disabled for performance on large repositories".to_string()),
+                error: Some("disabled".to_string()),
+            };
+            FILE_CREATION_CACHE.insert(cache_key, info.clone());
+            return info;
+        }
+    }
+
+    let log_out = git_repo
+        .run_git(&[
+            "log",
+            "--diff-filter=A",
+            "--format=%H%x1f%aI%x1f%s",
+            "--reverse",
+            "--",
+            path,
+        ])
+        .unwrap_or_default();
+
+    let info = match log_out.lines().next() {
+        Some(line) => {
+            let parts: Vec<&str> = line.split('\x1f').collect();
+            if parts.len() >= 2 {
+                FileCreationInfo {
+                    found: true,
+                    commit_sha: Some(parts[0].to_string()),
+                    commit_date: Some(parts[1].to_string()),
+                    date: Some(parts[1].to_string()),
+                    message: parts.get(2).map(|s| s.to_string()),
+                    error: None,
+                }
+            } else {
+                creation_not_found()
+            }
+        }
+        None => creation_not_found(),
+    };
+
+    // Insert into cache (moka handles eviction automatically)
+    FILE_CREATION_CACHE.insert(cache_key, info.clone());
+
+    info
+}
+
+fn require_path(path: Option<&str>) -> ServiceResult<&str> {
+    match path {
+        Some(path) if !path.is_empty() => Ok(path),
+        _ => Err(ServiceError::BadRequest("path parameter is required".to_string())),
+    }
+}
+
+/// Raw contents of a file at a revision
+fn show_blob(git_repo: &GitRepository, reference: Option<&str>, path: &str) -> ServiceResult<Vec<u8>> {
+    let ref_sha = normalize_sha(reference.unwrap_or("HEAD"));
+    git_repo
+        .run_git_bytes(&["show", &format!("{}:{}", ref_sha, path)])
+        .map_err(|e| ServiceError::NotFound(format!("File not found: {}", e)))
+}
+
+/// Names of the entries of a tree (optionally limited to `path`)
+pub fn get_files(
+    repos_root: &Path,
+    repo: &str,
+    reference: Option<&str>,
+    path: Option<&str>,
+) -> ServiceResult<Vec<String>> {
+    let git_repo = open_repo(repos_root, repo)?;
+
+    let mut args = vec!["ls-tree", "--name-only", reference.unwrap_or("HEAD")];
+    if let Some(path) = path.filter(|p| !p.is_empty()) {
+        args.push("--");
+        args.push(path);
+    }
+
+    let output = git_repo
+        .run_git(&args)
+        .map_err(|e| internal("Failed to list files", e))?;
+
+    Ok(output.lines().map(|l| l.trim().to_string()).collect())
+}
+
+/// Files changed by a commit against one of its parents (the first by
+/// default), or for a merge with `parent` "combined", the files that differ
+/// from all of them
+pub fn get_commit_files(
+    repos_root: &Path,
+    repo: &str,
+    reference: Option<&str>,
+    parent: Option<&str>,
+) -> ServiceResult<Vec<FileChange>> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let ref_sha = normalize_sha(reference.unwrap_or("HEAD"));
+    let commit = git2::Oid::from_str(&ref_sha).and_then(|oid| git_repo.repo.find_commit(oid));
+    let parent = match commit {
+        Ok(commit) => diff_parent(&commit, parent)?,
+        // An unknown commit is reported by the listing itself
+        Err(_) => DiffParent::default(),
+    };
+
+    git_repo
+        .get_commit_changed_files(&ref_sha, parent)
+        .map_err(|e| internal("Failed to get commit files", e))
+}
+
+/// Contents of a file at a revision. The bytes are returned untouched; each
+/// front end picks a binary-safe encoding.
+pub fn get_file(
+    repos_root: &Path,
+    repo: &str,
+    reference: Option<&str>,
+    path: Option<&str>,
+) -> ServiceResult<Vec<u8>> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let path = require_path(path)?;
+    show_blob(&git_repo, reference, path)
+}
+
+/// The commit that added a file
+pub fn get_file_creation(
+    repos_root: &Path,
+    repo: &str,
+    path: Option<&str>,
+) -> ServiceResult<FileCreationInfo> {
+    let path = require_path(path)?;
+    let git_repo = open_repo(repos_root, repo)?;
+    let repo_key = get_repo_path(repo, repos_root).to_string_lossy().to_string();
+
+    Ok(compute_file_creation_info(&git_repo, &repo_key, path))
+}
+
+/// Parse the JSON-encoded array of paths taken by [`get_file_creation_batch`].
+/// Example: `["a.txt","b.txt"]`
+pub fn parse_path_list(paths: &str) -> ServiceResult<Vec<String>> {
+    let paths: Vec<String> = serde_json::from_str(paths).map_err(|e| {
+        ServiceError::BadRequest(format!(
+            "invalid paths parameter (expected JSON array of strings): {}",
+            e
+        ))
+    })?;
+
+    if paths.is_empty() {
+        return Err(ServiceError::BadRequest(
+            "paths parameter must be a non-empty JSON array".to_string(),
+        ));
+    }
+    Ok(paths)
+}
+
+/// Creation info for many files at once, keyed by path
+pub fn get_file_creation_batch(
+    repos_root: &Path,
+    repo: &str,
+    paths: &[String],
+) -> ServiceResult<HashMap<String, FileCreationInfo>> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let repo_key = get_repo_path(repo, repos_root).to_string_lossy().to_string();
+
+    // The compute function contains the conditional logic and caching,
+    // so we can just call it in a loop.
+    Ok(paths
+        .iter()
+        .filter(|path| !path.is_empty())
+        .map(|path| {
+            let info = compute_file_creation_info(&git_repo, &repo_key, path);
+            (path.clone(), info)
+        })
+        .collect())
+}
+
+/// An image at a revision as a data URL
+pub fn get_image(
+    repos_root: &Path,
+    repo: &str,
+    reference: Option<&str>,
+    path: Option<&str>,
+) -> ServiceResult<ImageResponse> {
+    let path = require_path(path)?;
+    let git_repo = open_repo(repos_root, repo)?;
+    let data = show_blob(&git_repo, reference, path)?;
+
+    let base64_data = general_purpose::STANDARD.encode(&data);
+
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    let mime_type = mime_guess::from_ext(&ext)
+        .first_or_octet_stream()
+        .to_string();
+
+    Ok(ImageResponse {
+        data: format!("data:{};base64,{}", mime_type, base64_data),
+        mime_type,
+    })
+}
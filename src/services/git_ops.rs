@@ -0,0 +1,242 @@
+use std::path::Path;
+
+use crate::git::hooks::HookFailure;
+use crate::git::stash::StashPart;
+use crate::models::{
+    DiffResponse, DiffSettings, GitOperationResponse, HookRun, StashApplyResponse, StashDetail,
+    StashListResponse, StashPushRequest,
+};
+use crate::services::diff::{diff_config, diff_response};
+use crate::services::{open_repo, ServiceError, ServiceResult};
+
+const DEFAULT_STASH: &str = "stash@{0}";
+
+fn operation_result(result: anyhow::Result<String>, message: String) -> GitOperationResponse {
+    match result {
+        Ok(output) => GitOperationResponse {
+            success: true,
+            message: Some(message),
+            output: Some(output),
+            error: None,
+            hooks: Vec::new(),
+        },
+        Err(e) => GitOperationResponse {
+            success: false,
+            message: None,
+            output: None,
+            error: Some(e.to_string()),
+            hooks: Vec::new(),
+        },
+    }
+}
+
+/// `operation_result` for commands that run hooks, reporting the hook runs
+/// whether the command succeeded or a hook stopped it
+fn hooked_operation_result(
+    result: anyhow::Result<(String, Vec<HookRun>)>,
+    message: String,
+) -> GitOperationResponse {
+    match result {
+        Ok((output, hooks)) => GitOperationResponse {
+            hooks,
+            ..operation_result(Ok(output), message)
+        },
+        Err(e) => match e.downcast::<HookFailure>() {
+            Ok(failure) => GitOperationResponse {
+                success: false,
+                message: None,
+                output: None,
+                error: Some(failure.to_string()),
+                hooks: failure.hooks,
+            },
+            Err(e) => operation_result(Err(e), message),
+        },
+    }
+}
+
+fn no_changes(output: Option<String>) -> GitOperationResponse {
+    GitOperationResponse {
+        success: false,
+        message: Some("No local changes to stash".to_string()),
+        output,
+        error: None,
+        hooks: Vec::new(),
+    }
+}
+
+/// Pull changes from the upstream branch
+pub fn pull_repo(repos_root: &Path, repo: &str) -> ServiceResult<GitOperationResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+
+    if !git_repo.has_upstream().unwrap_or(false) {
+        return Ok(GitOperationResponse {
+            success: false,
+            message: Some("No upstream branch configured".to_string()),
+            output: None,
+            error: Some(
+                "No upstream branch configured. Push first or set upstream manually.".to_string(),
+            ),
+            hooks: Vec::new(),
+        });
+    }
+
+    Ok(operation_result(git_repo.pull(), "Pull successful".to_string()))
+}
+
+/// Push to the upstream branch, setting it up on first push
+pub fn push_repo(repos_root: &Path, repo: &str) -> ServiceResult<GitOperationResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+
+    if git_repo.has_upstream().unwrap_or(false) {
+        return Ok(hooked_operation_result(
+            git_repo.push(),
+            "Push successful".to_string(),
+        ));
+    }
+
+    let branch = git_repo.get_current_branch().unwrap_or_else(|_| "HEAD".to_string());
+    Ok(hooked_operation_result(
+        git_repo.push_set_upstream(&branch),
+        format!("Pushed and set upstream for branch '{}'", branch),
+    ))
+}
+
+pub fn stash_list(repos_root: &Path, repo: &str) -> ServiceResult<StashListResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let entries = git_repo.stash_list().unwrap_or_default();
+    Ok(StashListResponse { entries })
+}
+
+/// Stash local changes as described by `req`
+pub fn stash_push(
+    repos_root: &Path,
+    repo: &str,
+    req: &StashPushRequest,
+) -> ServiceResult<GitOperationResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+
+    // `--all` may only have ignored files to stash
+    if !req.all && !git_repo.has_uncommitted_changes().unwrap_or(false) {
+        return Ok(no_changes(None));
+    }
+
+    Ok(match git_repo.stash_push(req) {
+        // git exits successfully when the pathspec matches nothing to stash
+        Ok(output) if output.contains("No local changes to save") => no_changes(Some(output)),
+        result => operation_result(result, "Changes stashed".to_string()),
+    })
+}
+
+/// Pop a stash (the most recent one by default). `restore_index` also restores
+/// the staged state (`--index`).
+pub fn stash_pop(
+    repos_root: &Path,
+    repo: &str,
+    stash_ref: Option<&str>,
+    restore_index: bool,
+) -> ServiceResult<StashApplyResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+
+    if git_repo.stash_list().map(|l| l.is_empty()).unwrap_or(true) {
+        return Ok(StashApplyResponse {
+            success: false,
+            message: Some("No stashes to pop".to_string()),
+            output: None,
+            error: None,
+            conflicts: Vec::new(),
+            dropped: false,
+        });
+    }
+
+    let stash_ref = stash_ref.unwrap_or(DEFAULT_STASH);
+    Ok(git_repo.stash_outcome(
+        || git_repo.stash_pop(stash_ref, restore_index),
+        format!("Stash {} popped", stash_ref),
+        true,
+    ))
+}
+
+/// Apply a stash and keep it
+pub fn stash_apply(
+    repos_root: &Path,
+    repo: &str,
+    stash_ref: Option<&str>,
+    restore_index: bool,
+) -> ServiceResult<StashApplyResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let stash_ref = stash_ref.unwrap_or(DEFAULT_STASH);
+    Ok(git_repo.stash_outcome(
+        || git_repo.stash_apply(stash_ref, restore_index),
+        format!("Stash {} applied", stash_ref),
+        false,
+    ))
+}
+
+/// Create a branch at a stash's base commit and restore the stash there
+pub fn stash_branch(
+    repos_root: &Path,
+    repo: &str,
+    branch: &str,
+    stash_ref: Option<&str>,
+) -> ServiceResult<StashApplyResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let stash_ref = stash_ref.unwrap_or(DEFAULT_STASH);
+    Ok(git_repo.stash_outcome(
+        || git_repo.stash_branch(branch, stash_ref),
+        format!("Created branch {} from {}", branch.trim(), stash_ref),
+        true,
+    ))
+}
+
+pub fn stash_drop(
+    repos_root: &Path,
+    repo: &str,
+    stash_ref: Option<&str>,
+) -> ServiceResult<GitOperationResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let stash_ref = stash_ref.unwrap_or(DEFAULT_STASH);
+    Ok(operation_result(
+        git_repo.stash_drop(stash_ref),
+        format!("Stash {} dropped", stash_ref),
+    ))
+}
+
+/// Show a stash: base commit, branch and the files it touches, split by index,
+/// worktree and untracked parts
+pub fn stash_show(
+    repos_root: &Path,
+    repo: &str,
+    stash_ref: Option<&str>,
+) -> ServiceResult<StashDetail> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let stash_ref = stash_ref.unwrap_or(DEFAULT_STASH);
+    git_repo.stash_detail(stash_ref).map_err(|e| {
+        ServiceError::BadRequest(format!("Failed to read stash {}: {}", stash_ref, e))
+    })
+}
+
+/// Diff of a single file in one part ("combined" by default, "index",
+/// "worktree" or "untracked") of a stash
+pub fn stash_diff(
+    repos_root: &Path,
+    repo: &str,
+    stash_ref: Option<&str>,
+    path: &str,
+    part: Option<&str>,
+    settings: &DiffSettings,
+) -> ServiceResult<DiffResponse> {
+    let part = match part {
+        None => StashPart::Combined,
+        Some(p) => StashPart::parse(p)
+            .ok_or_else(|| ServiceError::BadRequest(format!("Unknown stash part: {}", p)))?,
+    };
+
+    let git_repo = open_repo(repos_root, repo)?;
+    let config = diff_config(&git_repo, settings)?;
+    let stash_ref = stash_ref.unwrap_or(DEFAULT_STASH);
+    let file_diff = git_repo
+        .stash_file_diff(stash_ref, path, part, &config)
+        .map_err(|e| ServiceError::BadRequest(format!("Failed to get stash diff: {}", e)))?;
+
+    Ok(diff_response(file_diff, &config))
+}
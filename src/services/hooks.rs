@@ -0,0 +1,24 @@
+use std::path::Path;
+
+use crate::models::{HookInfo, HookListResponse, HookToggleRequest};
+use crate::services::{internal, open_repo, ServiceError, ServiceResult};
+
+/// Hooks installed in the repository's hooks directory
+pub fn get_hooks(repos_root: &Path, repo: &str) -> ServiceResult<HookListResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    git_repo
+        .list_hooks()
+        .map_err(|e| internal("Failed to list hooks", e))
+}
+
+/// Enable or disable an installed hook
+pub fn set_hook_enabled(
+    repos_root: &Path,
+    repo: &str,
+    req: &HookToggleRequest,
+) -> ServiceResult<HookInfo> {
+    let git_repo = open_repo(repos_root, repo)?;
+    git_repo
+        .set_hook_enabled(&req.name, req.enabled)
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))
+}
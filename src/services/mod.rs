@@ -0,0 +1,131 @@
+//! Transport-neutral implementation of every operation the UI can request.
+//!
+//! The axum handlers and the Tauri commands are thin adapters over these
+//! functions, so the web and desktop builds always behave the same. Service
+//! functions are synchronous; callers run them on a blocking thread.
+
+pub mod branches;
+pub mod clone;
+pub mod commits;
+pub mod compare;
+pub mod conflicts;
+pub mod diff;
+pub mod discard;
+pub mod explorer;
+pub mod fetch;
+pub mod files;
+pub mod git_ops;
+pub mod hooks;
+pub mod patch;
+pub mod rebase;
+pub mod repos;
+pub mod staging;
+pub mod submodules;
+pub mod worktrees;
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::git::hooks::HookFailure;
+use crate::git::repository::GitRepository;
+use crate::models::{CommitLintResult, ErrorResponse, HookRun};
+use crate::utils::get_repo_path;
+
+/// Failure of a service call, classified so each front end can report it
+/// in its own way (HTTP status code, Tauri error string).
+#[derive(Debug)]
+pub enum ServiceError {
+    /// The repository, job or object asked for does not exist
+    NotFound(String),
+    /// The request itself is invalid or cannot be carried out in the current state
+    BadRequest(String),
+    /// A git hook rejected the operation; carries every hook that ran
+    HookFailed(String, Vec<HookRun>),
+    /// The commit message breaks the repository's rules; carries the lint result
+    LintFailed(String, CommitLintResult),
+    /// Anything else
+    Internal(String),
+}
+
+impl ServiceError {
+    pub fn message(&self) -> &str {
+        match self {
+            Self::NotFound(msg)
+            | Self::BadRequest(msg)
+            | Self::HookFailed(msg, _)
+            | Self::LintFailed(msg, _)
+            | Self::Internal(msg) => msg,
+        }
+    }
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+impl From<anyhow::Error> for ServiceError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Internal(e.to_string())
+    }
+}
+
+pub type ServiceResult<T> = Result<T, ServiceError>;
+
+impl From<ServiceError> for ErrorResponse {
+    fn from(e: ServiceError) -> Self {
+        match e {
+            ServiceError::HookFailed(error, hooks) => Self {
+                error,
+                hooks,
+                lint: None,
+            },
+            ServiceError::LintFailed(error, lint) => Self {
+                error,
+                hooks: Vec::new(),
+                lint: Some(lint),
+            },
+            e => Self {
+                error: e.to_string(),
+                hooks: Vec::new(),
+                lint: None,
+            },
+        }
+    }
+}
+
+/// Shorthand for an internal error with a context prefix
+pub(crate) fn internal(context: &str, e: impl fmt::Display) -> ServiceError {
+    ServiceError::Internal(format!("{}: {}", context, e))
+}
+
+/// Like `internal`, but a command stopped by a failing hook becomes `HookFailed`
+pub(crate) fn git_failure(context: &str, e: anyhow::Error) -> ServiceError {
+    match e.downcast::<HookFailure>() {
+        Ok(failure) => ServiceError::HookFailed(failure.to_string(), failure.hooks),
+        Err(e) => internal(context, e),
+    }
+}
+
+/// Open a repository by name, reporting a missing directory as NotFound
+pub fn open_repo(repos_root: &Path, repo: &str) -> ServiceResult<GitRepository> {
+    let path = get_repo_path(repo, repos_root);
+    if !path.exists() {
+        return Err(ServiceError::NotFound(format!("Repository not found: {}", repo)));
+    }
+    GitRepository::open(&path).map_err(|e| internal("Failed to open repository", e))
+}
+
+/// Use a client-supplied root folder when given, otherwise the configured one
+pub fn resolve_root(custom_root: Option<&str>, repos_root: &Path) -> PathBuf {
+    match custom_root {
+        Some(custom_root) => {
+            let path = PathBuf::from(custom_root);
+            path.canonicalize().unwrap_or(path)
+        }
+        None => repos_root.to_path_buf(),
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::Path;
+
+use crate::git::hooks::HookFailure;
+use crate::models::{
+    DiffSettings, FormatPatchRequest, FormatPatchResponse, PatchApplyRequest, PatchApplyResponse,
+    PatchResponse,
+};
+use crate::services::diff::diff_config;
+use crate::services::{open_repo, ServiceError, ServiceResult};
+
+/// Complete patch of a commit (against its first parent) or a range
+pub fn get_patch(
+    repos_root: &Path,
+    repo: &str,
+    reference: &str,
+    settings: &DiffSettings,
+) -> ServiceResult<PatchResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let config = diff_config(&git_repo, settings)?;
+    git_repo
+        .get_patch(reference, &config)
+        .map_err(|e| ServiceError::BadRequest(format!("Failed to get patch: {}", e)))
+}
+
+/// `git format-patch` files for a commit or range, for mailing or attaching
+pub fn format_patch(
+    repos_root: &Path,
+    repo: &str,
+    req: &FormatPatchRequest,
+) -> ServiceResult<FormatPatchResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    git_repo
+        .format_patch(req)
+        .map_err(|e| ServiceError::BadRequest(format!("Failed to export patches: {}", e)))
+}
+
+/// Apply an uploaded diff or mbox to the working tree, the index or as commits
+pub fn apply_patch(
+    repos_root: &Path,
+    repo: &str,
+    req: &PatchApplyRequest,
+) -> ServiceResult<PatchApplyResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    git_repo
+        .apply_patch(req)
+        .map_err(|e| match e.downcast::<HookFailure>() {
+            Ok(failure) => ServiceError::HookFailed(failure.to_string(), failure.hooks),
+            Err(e) => ServiceError::BadRequest(format!("Failed to apply patch: {}", e)),
+        })
+}
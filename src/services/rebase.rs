@@ -0,0 +1,136 @@
+use std::path::Path;
+
+use crate::git::repository::GitRepository;
+use crate::models::{Commit, RebasePlanItem, RebasePlanRequest, RebasePlanResponse, RebasePreview};
+use crate::services::{internal, open_repo, ServiceError, ServiceResult};
+use crate::utils::normalize_sha;
+
+fn ensure_clean(git_repo: &GitRepository, error: &str) -> ServiceResult<()> {
+    let status_out = git_repo.run_git(&["status", "--porcelain"]).unwrap_or_default();
+    if status_out.trim().is_empty() {
+        Ok(())
+    } else {
+        Err(ServiceError::BadRequest(error.to_string()))
+    }
+}
+
+/// Commits that a rebase of `from` (default HEAD) onto `onto` (default main) would replay
+pub fn get_rebase_preview(
+    repos_root: &Path,
+    repo: &str,
+    onto: Option<&str>,
+    from: Option<&str>,
+) -> ServiceResult<RebasePreview> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let onto = onto.unwrap_or("main");
+    let from = from.unwrap_or("HEAD");
+
+    ensure_clean(
+        &git_repo,
+        "Cannot rebase with uncommitted changes. Please commit or stash first.",
+    )?;
+
+    let merge_base = git_repo
+        .run_git(&["merge-base", from, onto])
+        .map_err(|_| ServiceError::BadRequest("Cannot find common ancestor".to_string()))?;
+    let merge_base = normalize_sha(merge_base.trim());
+
+    let format = "%H%x1f%an%x1f%ad%x1f%s%x1e";
+    let commits_out = git_repo
+        .run_git(&[
+            "log",
+            &format!("{}..{}", merge_base, from),
+            &format!("--format={}", format),
+            "--date=iso-strict",
+        ])
+        .map_err(|e| internal("Failed to get commits", e))?;
+
+    let mut commits = Vec::new();
+    for chunk in commits_out.split('\x1e') {
+        let chunk = chunk.trim();
+        if chunk.is_empty() {
+            continue;
+        }
+        let parts: Vec<&str> = chunk.split('\x1f').collect();
+        if parts.len() < 4 {
+            continue;
+        }
+
+        commits.push(Commit {
+            sha: normalize_sha(parts[0].trim()),
+            author: parts[1].trim().to_string(),
+            email: String::new(),
+            date: parts[2].trim().to_string(),
+            message: parts[3].trim().to_string(),
+            parents: Vec::new(),
+            is_merge: false,
+            branches: Vec::new(),
+            primary_branch: None,
+            is_head: None,
+            is_main: None,
+            branch_angle: None,
+            branch_info: None,
+            branch_divergence_point: None,
+            branch_base: None,
+            branch_divergence_age_days: None,
+            is_shallow_boundary: None,
+            signature: None,
+        });
+    }
+
+    Ok(RebasePreview {
+        commits,
+        onto: onto.to_string(),
+        from: from.to_string(),
+        merge_base,
+    })
+}
+
+/// Validate a rebase plan. Only dry runs are supported; executing the plan is
+/// reported as unsupported in the response.
+pub fn post_rebase_plan(
+    repos_root: &Path,
+    repo: &str,
+    req: RebasePlanRequest,
+) -> ServiceResult<RebasePlanResponse> {
+    if req.onto.is_empty() || req.plan.is_empty() {
+        return Err(ServiceError::BadRequest(
+            "onto and plan (array) required".to_string(),
+        ));
+    }
+
+    let git_repo = open_repo(repos_root, repo)?;
+    ensure_clean(&git_repo, "Cannot rebase with uncommitted changes")?;
+
+    if req.dry_run.unwrap_or(false) {
+        let plan = req
+            .plan
+            .into_iter()
+            .map(|item| RebasePlanItem {
+                action: if item.action.is_empty() {
+                    "pick".to_string()
+                } else {
+                    item.action
+                },
+                sha: item.sha,
+                message: item.message,
+            })
+            .collect();
+        return Ok(RebasePlanResponse {
+            success: true,
+            dry_run: Some(true),
+            plan: Some(plan),
+            error: None,
+        });
+    }
+
+    // For actual rebase, return error suggesting manual rebase
+    Ok(RebasePlanResponse {
+        success: false,
+        dry_run: None,
+        plan: None,
+        error: Some(
+            "Interactive rebase execution requires additional setup. Use preview mode to plan your rebase.".to_string(),
+        ),
+    })
+}
@@ -0,0 +1,21 @@
+use std::path::Path;
+
+use crate::git::discovery::{discover_repo_paths, repo_entry};
+use crate::git::worktrees::add_worktree_entries;
+use crate::models::{ConfigResponse, Repo};
+use crate::services::{internal, ServiceResult};
+
+pub fn get_config(repos_root: &Path) -> ConfigResponse {
+    ConfigResponse {
+        repos_root: repos_root.to_string_lossy().to_string(),
+    }
+}
+
+/// Repositories under repos_root (the folder itself and its direct children),
+/// followed by their linked worktrees
+pub fn get_repos(repos_root: &Path) -> ServiceResult<Vec<Repo>> {
+    let paths = discover_repo_paths(repos_root)
+        .map_err(|e| internal("Failed to read repos directory", e))?;
+    let repos = paths.iter().map(|path| repo_entry(path)).collect();
+    Ok(add_worktree_entries(repos))
+}
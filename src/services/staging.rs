@@ -0,0 +1,172 @@
+use std::path::Path;
+
+use crate::git::commit::is_explicit_author;
+use crate::git::staging::LineSelection;
+use crate::models::{
+    CommitLintRequest, CommitLintResult, CommitLintRules, CommitRequest, CommitResult,
+    DiffSettings, HunkSelection, IgnorePatternRequest, IgnorePatternResponse, StatusResponse,
+    SuccessResponse,
+};
+use crate::services::diff::diff_config;
+use crate::services::{git_failure, internal, open_repo, ServiceError, ServiceResult};
+
+/// Changed paths; with `include_ignored`, ignored paths and their matching rule too
+pub fn get_status(
+    repos_root: &Path,
+    repo: &str,
+    include_ignored: bool,
+) -> ServiceResult<StatusResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let files = git_repo
+        .status_files(include_ignored)
+        .map_err(|e| internal("Failed to get status", e))?;
+    Ok(StatusResponse { files })
+}
+
+/// Append a pattern to `.gitignore` or `info/exclude` (`dryRun` only previews it)
+pub fn add_ignore_pattern(
+    repos_root: &Path,
+    repo: &str,
+    req: &IgnorePatternRequest,
+) -> ServiceResult<IgnorePatternResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    git_repo
+        .add_ignore_pattern(req)
+        .map_err(|e| ServiceError::BadRequest(e.to_string()))
+}
+
+/// Stage a whole file, or only the selected hunks/lines of its unstaged diff
+/// as computed with `settings`. With `intent_to_add` and no selection, an
+/// untracked file is only marked intent-to-add (`git add -N`).
+pub fn stage(
+    repos_root: &Path,
+    repo: &str,
+    path: &str,
+    hunks: Option<&[usize]>,
+    lines: Option<&[HunkSelection]>,
+    intent_to_add: bool,
+    settings: &DiffSettings,
+) -> ServiceResult<SuccessResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let config = diff_config(&git_repo, settings)?;
+
+    match LineSelection::from_request(hunks, lines, config) {
+        Some(selection) if selection.is_empty() => {}
+        Some(selection) => git_repo
+            .stage_path(path, Some(&selection))
+            .map_err(|e| internal("Failed to stage lines", e))?,
+        None if intent_to_add => git_repo
+            .intent_to_add(path)
+            .map_err(|e| ServiceError::BadRequest(format!("Failed to add {}: {}", path, e)))?,
+        None => git_repo
+            .stage_path(path, None)
+            .map_err(|e| internal("Failed to stage file", e))?,
+    }
+
+    Ok(SuccessResponse { success: true })
+}
+
+/// Unstage a whole file, or only the selected hunks/lines of its staged diff
+/// as computed with `settings`
+pub fn unstage(
+    repos_root: &Path,
+    repo: &str,
+    path: &str,
+    hunks: Option<&[usize]>,
+    lines: Option<&[HunkSelection]>,
+    settings: &DiffSettings,
+) -> ServiceResult<SuccessResponse> {
+    let git_repo = open_repo(repos_root, repo)?;
+    let config = diff_config(&git_repo, settings)?;
+
+    match LineSelection::from_request(hunks, lines, config) {
+        Some(selection) if selection.is_empty() => {}
+        Some(selection) => git_repo
+            .unstage_path(path, Some(&selection))
+            .map_err(|e| internal("Failed to unstage lines", e))?,
+        None => git_repo
+            .unstage_path(path, None)
+            .map_err(|e| internal("Failed to unstage file", e))?,
+    }
+
+    Ok(SuccessResponse { success: true })
+}
+
+/// Create a commit from the index (or from `paths` only) and describe it
+pub fn commit(repos_root: &Path, repo: &str, req: &CommitRequest) -> ServiceResult<CommitResult> {
+    if req.message.trim().is_empty() {
+        return Err(ServiceError::BadRequest("commit message required".to_string()));
+    }
+    if let Some(author) = req.author.as_deref().map(str::trim) {
+        if !author.is_empty() && !is_explicit_author(author) {
+            return Err(ServiceError::BadRequest(format!(
+                "author must look like \"Name <email>\": {}",
+                author
+            )));
+        }
+    }
+
+    let git_repo = open_repo(repos_root, repo)?;
+    if !req.skip_lint {
+        let lint = git_repo
+            .lint_commit_message(&req.message, None, req.sign_off)
+            .map_err(|e| internal("Failed to check commit message", e))?;
+        if !lint.valid {
+            let problems: Vec<String> = lint
+                .violations
+                .iter()
+                .map(|v| format!("{}:{} {}", v.line, v.start + 1, v.message))
+                .collect();
+            return Err(ServiceError::LintFailed(
+                format!(
+                    "Commit message breaks the repository's rules: {}",
+                    problems.join("; ")
+                ),
+                lint,
+            ));
+        }
+    }
+
+    git_repo
+        .create_commit(req)
+        .map_err(|e| git_failure("Failed to create commit", e))
+}
+
+/// Check a commit message against the repository's rules without committing
+pub fn lint_commit_message(
+    repos_root: &Path,
+    repo: &str,
+    req: &CommitLintRequest,
+) -> ServiceResult<CommitLintResult> {
+    let git_repo = open_repo(repos_root, repo)?;
+    git_repo
+        .lint_commit_message(&req.message, req.branch.as_deref(), req.sign_off)
+        .map_err(|e| internal("Failed to check commit message", e))
+}
+
+pub fn get_commit_lint_rules(repos_root: &Path, repo: &str) -> ServiceResult<CommitLintRules> {
+    let git_repo = open_repo(repos_root, repo)?;
+    git_repo
+        .commit_lint_rules()
+        .map_err(|e| internal("Failed to read commit message rules", e))
+}
+
+pub fn set_commit_lint_rules(
+    repos_root: &Path,
+    repo: &str,
+    rules: &CommitLintRules,
+) -> ServiceResult<CommitLintRules> {
+    if let Some(pattern) = rules.ticket_pattern.as_deref().filter(|p| !p.is_empty()) {
+        regex::Regex::new(pattern).map_err(|e| {
+            ServiceError::BadRequest(format!("Invalid ticket pattern: {}", e))
+        })?;
+    }
+
+    let git_repo = open_repo(repos_root, repo)?;
+    git_repo
+        .set_commit_lint_rules(rules)
+        .map_err(|e| internal("Failed to save commit message rules", e))?;
+    git_repo
+        .commit_lint_rules()
+        .map_err(|e| internal("Failed to read commit message rules", e))
+}
@@ -0,0 +1,36 @@
+use std::path::Path;
+
+use crate::models::{SubmoduleInfo, SubmoduleUpdateRequest};
+use crate::services::{internal, open_repo, ServiceResult};
+
+#[derive(Debug, Clone, Copy)]
+pub enum SubmoduleAction {
+    Init,
+    Update,
+    Sync,
+}
+
+/// List submodules with recorded vs checked-out commits and dirty state
+pub fn get_submodules(repos_root: &Path, repo: &str) -> ServiceResult<Vec<SubmoduleInfo>> {
+    open_repo(repos_root, repo)?
+        .list_submodules()
+        .map_err(|e| internal("Failed to list submodules", e))
+}
+
+/// Run `git submodule init/update/sync` and return the refreshed submodule list
+pub fn run_submodule_action(
+    repos_root: &Path,
+    repo: &str,
+    req: &SubmoduleUpdateRequest,
+    action: SubmoduleAction,
+) -> ServiceResult<Vec<SubmoduleInfo>> {
+    let git_repo = open_repo(repos_root, repo)?;
+    match action {
+        SubmoduleAction::Init => git_repo.submodule_init(&req.paths),
+        SubmoduleAction::Update => git_repo.submodule_update(&req.paths, req.init, req.recursive),
+        SubmoduleAction::Sync => git_repo.submodule_sync(&req.paths, req.recursive),
+    }?;
+    git_repo
+        .list_submodules()
+        .map_err(|e| internal("Failed to list submodules", e))
+}
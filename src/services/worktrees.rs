@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use crate::models::{WorktreeAddRequest, WorktreeInfo, WorktreeRemoveRequest};
+use crate::services::{internal, open_repo, ServiceResult};
+
+pub enum WorktreeAction {
+    Add(WorktreeAddRequest),
+    Remove(WorktreeRemoveRequest),
+    Prune,
+}
+
+/// List the repository's worktrees (main first)
+pub fn get_worktrees(repos_root: &Path, repo: &str) -> ServiceResult<Vec<WorktreeInfo>> {
+    open_repo(repos_root, repo)?
+        .list_worktrees()
+        .map_err(|e| internal("Failed to list worktrees", e))
+}
+
+/// Add, remove or prune worktrees and return the refreshed list
+pub fn run_worktree_action(
+    repos_root: &Path,
+    repo: &str,
+    action: WorktreeAction,
+) -> ServiceResult<Vec<WorktreeInfo>> {
+    let git_repo = open_repo(repos_root, repo)?;
+    match &action {
+        WorktreeAction::Add(req) => git_repo.add_worktree(req),
+        WorktreeAction::Remove(req) => git_repo.remove_worktree(&req.path, req.force),
+        WorktreeAction::Prune => git_repo.prune_worktrees(),
+    }?;
+    git_repo
+        .list_worktrees()
+        .map_err(|e| internal("Failed to list worktrees", e))
+}
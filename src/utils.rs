@@ -1,7 +1,7 @@
 use regex::Regex;
 use std::path::{Path, PathBuf};
 
-pub fn get_repo_path(name: &str, repos_root: &PathBuf) -> PathBuf {
+pub fn get_repo_path(name: &str, repos_root: &Path) -> PathBuf {
     let candidate = Path::new(name);
     if candidate.is_absolute() {
         // When the client sends an absolute path, trust it directly.
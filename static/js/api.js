@@ -77,6 +77,20 @@ async function fetchFallback(path, options) {
   }
 }
 
+/**
+ * Diff options (context, whitespace, algorithm, size limits, hunk page...)
+ * forwarded from a query string
+ * @param {Object} queryParams - Parsed query parameters
+ * @returns {Object} Only the options that are set
+ */
+function diffSettings(queryParams) {
+  const settings = {};
+  for (const key of ['context', 'whitespace', 'ignoreBlankLines', 'algorithm', 'indentHeuristic', 'maxFileSize', 'maxLines', 'hunkOffset', 'hunkLine']) {
+    if (queryParams[key]) settings[key] = queryParams[key];
+  }
+  return settings;
+}
+
 /**
  * Map API path to Tauri command and arguments
  * @param {string} path - API path
@@ -211,6 +225,26 @@ function mapPathToCommand(path, options) {
       repo: decodeURIComponent(pathParts[2])
     };
   }
+  // /api/repos/:repo/tags/create (POST)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'tags' && pathParts[4] === 'create') {
+    command = 'create_tag';
+    args = { params: Object.assign({}, body, { repo: decodeURIComponent(pathParts[2]) }) };
+  }
+  // /api/repos/:repo/signing
+  else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'signing') {
+    command = 'get_signing_config';
+    args.repo = decodeURIComponent(pathParts[2]);
+  }
+  // /api/repos/:repo/commit/detail
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'commit' && pathParts[4] === 'detail') {
+    command = 'get_commit_detail';
+    args = {
+      params: {
+        repo: decodeURIComponent(pathParts[2]),
+        ref: queryParams.ref
+      }
+    };
+  }
   // /api/repos/:repo/files
   else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'files') {
     command = 'get_files';
@@ -238,6 +272,7 @@ function mapPathToCommand(path, options) {
     if (queryParams.path !== undefined && queryParams.path !== null) {
       params.path = queryParams.path;
     }
+    if (queryParams.parent) params.parent = queryParams.parent;
     args = { params };
   }
   // /api/repos/:repo/file
@@ -289,7 +324,32 @@ function mapPathToCommand(path, options) {
     if (queryParams.path) params.path = queryParams.path;
     // Rust expects "ref" (not "ref_") due to serde rename
     if (queryParams.ref) params.ref = queryParams.ref;
+    if (queryParams.parent) params.parent = queryParams.parent;
     if (queryParams.staged) params.staged = queryParams.staged;
+    Object.assign(params, diffSettings(queryParams));
+    args = { params };
+  }
+  // /api/repos/:repo/compare/files
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'compare' && pathParts[4] === 'files') {
+    command = 'get_compare_files';
+    const params = {
+      repo: decodeURIComponent(pathParts[2])
+    };
+    if (queryParams.from) params.from = queryParams.from;
+    if (queryParams.to) params.to = queryParams.to;
+    args = { params };
+  }
+  // /api/repos/:repo/compare/diff
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'compare' && pathParts[4] === 'diff') {
+    command = 'get_compare_diff';
+    const params = {
+      repo: decodeURIComponent(pathParts[2])
+    };
+    if (queryParams.from) params.from = queryParams.from;
+    if (queryParams.to) params.to = queryParams.to;
+    if (queryParams.path) params.path = queryParams.path;
+    if (queryParams.oldPath) params.oldPath = queryParams.oldPath;
+    Object.assign(params, diffSettings(queryParams));
     args = { params };
   }
   // /api/repos/:repo/status
@@ -297,9 +357,15 @@ function mapPathToCommand(path, options) {
     command = 'get_status';
     // get_status takes repo as a direct String parameter, not a struct
     args = {
-      repo: decodeURIComponent(pathParts[2])
+      repo: decodeURIComponent(pathParts[2]),
+      ignored: queryParams.ignored === 'true' || queryParams.ignored === true
     };
   }
+  // /api/repos/:repo/ignore (POST)
+  else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'ignore') {
+    command = 'add_ignore_pattern';
+    args = { params: Object.assign({}, body, { repo: decodeURIComponent(pathParts[2]) }) };
+  }
   // /api/repos/:repo/stage (POST)
   else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'stage') {
     command = 'stage';
@@ -309,6 +375,9 @@ function mapPathToCommand(path, options) {
     if (body) {
       req.path = body.path;
       req.hunks = body.hunks;
+      req.lines = body.lines;
+      req.intentToAdd = body.intentToAdd;
+      Object.assign(req, diffSettings(body));
     }
     args = { req };
   }
@@ -320,25 +389,188 @@ function mapPathToCommand(path, options) {
     };
     if (body) {
       req.path = body.path;
+      req.hunks = body.hunks;
+      req.lines = body.lines;
+      Object.assign(req, diffSettings(body));
     }
     args = { req };
   }
+  // /api/repos/:repo/discard (POST)
+  else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'discard') {
+    command = 'discard_changes';
+    args = { params: Object.assign({}, body, { repo: decodeURIComponent(pathParts[2]) }) };
+  }
+  // /api/repos/:repo/clean (POST)
+  else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'clean') {
+    command = 'clean';
+    args = { params: Object.assign({}, body, { repo: decodeURIComponent(pathParts[2]) }) };
+  }
+  // /api/repos/:repo/discard/snapshots
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'discard' && pathParts[4] === 'snapshots') {
+    command = 'get_discard_snapshots';
+    args.repo = decodeURIComponent(pathParts[2]);
+  }
+  // /api/repos/:repo/discard/restore (POST)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'discard' && pathParts[4] === 'restore') {
+    command = 'restore_discarded';
+    args = { params: Object.assign({}, body, { repo: decodeURIComponent(pathParts[2]) }) };
+  }
   // /api/repos/:repo/commit (POST)
   else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'commit') {
     command = 'commit';
-    const req = {
-      repo: decodeURIComponent(pathParts[2])
-    };
-    if (body) {
-      req.message = body.message;
+    args = { req: Object.assign({}, body, { repo: decodeURIComponent(pathParts[2]) }) };
+  }
+  // /api/repos/:repo/commit/lint (POST)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'commit' && pathParts[4] === 'lint') {
+    command = 'lint_commit_message';
+    args = { params: Object.assign({}, body, { repo: decodeURIComponent(pathParts[2]) }) };
+  }
+  // /api/repos/:repo/commit/rules (GET reads, POST updates)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'commit' && pathParts[4] === 'rules') {
+    if ((options.method || 'GET').toUpperCase() === 'POST') {
+      command = 'set_commit_lint_rules';
+      args = { params: Object.assign({}, body, { repo: decodeURIComponent(pathParts[2]) }) };
+    } else {
+      command = 'get_commit_lint_rules';
+      args.repo = decodeURIComponent(pathParts[2]);
     }
-    args = { req };
+  }
+  // /api/repos/:repo/patch
+  else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'patch') {
+    command = 'get_patch';
+    args = {
+      params: Object.assign({ repo: decodeURIComponent(pathParts[2]), ref: queryParams.ref }, diffSettings(queryParams))
+    };
+  }
+  // /api/repos/:repo/patch/export (POST)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'patch' && pathParts[4] === 'export') {
+    command = 'format_patch';
+    args = { params: Object.assign({}, body, { repo: decodeURIComponent(pathParts[2]) }) };
+  }
+  // /api/repos/:repo/patch/apply (POST)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'patch' && pathParts[4] === 'apply') {
+    command = 'apply_patch';
+    args = { params: Object.assign({}, body, { repo: decodeURIComponent(pathParts[2]) }) };
+  }
+  // /api/repos/:repo/hooks
+  else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'hooks') {
+    command = 'get_hooks';
+    args.repo = decodeURIComponent(pathParts[2]);
+  }
+  // /api/repos/:repo/hooks/toggle (POST)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'hooks' && pathParts[4] === 'toggle') {
+    command = 'set_hook_enabled';
+    args = { params: Object.assign({}, body, { repo: decodeURIComponent(pathParts[2]) }) };
   }
   // /api/repos/:repo/fetch (POST)
   else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'fetch') {
     command = 'fetch_repo';
     args.repo = decodeURIComponent(pathParts[2]);
   }
+  // /api/repos/:repo/fetch/policy (GET reads, POST updates)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'fetch' && pathParts[4] === 'policy') {
+    if ((options.method || 'GET').toUpperCase() === 'POST') {
+      command = 'set_fetch_policy';
+      const params = {
+        repo: decodeURIComponent(pathParts[2]),
+        enabled: body ? body.enabled !== false : true
+      };
+      if (body && body.intervalSecs !== undefined) params.interval_secs = body.intervalSecs;
+      args = { params };
+    } else {
+      command = 'get_fetch_policy';
+      args.repo = decodeURIComponent(pathParts[2]);
+    }
+  }
+  // /api/repos/:repo/shallow
+  else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'shallow') {
+    command = 'get_shallow_info';
+    args.repo = decodeURIComponent(pathParts[2]);
+  }
+  // /api/repos/:repo/shallow/deepen (POST)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'shallow' && pathParts[4] === 'deepen') {
+    command = 'deepen_history';
+    args = {
+      params: {
+        repo: decodeURIComponent(pathParts[2]),
+        deepen: (body && body.deepen) || null,
+        since: (body && body.since) || null,
+        unshallow: !!(body && body.unshallow)
+      }
+    };
+  }
+  // /api/fetch/status
+  else if (pathParts.length === 3 && pathParts[0] === 'api' && pathParts[1] === 'fetch' && pathParts[2] === 'status') {
+    command = 'get_fetch_status';
+    args = { params: { repos_root: queryParams.repos_root || null } };
+  }
+  // /api/clone (POST)
+  else if (pathParts.length === 2 && pathParts[0] === 'api' && pathParts[1] === 'clone') {
+    command = 'start_clone';
+    args = { req: body || {} };
+  }
+  // /api/clone/:id
+  else if (pathParts.length === 3 && pathParts[0] === 'api' && pathParts[1] === 'clone') {
+    command = 'get_clone_status';
+    args.id = decodeURIComponent(pathParts[2]);
+  }
+  // /api/clone/:id/cancel (POST)
+  else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'clone' && pathParts[3] === 'cancel') {
+    command = 'cancel_clone';
+    args.id = decodeURIComponent(pathParts[2]);
+  }
+  // /api/repos/:repo/submodules
+  else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'submodules') {
+    command = 'get_submodules';
+    args.repo = decodeURIComponent(pathParts[2]);
+  }
+  // /api/repos/:repo/submodules/:action (POST init, update, sync)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'submodules' && ['init', 'update', 'sync'].includes(pathParts[4])) {
+    command = `submodule_${pathParts[4]}`;
+    args = {
+      params: {
+        repo: decodeURIComponent(pathParts[2]),
+        paths: (body && body.paths) || [],
+        init: !!(body && body.init),
+        recursive: !!(body && body.recursive)
+      }
+    };
+  }
+  // /api/repos/:repo/worktrees
+  else if (pathParts.length === 4 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'worktrees') {
+    command = 'get_worktrees';
+    args.repo = decodeURIComponent(pathParts[2]);
+  }
+  // /api/repos/:repo/worktrees/add (POST)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'worktrees' && pathParts[4] === 'add') {
+    command = 'add_worktree';
+    args = {
+      params: {
+        repo: decodeURIComponent(pathParts[2]),
+        path: body ? body.path : '',
+        branch: (body && body.branch) || null,
+        commit: (body && body.commit) || null,
+        new_branch: (body && body.newBranch) || null,
+        force: !!(body && body.force)
+      }
+    };
+  }
+  // /api/repos/:repo/worktrees/remove (POST)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'worktrees' && pathParts[4] === 'remove') {
+    command = 'remove_worktree';
+    args = {
+      params: {
+        repo: decodeURIComponent(pathParts[2]),
+        path: body ? body.path : '',
+        force: !!(body && body.force)
+      }
+    };
+  }
+  // /api/repos/:repo/worktrees/prune (POST)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'worktrees' && pathParts[4] === 'prune') {
+    command = 'prune_worktrees';
+    args.repo = decodeURIComponent(pathParts[2]);
+  }
   // /api/repos/:repo/rebase/preview
   else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'rebase' && pathParts[4] === 'preview') {
     command = 'get_rebase_preview';
@@ -411,17 +643,28 @@ function mapPathToCommand(path, options) {
     } else if (queryParams.message) {
       args.message = queryParams.message;
     }
+    if (body) {
+      args.options = body;
+    }
   }
   // /api/repos/:repo/stash/pop (POST)
   else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'stash' && pathParts[4] === 'pop') {
     command = 'stash_pop';
     args.repo = decodeURIComponent(pathParts[2]);
+    if (queryParams.ref) args.stash_ref = queryParams.ref;
+    if (queryParams.index) args.index = queryParams.index === 'true';
   }
   // /api/repos/:repo/stash/apply (POST)
   else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'stash' && pathParts[4] === 'apply') {
     command = 'stash_apply';
     args.repo = decodeURIComponent(pathParts[2]);
     if (queryParams.ref) args.stash_ref = queryParams.ref;
+    if (queryParams.index) args.index = queryParams.index === 'true';
+  }
+  // /api/repos/:repo/stash/branch (POST)
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'stash' && pathParts[4] === 'branch') {
+    command = 'stash_branch';
+    args = { repo: decodeURIComponent(pathParts[2]), req: body || {} };
   }
   // /api/repos/:repo/stash/drop (POST)
   else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'stash' && pathParts[4] === 'drop') {
@@ -429,6 +672,24 @@ function mapPathToCommand(path, options) {
     args.repo = decodeURIComponent(pathParts[2]);
     if (queryParams.ref) args.stash_ref = queryParams.ref;
   }
+  // /api/repos/:repo/stash/show
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'stash' && pathParts[4] === 'show') {
+    command = 'stash_show';
+    args = { params: { repo: decodeURIComponent(pathParts[2]), stash_ref: queryParams.ref } };
+  }
+  // /api/repos/:repo/stash/diff
+  else if (pathParts.length === 5 && pathParts[0] === 'api' && pathParts[1] === 'repos' && pathParts[3] === 'stash' && pathParts[4] === 'diff') {
+    command = 'stash_diff';
+    args = {
+      params: {
+        repo: decodeURIComponent(pathParts[2]),
+        stash_ref: queryParams.ref,
+        path: queryParams.path,
+        part: queryParams.part,
+        ...diffSettings(queryParams),
+      }
+    };
+  }
   else {
     throw new Error(`Unknown API path: ${path}`);
   }
@@ -551,6 +812,11 @@ async function invokeTauriCommand(command, args) {
 
     return result;
   } catch (error) {
+    // Structured errors (e.g. a rejected commit with its hook runs) read the
+    // same as the JSON body the HTTP server sends
+    if (error && typeof error === 'object' && typeof error.error === 'string') {
+      throw new Error(JSON.stringify(error));
+    }
     // Tauri errors can be strings or Error objects
     const errorMessage = error?.message || error?.toString() || String(error) || 'Unknown error';
     throw new Error(errorMessage);
@@ -146,8 +146,8 @@ function updateBranchStatusUI(status) {
 
   // Update sync status
   if (gitOpsSync) {
-    const { ahead, behind, has_upstream } = status;
-    if (!has_upstream) {
+    const { ahead, behind, hasUpstream } = status;
+    if (!hasUpstream) {
       gitOpsSync.textContent = "no upstream";
       gitOpsSync.className = "git-ops-sync";
     } else if (ahead > 0 && behind > 0) {
@@ -168,7 +168,7 @@ function updateBranchStatusUI(status) {
   // Update badges
   updateBadge(pullBadge, status.behind || 0);
   updateBadge(pushBadge, status.ahead || 0);
-  updateBadge(stashBadge, status.stash_count || 0);
+  updateBadge(stashBadge, status.stashCount || 0);
 }
 
 // ============================================================================
@@ -347,6 +347,16 @@ async function doGitStashPop() {
         loadStatus(),
         fetchBranchStatus(true)
       ]);
+    } else if (result.conflicts && result.conflicts.length > 0) {
+      const paths = result.conflicts.map(c => c.path).join(", ");
+      setGitOpsStatus(`Stash kept, conflicts in: ${paths}`, "error", 0);
+
+      // The conflicted files are in the working tree now
+      branchStatusCache = null;
+      await Promise.all([
+        loadStatus(),
+        fetchBranchStatus(true)
+      ]);
     } else {
       setGitOpsStatus(result.error || result.message || "Pop failed", "error");
     }
@@ -114,7 +114,8 @@ async function loadRepos() {
     repos.forEach((r, i) => {
       const opt = document.createElement("option");
       opt.value = r.id;
-      opt.textContent = r.name;
+      // Linked worktrees are listed right after their main repository
+      opt.textContent = r.worktreeOf ? `↳ ${r.name} (worktree)` : r.name;
       if (lastRepoId && r.id === lastRepoId) {
         opt.selected = true;
         state.currentRepo = r.id;